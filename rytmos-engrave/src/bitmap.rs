@@ -0,0 +1,140 @@
+use core::{
+    convert::Infallible,
+    fmt::{self, Write},
+};
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Point, Size},
+    pixelcolor::BinaryColor,
+    Pixel,
+};
+
+/// Monochrome image to draw into off-screen, e.g. to check what a staff looks like without a
+/// display. Pixels drawn outside of it are dropped, like on a display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap<const WIDTH: usize, const HEIGHT: usize> {
+    pixels: [[bool; WIDTH]; HEIGHT],
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Bitmap<WIDTH, HEIGHT> {
+    /// An image with all pixels off.
+    pub fn new() -> Self {
+        Self {
+            pixels: [[false; WIDTH]; HEIGHT],
+        }
+    }
+
+    /// Color of the pixel at the given point, `None` if it lies outside of the image.
+    pub fn pixel(&self, point: Point) -> Option<BinaryColor> {
+        let x = usize::try_from(point.x).ok()?;
+        let y = usize::try_from(point.y).ok()?;
+
+        self.pixels
+            .get(y)
+            .and_then(|row| row.get(x))
+            .map(|&on| BinaryColor::from(on))
+    }
+
+    /// The rows of pixels from top to bottom, `true` for the pixels that are on.
+    pub(crate) fn rows(&self) -> impl Iterator<Item = &[bool]> {
+        self.pixels.iter().map(|row| row.as_slice())
+    }
+
+    /// Writes the image as a plain (P1) PBM, one line per row of pixels with `1` for the pixels
+    /// that are on. Being text, it can be committed and compared line by line.
+    pub fn write_pbm(&self, f: &mut impl Write) -> fmt::Result {
+        writeln!(f, "P1")?;
+        writeln!(f, "{WIDTH} {HEIGHT}")?;
+
+        for row in self.rows() {
+            for &on in row {
+                f.write_char(if on { '1' } else { '0' })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads an image written by `write_pbm`. Returns `None` if the text is not a plain PBM of
+    /// the size of this image.
+    pub fn from_pbm(text: &str) -> Option<Self> {
+        // Comments run from a # to the end of the line
+        let mut tokens = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(|line| line.split_whitespace());
+
+        if tokens.next()? != "P1"
+            || tokens.next()?.parse::<usize>().ok()? != WIDTH
+            || tokens.next()?.parse::<usize>().ok()? != HEIGHT
+        {
+            return None;
+        }
+
+        // Pixels do not need whitespace between them
+        let mut pixels = tokens.flat_map(|token| token.chars());
+        let mut bitmap = Self::new();
+        for row in bitmap.pixels.iter_mut() {
+            for pixel in row.iter_mut() {
+                *pixel = match pixels.next()? {
+                    '0' => false,
+                    '1' => true,
+                    _ => return None,
+                };
+            }
+        }
+
+        match pixels.next() {
+            Some(_) => None,
+            None => Some(bitmap),
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Default for Bitmap<WIDTH, HEIGHT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> OriginDimensions for Bitmap<WIDTH, HEIGHT> {
+    fn size(&self) -> Size {
+        Size::new(WIDTH as u32, HEIGHT as u32)
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> DrawTarget for Bitmap<WIDTH, HEIGHT> {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let area = self.bounding_box();
+
+        for Pixel(point, color) in pixels {
+            if area.contains(point) {
+                self.pixels[point.y as usize][point.x as usize] = color.is_on();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Shows the image with a `#` for every pixel that is on, e.g. in the message of a failing test.
+impl<const WIDTH: usize, const HEIGHT: usize> fmt::Display for Bitmap<WIDTH, HEIGHT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.pixels {
+            for &on in row {
+                f.write_char(if on { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
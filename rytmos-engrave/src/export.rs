@@ -0,0 +1,75 @@
+//! Export of a [`Bitmap`] as an image for documentation or the web. Pixels that are on are
+//! exported as black ink on white paper.
+//!
+//! The engraver draws the pixels of a display through `embedded-graphics`, it has no noteheads,
+//! lines or beams to export as vector shapes. The SVG therefore has the drawn pixels as
+//! rectangles: it stays sharp at any size, but looks like the display.
+
+use core::fmt::Write as _;
+use std::{io, string::String, vec::Vec};
+
+use crate::bitmap::Bitmap;
+
+impl<const WIDTH: usize, const HEIGHT: usize> Bitmap<WIDTH, HEIGHT> {
+    /// The image as SVG, with every pixel as large as `scale` pixels. The pixels that are on in a
+    /// row are joined into rectangles.
+    pub fn to_svg(&self, scale: u32) -> String {
+        let scale = scale.max(1);
+        let (width, height) = (WIDTH, HEIGHT);
+        let mut svg = String::new();
+
+        // Writing to a string does not fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {width} {height}" shape-rendering="crispEdges">"#,
+            width as u32 * scale,
+            height as u32 * scale,
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="{width}" height="{height}" fill="white"/>"#
+        );
+        let _ = write!(svg, r#"<path fill="black" d=""#);
+
+        for (y, row) in self.rows().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if !row[x] {
+                    x += 1;
+                    continue;
+                }
+
+                let run = row[x..].iter().take_while(|&&on| on).count();
+                let _ = write!(svg, "M{x} {y}h{run}v1h-{run}z");
+                x += run;
+            }
+        }
+
+        let _ = writeln!(svg, r#""/>"#);
+        let _ = writeln!(svg, "</svg>");
+
+        svg
+    }
+
+    /// Writes the image as a grayscale PNG, with every pixel as large as `scale` pixels.
+    pub fn write_png(&self, writer: impl io::Write, scale: u32) -> Result<(), png::EncodingError> {
+        let scale = scale.max(1);
+        let mut encoder = png::Encoder::new(writer, WIDTH as u32 * scale, HEIGHT as u32 * scale);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut data = Vec::with_capacity(WIDTH * HEIGHT * (scale * scale) as usize);
+        for row in self.rows() {
+            for _ in 0..scale {
+                for &on in row {
+                    let gray = if on { 0x00 } else { 0xff };
+                    data.extend((0..scale).map(|_| gray));
+                }
+            }
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()
+    }
+}
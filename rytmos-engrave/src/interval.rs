@@ -0,0 +1,351 @@
+use core::ops::{Add, Sub};
+
+use crate::staff::{Accidental, Chord, ChordQuality, ChordSymbol, Key, Music, Note};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalQuality {
+    Diminished,
+    Minor,
+    Perfect,
+    Major,
+    Augmented,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+/// Distance between two notes as it is written, e.g. a major third or a perfect fifth. Keeps
+/// the spelling of transposed notes: C up a major third is E, up a diminished fourth is Fb.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    quality: IntervalQuality,
+    /// 1 for a unison, 8 for an octave, 10 for a tenth
+    number: u8,
+}
+
+impl Interval {
+    pub const PERFECT_UNISON: Self = Self::simple(IntervalQuality::Perfect, 1);
+    pub const AUGMENTED_UNISON: Self = Self::simple(IntervalQuality::Augmented, 1);
+    pub const MINOR_SECOND: Self = Self::simple(IntervalQuality::Minor, 2);
+    pub const MAJOR_SECOND: Self = Self::simple(IntervalQuality::Major, 2);
+    pub const MINOR_THIRD: Self = Self::simple(IntervalQuality::Minor, 3);
+    pub const MAJOR_THIRD: Self = Self::simple(IntervalQuality::Major, 3);
+    pub const PERFECT_FOURTH: Self = Self::simple(IntervalQuality::Perfect, 4);
+    pub const AUGMENTED_FOURTH: Self = Self::simple(IntervalQuality::Augmented, 4);
+    pub const DIMINISHED_FIFTH: Self = Self::simple(IntervalQuality::Diminished, 5);
+    pub const PERFECT_FIFTH: Self = Self::simple(IntervalQuality::Perfect, 5);
+    pub const AUGMENTED_FIFTH: Self = Self::simple(IntervalQuality::Augmented, 5);
+    pub const MINOR_SIXTH: Self = Self::simple(IntervalQuality::Minor, 6);
+    pub const MAJOR_SIXTH: Self = Self::simple(IntervalQuality::Major, 6);
+    pub const DIMINISHED_SEVENTH: Self = Self::simple(IntervalQuality::Diminished, 7);
+    pub const MINOR_SEVENTH: Self = Self::simple(IntervalQuality::Minor, 7);
+    pub const MAJOR_SEVENTH: Self = Self::simple(IntervalQuality::Major, 7);
+    pub const PERFECT_OCTAVE: Self = Self::simple(IntervalQuality::Perfect, 8);
+    pub const MAJOR_NINTH: Self = Self::simple(IntervalQuality::Major, 9);
+
+    /// Semitones of the major or perfect interval of each number within an octave.
+    const MAJOR_SEMITONES: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+    /// Steps on the circle of fifths of the major or perfect interval of each number within an
+    /// octave, e.g. a major second is two fifths up.
+    const MAJOR_FIFTHS: [i32; 7] = [0, 2, 4, -1, 1, 3, 5];
+
+    const fn simple(quality: IntervalQuality, number: u8) -> Self {
+        Self { quality, number }
+    }
+
+    /// Returns `None` for a number of 0, or a quality that the number does not have: unisons,
+    /// fourths, fifths and octaves are perfect, the others major or minor. A unison can not be
+    /// diminished.
+    pub fn new(quality: IntervalQuality, number: u8) -> Option<Self> {
+        let perfect = Self::is_perfect_number(number.checked_sub(1)?);
+
+        let valid = match quality {
+            IntervalQuality::Perfect => perfect,
+            IntervalQuality::Major | IntervalQuality::Minor => !perfect,
+            IntervalQuality::Augmented => true,
+            IntervalQuality::Diminished => number > 1,
+        };
+
+        valid.then_some(Self { quality, number })
+    }
+
+    /// The interval from the lower note up to the upper note. Returns `None` if the upper note is
+    /// lower, or if the interval is more than augmented or diminished.
+    pub fn between(lower: Note, upper: Note) -> Option<Self> {
+        let steps = upper.diatonic_step() - lower.diatonic_step();
+        let number = u8::try_from(steps + 1).ok()?;
+        let simple = steps as usize % 7;
+
+        let major = Self::MAJOR_SEMITONES[simple] + 12 * (steps / 7);
+        let difference = upper.semitones() - lower.semitones() - major;
+
+        let quality = match (Self::is_perfect_number(steps as u8), difference) {
+            (true, -1) => IntervalQuality::Diminished,
+            (true, 0) => IntervalQuality::Perfect,
+            (true, 1) => IntervalQuality::Augmented,
+            (false, -2) => IntervalQuality::Diminished,
+            (false, -1) => IntervalQuality::Minor,
+            (false, 0) => IntervalQuality::Major,
+            (false, 1) => IntervalQuality::Augmented,
+            _ => return None,
+        };
+
+        Self::new(quality, number)
+    }
+
+    /// The interval spanning the given semitones, spelled as within the major scale, with an
+    /// augmented fourth for the tritone.
+    pub fn from_semitones(semitones: u8) -> Self {
+        const SIMPLE: [Interval; 12] = [
+            Interval::PERFECT_UNISON,
+            Interval::MINOR_SECOND,
+            Interval::MAJOR_SECOND,
+            Interval::MINOR_THIRD,
+            Interval::MAJOR_THIRD,
+            Interval::PERFECT_FOURTH,
+            Interval::AUGMENTED_FOURTH,
+            Interval::PERFECT_FIFTH,
+            Interval::MINOR_SIXTH,
+            Interval::MAJOR_SIXTH,
+            Interval::MINOR_SEVENTH,
+            Interval::MAJOR_SEVENTH,
+        ];
+
+        let simple = SIMPLE[semitones as usize % 12];
+        Self {
+            number: simple.number + 7 * (semitones / 12),
+            ..simple
+        }
+    }
+
+    /// Whether intervals with the given number of steps, one less than their number, are perfect
+    /// rather than major or minor.
+    fn is_perfect_number(steps: u8) -> bool {
+        matches!(steps % 7, 0 | 3 | 4)
+    }
+
+    pub fn quality(self) -> IntervalQuality {
+        self.quality
+    }
+
+    pub fn number(self) -> u8 {
+        self.number
+    }
+
+    /// Number of staff positions the interval spans.
+    pub fn steps(self) -> i32 {
+        self.number as i32 - 1
+    }
+
+    pub fn semitones(self) -> i32 {
+        let steps = self.steps();
+        let major = Self::MAJOR_SEMITONES[steps as usize % 7] + 12 * (steps / 7);
+        major + self.alteration()
+    }
+
+    /// Steps on the circle of fifths that the interval moves a key or a note letter.
+    fn fifths(self) -> i32 {
+        Self::MAJOR_FIFTHS[self.steps() as usize % 7] + 7 * self.alteration()
+    }
+
+    /// Semitones the interval differs from the major or perfect interval of its number.
+    fn alteration(self) -> i32 {
+        let perfect = Self::is_perfect_number(self.steps() as u8);
+        match self.quality {
+            IntervalQuality::Diminished if perfect => -1,
+            IntervalQuality::Diminished => -2,
+            IntervalQuality::Minor => -1,
+            IntervalQuality::Perfect | IntervalQuality::Major => 0,
+            IntervalQuality::Augmented => 1,
+        }
+    }
+}
+
+impl Note {
+    /// The note the given interval up or down, spelled by the interval. Returns `None` if that
+    /// needs more than a double sharp or flat.
+    pub fn transpose(self, interval: Interval, direction: Direction) -> Option<Note> {
+        let (steps, semitones) = match direction {
+            Direction::Up => (interval.steps(), interval.semitones()),
+            Direction::Down => (-interval.steps(), -interval.semitones()),
+        };
+
+        let step = self.diatonic_step() + steps;
+        let natural = Note::from_diatonic_step(step, Accidental::Natural);
+        let accidental =
+            Accidental::from_semitones(self.semitones() + semitones - natural.semitones())?;
+
+        Some(Note::from_diatonic_step(step, accidental))
+    }
+}
+
+/// The note the interval up, see `Note::transpose`.
+impl Add<Interval> for Note {
+    type Output = Option<Note>;
+
+    fn add(self, interval: Interval) -> Self::Output {
+        self.transpose(interval, Direction::Up)
+    }
+}
+
+/// The note the interval down, see `Note::transpose`.
+impl Sub<Interval> for Note {
+    type Output = Option<Note>;
+
+    fn sub(self, interval: Interval) -> Self::Output {
+        self.transpose(interval, Direction::Down)
+    }
+}
+
+impl Key {
+    /// The key in the same mode the given interval up or down. Returns `None` if that key has
+    /// more than six sharps or flats.
+    pub fn transpose(self, interval: Interval, direction: Direction) -> Option<Key> {
+        let fifths = match direction {
+            Direction::Up => interval.fifths(),
+            Direction::Down => -interval.fifths(),
+        };
+
+        Key::from_fifths(self.fifths() + fifths, self.mode())
+    }
+}
+
+impl Chord {
+    pub fn transpose(self, interval: Interval, direction: Direction) -> Option<Chord> {
+        let mut notes = [Note::C(Accidental::Natural, 0); Chord::MAX_NOTES];
+        for (transposed, note) in notes.iter_mut().zip(self.notes()) {
+            *transposed = note.transpose(interval, direction)?;
+        }
+
+        Chord::new(&notes[..self.notes().len()])
+    }
+}
+
+impl ChordQuality {
+    /// Intervals of the notes of the chord above its root, starting with the root itself.
+    pub fn intervals(self) -> &'static [Interval] {
+        use Interval as I;
+
+        match self {
+            ChordQuality::Major => &[I::PERFECT_UNISON, I::MAJOR_THIRD, I::PERFECT_FIFTH],
+            ChordQuality::Minor => &[I::PERFECT_UNISON, I::MINOR_THIRD, I::PERFECT_FIFTH],
+            ChordQuality::Augmented => &[I::PERFECT_UNISON, I::MAJOR_THIRD, I::AUGMENTED_FIFTH],
+            ChordQuality::Diminished => &[I::PERFECT_UNISON, I::MINOR_THIRD, I::DIMINISHED_FIFTH],
+            ChordQuality::Suspended2 => &[I::PERFECT_UNISON, I::MAJOR_SECOND, I::PERFECT_FIFTH],
+            ChordQuality::Suspended4 => &[I::PERFECT_UNISON, I::PERFECT_FOURTH, I::PERFECT_FIFTH],
+            ChordQuality::Power => &[I::PERFECT_UNISON, I::PERFECT_FIFTH],
+            ChordQuality::Sixth => &[
+                I::PERFECT_UNISON,
+                I::MAJOR_THIRD,
+                I::PERFECT_FIFTH,
+                I::MAJOR_SIXTH,
+            ],
+            ChordQuality::MinorSixth => &[
+                I::PERFECT_UNISON,
+                I::MINOR_THIRD,
+                I::PERFECT_FIFTH,
+                I::MAJOR_SIXTH,
+            ],
+            ChordQuality::Dominant7 => &[
+                I::PERFECT_UNISON,
+                I::MAJOR_THIRD,
+                I::PERFECT_FIFTH,
+                I::MINOR_SEVENTH,
+            ],
+            ChordQuality::Major7 => &[
+                I::PERFECT_UNISON,
+                I::MAJOR_THIRD,
+                I::PERFECT_FIFTH,
+                I::MAJOR_SEVENTH,
+            ],
+            ChordQuality::Minor7 => &[
+                I::PERFECT_UNISON,
+                I::MINOR_THIRD,
+                I::PERFECT_FIFTH,
+                I::MINOR_SEVENTH,
+            ],
+            ChordQuality::HalfDiminished7 => &[
+                I::PERFECT_UNISON,
+                I::MINOR_THIRD,
+                I::DIMINISHED_FIFTH,
+                I::MINOR_SEVENTH,
+            ],
+            ChordQuality::Diminished7 => &[
+                I::PERFECT_UNISON,
+                I::MINOR_THIRD,
+                I::DIMINISHED_FIFTH,
+                I::DIMINISHED_SEVENTH,
+            ],
+            ChordQuality::Dominant9 => &[
+                I::PERFECT_UNISON,
+                I::MAJOR_THIRD,
+                I::PERFECT_FIFTH,
+                I::MINOR_SEVENTH,
+                I::MAJOR_NINTH,
+            ],
+        }
+    }
+}
+
+impl ChordSymbol {
+    pub fn transpose(self, interval: Interval, direction: Direction) -> Option<ChordSymbol> {
+        let chord_symbol =
+            ChordSymbol::new(self.root().transpose(interval, direction)?, self.quality());
+
+        match self.bass() {
+            Some(bass) => Some(chord_symbol.over(bass.transpose(interval, direction)?)),
+            None => Some(chord_symbol),
+        }
+    }
+}
+
+impl Music {
+    /// The music with its notes and chord symbols transposed, see `Note::transpose`.
+    pub fn transpose(self, interval: Interval, direction: Direction) -> Option<Music> {
+        Some(match self {
+            Music::Note(note, duration) => {
+                Music::Note(note.transpose(interval, direction)?, duration)
+            }
+            Music::Chord(chord, duration) => {
+                Music::Chord(chord.transpose(interval, direction)?, duration)
+            }
+            Music::ChordSymbol(chord_symbol) => {
+                Music::ChordSymbol(chord_symbol.transpose(interval, direction)?)
+            }
+            Music::Rest(_)
+            | Music::Tie
+            | Music::Slur
+            | Music::Articulation(_)
+            | Music::Dynamic(_)
+            | Music::Hairpin(_) => self,
+        })
+    }
+}
+
+/// Transposes the music and its key in place. Returns `None` and leaves both unchanged if a note
+/// or the key can not be transposed, see `Note::transpose` and `Key::transpose`.
+pub fn transpose(
+    music: &mut [Music],
+    key: &mut Key,
+    interval: Interval,
+    direction: Direction,
+) -> Option<()> {
+    let transposed_key = key.transpose(interval, direction)?;
+    if music
+        .iter()
+        .any(|element| element.transpose(interval, direction).is_none())
+    {
+        return None;
+    }
+
+    for element in music.iter_mut() {
+        *element = element.transpose(interval, direction)?;
+    }
+    *key = transposed_key;
+
+    Some(())
+}
@@ -0,0 +1,240 @@
+use core::convert::Infallible;
+
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point},
+    pixelcolor::BinaryColor,
+    prelude::Size,
+    primitives::{Line, Primitive, PrimitiveStyle, Rectangle},
+    Drawable, Pixel,
+};
+use heapless::Vec;
+
+use crate::staff::{Clef, Duration, Key, Note, Staff, Ticks};
+
+pub const MAX_LAYOUT_SYMBOLS: usize = 64;
+
+/// Where a note or rest was drawn, see `Staff::draw`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolPosition {
+    /// Time at which the symbol starts, counted from the start of the first music on the staff
+    pub start: Ticks,
+    /// Duration as it is played
+    pub duration: Duration,
+    pub rest: bool,
+    /// Box around the noteheads, stem and accidentals, or around the rest
+    pub bounding_box: Rectangle,
+    /// Clef and key of the staff the symbol is on, `None` on a tablature staff
+    pub staff: Option<(Clef, Key)>,
+}
+
+impl SymbolPosition {
+    /// Whether the symbol sounds at the given time.
+    pub fn contains(&self, time: Ticks) -> bool {
+        self.start <= time && time < self.start + self.duration.ticks()
+    }
+}
+
+/// What lies under a point on a drawn staff, see `Layout::hit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+    /// Index in `Layout::symbols` of the symbol under the point
+    pub index: usize,
+    /// Time at which that symbol starts
+    pub start: Ticks,
+    /// Note of the line or space under the point, with the accidental of the key. `None` on a
+    /// tablature staff
+    pub pitch: Option<Note>,
+}
+
+/// Positions of the notes and rests on a staff after drawing it, in the order they were drawn.
+/// Used to follow the music while it plays, with a cursor or by highlighting the current note.
+#[derive(Debug, Clone, Default)]
+pub struct Layout {
+    symbols: Vec<SymbolPosition, MAX_LAYOUT_SYMBOLS>,
+    /// Area of the five staff lines
+    staff_lines: Rectangle,
+    /// Time of the music laid out so far
+    length: Ticks,
+    /// Clef and key of the symbols pushed next
+    staff: Option<(Clef, Key)>,
+}
+
+impl Layout {
+    pub(crate) fn new(staff_lines: Rectangle) -> Self {
+        Self {
+            symbols: Vec::new(),
+            staff_lines,
+            length: Ticks(0),
+            staff: None,
+        }
+    }
+
+    /// Sets the clef and key of the symbols pushed after it.
+    pub(crate) fn set_staff(&mut self, clef: Clef, key: Key) {
+        self.staff = Some((clef, key));
+    }
+
+    /// Adds a symbol that starts at the given time after the music laid out so far.
+    /// Symbols beyond `MAX_LAYOUT_SYMBOLS` are not recorded.
+    pub(crate) fn push(
+        &mut self,
+        offset: Ticks,
+        duration: Duration,
+        rest: bool,
+        bounding_box: Rectangle,
+    ) {
+        let _ = self.symbols.push(SymbolPosition {
+            start: self.length + offset,
+            duration,
+            rest,
+            bounding_box,
+            staff: self.staff,
+        });
+    }
+
+    /// Marks the end of a piece of music, the symbols pushed after it start later.
+    pub(crate) fn extend(&mut self, length: Ticks) {
+        self.length += length;
+    }
+
+    pub fn symbols(&self) -> &[SymbolPosition] {
+        &self.symbols
+    }
+
+    /// Total time of the music on the staff.
+    pub fn length(&self) -> Ticks {
+        self.length
+    }
+
+    /// The symbol that sounds at the given time. With two voices this is the one of the upper
+    /// voice.
+    pub fn symbol_at(&self, time: Ticks) -> Option<&SymbolPosition> {
+        self.symbols.iter().find(|symbol| symbol.contains(time))
+    }
+
+    /// The symbol under the given point, or the symbol closest to it from left to right, with the
+    /// pitch of the line or space under the point. Between two voices the symbol closest from top
+    /// to bottom is chosen. Returns `None` if the point lies left or right of the staff lines, or
+    /// if there are no symbols.
+    pub fn hit(&self, point: Point) -> Option<Hit> {
+        let left = self.staff_lines.top_left.x;
+        if point.x < left || point.x >= left + self.staff_lines.size.width as i32 {
+            return None;
+        }
+
+        // Distance from a range of pixels to the point, 0 if the point lies in it
+        let distance = |start: i32, length: u32, point: i32| {
+            let end = start + length as i32 - 1;
+            (start - point).max(point - end).max(0)
+        };
+
+        let (index, symbol) = self.symbols.iter().enumerate().min_by_key(|(_, symbol)| {
+            let bounding_box = symbol.bounding_box;
+            (
+                distance(bounding_box.top_left.x, bounding_box.size.width, point.x),
+                distance(bounding_box.top_left.y, bounding_box.size.height, point.y),
+            )
+        })?;
+
+        let pitch = symbol.staff.map(|(clef, key)| {
+            let offset = point.y - self.staff_lines.top_left.y;
+            let steps_below_top_line =
+                (offset * 2 + Staff::LINE_SPACING / 2).div_euclid(Staff::LINE_SPACING);
+            Note::on_staff(steps_below_top_line, clef, key)
+        });
+
+        Some(Hit {
+            index,
+            start: symbol.start,
+            pitch,
+        })
+    }
+
+    /// Draws a vertical line over the staff lines just before the symbol that sounds at the
+    /// given time. Draws nothing if no symbol sounds at that time.
+    pub fn draw_cursor<D>(&self, target: &mut D, time: Ticks) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let Some(symbol) = self.symbol_at(time) else {
+            return Ok(());
+        };
+
+        let x = symbol.bounding_box.top_left.x - 2;
+        let top = self.staff_lines.top_left.y;
+        let bottom = top + self.staff_lines.size.height as i32 - 1;
+
+        Line::new(Point::new(x, top), Point::new(x, bottom))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(target)
+    }
+}
+
+/// Draw target that inverts the color of everything drawn inside an area, used to highlight a
+/// symbol. Only gives a fully inverted area if the area is filled before drawing through it.
+pub struct Inverted<'a, D> {
+    target: &'a mut D,
+    area: Rectangle,
+}
+
+impl<'a, D> Inverted<'a, D> {
+    pub fn new(target: &'a mut D, area: Rectangle) -> Self {
+        Self { target, area }
+    }
+}
+
+impl<D> Dimensions for Inverted<'_, D>
+where
+    D: Dimensions,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D> DrawTarget for Inverted<'_, D>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    type Color = BinaryColor;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let area = self.area;
+        self.target
+            .draw_iter(pixels.into_iter().map(|Pixel(point, color)| {
+                if area.contains(point) {
+                    Pixel(point, color.invert())
+                } else {
+                    Pixel(point, color)
+                }
+            }))
+    }
+}
+
+/// Draw target that draws nothing, to lay out a staff without drawing it.
+pub(crate) struct LayoutTarget {
+    pub(crate) size: Size,
+}
+
+impl Dimensions for LayoutTarget {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.size)
+    }
+}
+
+impl DrawTarget for LayoutTarget {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, _pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        Ok(())
+    }
+}
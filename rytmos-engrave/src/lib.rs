@@ -1,8 +1,20 @@
 #![no_std]
+pub mod bitmap;
+#[cfg(feature = "std")]
+pub mod export;
 pub mod frequencies;
+pub mod interval;
+pub mod layout;
 pub mod macros;
+#[cfg(feature = "std")]
+pub mod midi;
+#[cfg(feature = "std")]
+pub mod musicxml;
+pub mod notation;
+pub mod passage;
 pub mod staff;
+pub mod tab;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 #[macro_use]
 extern crate std;
@@ -0,0 +1,301 @@
+use core::{cmp::Reverse, fmt};
+use std::vec::Vec;
+
+use midly::{MetaMessage, MidiMessage, Smf, Timing, TrackEventKind};
+
+use crate::staff::{Chord, Duration, Key, Mode, Music, Note, Ticks, TimeSignature};
+
+/// Tempo of MIDI files that do not set one, 120 beats per minute.
+const DEFAULT_TEMPO: u32 = 500_000;
+
+/// How the notes are spelled, which MIDI leaves to the reader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spelling {
+    Flats,
+    Sharps,
+    /// As the notes are conventionally written in the key of the file, or in C major if the file
+    /// has no key signature.
+    #[default]
+    Key,
+}
+
+impl Spelling {
+    fn spell(self, code: u8, key: Key) -> Note {
+        match self {
+            Spelling::Flats => Note::from_u8_flat(code),
+            Spelling::Sharps => Note::from_u8_sharp(code),
+            Spelling::Key => Note::from_midi(code, key, None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidiOptions {
+    /// Index of the track to read. In most files with several tracks the first one has only the
+    /// tempo and signatures.
+    pub track: usize,
+    /// Grid that the starts and ends of the notes are moved to, e.g. sixteenths, or eighth
+    /// triplets for music in triplets.
+    pub resolution: Duration,
+    pub spelling: Spelling,
+}
+
+impl Default for MidiOptions {
+    fn default() -> Self {
+        Self {
+            track: 0,
+            resolution: Duration::Sixteenth,
+            spelling: Spelling::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MidiError {
+    Midi(midly::Error),
+    /// The file times its events in frames per second instead of in beats
+    UnsupportedTiming,
+    NoSuchTrack,
+    /// An event is too late to count in `Ticks`
+    TooLong,
+}
+
+impl From<midly::Error> for MidiError {
+    fn from(error: midly::Error) -> Self {
+        MidiError::Midi(error)
+    }
+}
+
+impl fmt::Display for MidiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MidiError::Midi(error) => write!(f, "{error}"),
+            MidiError::UnsupportedTiming => write!(f, "timing in frames is not supported"),
+            MidiError::NoSuchTrack => write!(f, "no such track"),
+            MidiError::TooLong => write!(f, "too long to count in ticks"),
+        }
+    }
+}
+
+/// A note of a MIDI track, with its start and end moved to the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidiNote {
+    pub start: Ticks,
+    pub length: Ticks,
+    pub note: Note,
+    /// From 1 to 127
+    pub velocity: u8,
+}
+
+/// A track of a MIDI file, quantized to a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MidiImport {
+    /// Ordered by start, and from low to high
+    pub notes: Vec<MidiNote>,
+    pub resolution: Duration,
+    /// The first key signature in the file
+    pub key: Option<Key>,
+    /// The first time signature in the file
+    pub time_signature: Option<TimeSignature>,
+    /// Tempo in microseconds per quarter note from the given time on, the first one at the start
+    pub tempos: Vec<(Ticks, u32)>,
+}
+
+/// Moves the time to the closest multiple of the grid.
+fn quantize(ticks: u64, grid: Ticks) -> Result<Ticks, MidiError> {
+    let grid = u64::from(grid.0.max(1));
+    let ticks = (ticks + grid / 2) / grid * grid;
+    u32::try_from(ticks)
+        .map(Ticks)
+        .map_err(|_| MidiError::TooLong)
+}
+
+/// Reads a track of a Standard MIDI File and moves its notes to the grid of the options.
+pub fn read(bytes: &[u8], options: MidiOptions) -> Result<MidiImport, MidiError> {
+    let smf = Smf::parse(bytes)?;
+    let Timing::Metrical(per_beat) = smf.header.timing else {
+        return Err(MidiError::UnsupportedTiming);
+    };
+    let track = smf
+        .tracks
+        .get(options.track)
+        .ok_or(MidiError::NoSuchTrack)?;
+
+    let grid = options.resolution.ticks();
+    let per_beat = u64::from(per_beat.as_int().max(1));
+    let ticks = |time: u64| time * u64::from(Ticks::PER_QUARTER) / per_beat;
+
+    // Files with several tracks keep the tempo and signatures in any of them
+    let mut key = None;
+    let mut time_signature = None;
+    let mut tempos = Vec::new();
+    for events in &smf.tracks {
+        let mut time = 0;
+        for event in events {
+            time += u64::from(event.delta.as_int());
+            match event.kind {
+                TrackEventKind::Meta(MetaMessage::Tempo(tempo)) => {
+                    tempos.push((quantize(ticks(time), grid)?, tempo.as_int()));
+                }
+                TrackEventKind::Meta(MetaMessage::KeySignature(fifths, minor)) if key.is_none() => {
+                    let mode = if minor { Mode::Minor } else { Mode::Major };
+                    key = Key::from_fifths(fifths.into(), mode);
+                }
+                TrackEventKind::Meta(MetaMessage::TimeSignature(beats, beat_value, ..))
+                    if time_signature.is_none() =>
+                {
+                    // The beat value is stored as a power of two
+                    time_signature = 1u8
+                        .checked_shl(beat_value.into())
+                        .and_then(|beat_value| TimeSignature::new(beats, beat_value));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    tempos.sort_by_key(|&(time, _)| time);
+    if tempos.first().is_none_or(|&(time, _)| time != Ticks(0)) {
+        tempos.insert(0, (Ticks(0), DEFAULT_TEMPO));
+    }
+
+    let spelling_key = key.unwrap_or(Key::CMajor);
+    let mut notes = Vec::new();
+    let mut add_note = |code: u8, start: u64, end: u64, velocity: u8| -> Result<(), MidiError> {
+        let start = quantize(ticks(start), grid)?;
+        let end = quantize(ticks(end), grid)?;
+        let length = (end - start).max(grid);
+        // Notes that are moved to a grid step of their own have to end in time as well
+        if start.0.checked_add(length.0).is_none() {
+            return Err(MidiError::TooLong);
+        }
+        notes.push(MidiNote {
+            start,
+            length,
+            note: options.spelling.spell(code, spelling_key),
+            velocity,
+        });
+        Ok(())
+    };
+
+    // Start and velocity of the notes that sound, by key
+    let mut sounding = [None; 128];
+    let mut time = 0;
+    for event in track {
+        time += u64::from(event.delta.as_int());
+        let TrackEventKind::Midi { message, .. } = event.kind else {
+            continue;
+        };
+
+        let (code, velocity) = match message {
+            MidiMessage::NoteOn { key, vel } => (key.as_int(), vel.as_int()),
+            MidiMessage::NoteOff { key, .. } => (key.as_int(), 0),
+            _ => continue,
+        };
+
+        // A note that is played again while it sounds ends there
+        if let Some((start, velocity)) = sounding[usize::from(code)].take() {
+            add_note(code, start, time, velocity)?;
+        }
+        if velocity > 0 {
+            sounding[usize::from(code)] = Some((time, velocity));
+        }
+    }
+
+    // Notes that are not released end at the end of the track
+    for (code, sounding) in sounding.iter().enumerate() {
+        if let Some((start, velocity)) = *sounding {
+            add_note(code as u8, start, time, velocity)?;
+        }
+    }
+
+    notes.sort_by_key(|note| (note.start, note.note.semitones()));
+
+    Ok(MidiImport {
+        notes,
+        resolution: options.resolution,
+        key,
+        time_signature,
+        tempos,
+    })
+}
+
+impl MidiImport {
+    /// Durations that add up to the length, longest first. Only durations that are a multiple of
+    /// the resolution are used, so that the notes after them stay on the grid. The length is a
+    /// multiple of the resolution, which is a duration itself, so they always add up.
+    fn split(&self, length: Ticks) -> Vec<Duration> {
+        let grid = self.resolution.ticks();
+        let mut durations = Duration::ALL;
+        durations.sort_by_key(|duration| Reverse(duration.ticks()));
+
+        let mut remaining = length;
+        let mut parts = Vec::new();
+        while let Some(&duration) = durations
+            .iter()
+            .find(|duration| duration.ticks() <= remaining && duration.ticks() % grid == Ticks(0))
+        {
+            parts.push(duration);
+            remaining = remaining - duration.ticks();
+        }
+        debug_assert_eq!(remaining, Ticks(0), "{length:?} is not on the grid");
+
+        parts
+    }
+
+    /// The notes as music for a single voice, with rests in between. Notes that start together
+    /// are a chord of at most `Chord::MAX_NOTES` of the lowest notes. Where the shortest note of
+    /// a chord ends, the notes that still sound continue as a tied chord. Notes are cut off where
+    /// the next note starts, and notes that are not a single duration long are split into tied
+    /// notes.
+    pub fn music(&self) -> Vec<Music> {
+        let mut music = Vec::new();
+        let mut time = Ticks(0);
+
+        let mut index = 0;
+        while index < self.notes.len() {
+            let start = self.notes[index].start;
+            let count = self.notes[index..]
+                .iter()
+                .take_while(|note| note.start == start)
+                .count();
+            let mut sounding: Vec<&MidiNote> = self.notes[index..index + count].iter().collect();
+            sounding.dedup_by_key(|note| note.note.semitones());
+            sounding.truncate(Chord::MAX_NOTES);
+            index += count;
+            let next_start = self.notes.get(index).map(|note| note.start);
+
+            if start > time {
+                music.extend(self.split(start - time).into_iter().map(Music::Rest));
+            }
+            time = start;
+
+            while !sounding.is_empty() && next_start.is_none_or(|next| time < next) {
+                let mut end = sounding
+                    .iter()
+                    .map(|note| note.start + note.length)
+                    .min()
+                    .unwrap_or(time);
+                if let Some(next) = next_start {
+                    end = end.min(next);
+                }
+
+                let notes: Vec<Note> = sounding.iter().map(|note| note.note).collect();
+                for (i, duration) in self.split(end - time).into_iter().enumerate() {
+                    if i > 0 || time > start {
+                        music.push(Music::Tie);
+                    }
+                    music.push(match Chord::new(&notes) {
+                        Some(chord) if notes.len() > 1 => Music::Chord(chord, duration),
+                        _ => Music::Note(notes[0], duration),
+                    });
+                }
+
+                sounding.retain(|note| note.start + note.length > end);
+                time = end;
+            }
+        }
+
+        music
+    }
+}
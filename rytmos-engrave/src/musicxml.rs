@@ -0,0 +1,1109 @@
+use core::{
+    fmt::{self, Write as _},
+    mem,
+};
+use std::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::staff::{
+    Accidental, Articulation, Chord, ChordQuality, ChordSymbol, Clef, Duration, Dynamic, Hairpin,
+    Key, Mode, Music, Note, StaffElement, Ticks, TimeSignature,
+};
+
+/// Divisions of a quarter note in written files, so that all durations are a whole number.
+const DIVISIONS: u32 = Ticks::PER_QUARTER;
+
+/// Octave given to the notes of chord symbols, which have none in MusicXML.
+const CHORD_SYMBOL_OCTAVE: i32 = 3;
+
+const CHORD_KINDS: [(ChordQuality, &str); 15] = [
+    (ChordQuality::Major, "major"),
+    (ChordQuality::Minor, "minor"),
+    (ChordQuality::Augmented, "augmented"),
+    (ChordQuality::Diminished, "diminished"),
+    (ChordQuality::Suspended2, "suspended-second"),
+    (ChordQuality::Suspended4, "suspended-fourth"),
+    (ChordQuality::Power, "power"),
+    (ChordQuality::Sixth, "major-sixth"),
+    (ChordQuality::MinorSixth, "minor-sixth"),
+    (ChordQuality::Dominant7, "dominant"),
+    (ChordQuality::Major7, "major-seventh"),
+    (ChordQuality::Minor7, "minor-seventh"),
+    (ChordQuality::HalfDiminished7, "half-diminished"),
+    (ChordQuality::Diminished7, "diminished-seventh"),
+    (ChordQuality::Dominant9, "dominant-ninth"),
+];
+
+const MODES: [(Mode, &str); 7] = [
+    (Mode::Major, "major"),
+    (Mode::Dorian, "dorian"),
+    (Mode::Phrygian, "phrygian"),
+    (Mode::Lydian, "lydian"),
+    (Mode::Mixolydian, "mixolydian"),
+    (Mode::Minor, "minor"),
+    (Mode::Locrian, "locrian"),
+];
+
+const DYNAMICS: [(Dynamic, &str); 6] = [
+    (Dynamic::Pianissimo, "pp"),
+    (Dynamic::Piano, "p"),
+    (Dynamic::MezzoPiano, "mp"),
+    (Dynamic::MezzoForte, "mf"),
+    (Dynamic::Forte, "f"),
+    (Dynamic::Fortissimo, "ff"),
+];
+
+const ARTICULATIONS: [(Articulation, &str); 3] = [
+    (Articulation::Accent, "accent"),
+    (Articulation::Staccato, "staccato"),
+    (Articulation::Tenuto, "tenuto"),
+];
+
+/// Owned counterpart of a `StaffElement`, for music read from a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Element {
+    Music(Vec<Music>),
+    Barline,
+    KeySignature(Key),
+    TimeSignature(TimeSignature),
+    Clef(Clef),
+}
+
+impl Element {
+    pub fn as_staff_element(&self) -> StaffElement<'_> {
+        match self {
+            Element::Music(music) => StaffElement::Music(music),
+            Element::Barline => StaffElement::Barline,
+            Element::KeySignature(key) => StaffElement::KeySignature(*key),
+            Element::TimeSignature(time) => StaffElement::TimeSignature(*time),
+            Element::Clef(clef) => StaffElement::Clef(*clef),
+        }
+    }
+}
+
+/// A construct that is left out, because the staff has no way to show it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported {
+    /// Number of the measure it is in, as written in the file when reading, and counting from 1
+    /// when writing
+    pub measure: String,
+    /// Name of the element, e.g. `grace` or `backup`
+    pub element: String,
+}
+
+#[derive(Debug)]
+pub enum MusicXmlError {
+    Xml(roxmltree::Error),
+    /// The document is not a `score-partwise`
+    NotPartwise,
+    /// There is no part with the given id, or no part at all
+    NoSuchPart,
+    /// An element with a missing or invalid value, e.g. a pitch without an octave
+    Invalid(Unsupported),
+    /// Music that MusicXML files written by this module cannot contain, e.g. two voices
+    Unsupported(Unsupported),
+}
+
+impl From<roxmltree::Error> for MusicXmlError {
+    fn from(error: roxmltree::Error) -> Self {
+        MusicXmlError::Xml(error)
+    }
+}
+
+impl fmt::Display for MusicXmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MusicXmlError::Xml(error) => write!(f, "{error}"),
+            MusicXmlError::NotPartwise => write!(f, "not a partwise MusicXML score"),
+            MusicXmlError::NoSuchPart => write!(f, "no such part"),
+            MusicXmlError::Invalid(Unsupported { measure, element }) => {
+                write!(f, "invalid {element} in measure {measure}")
+            }
+            MusicXmlError::Unsupported(Unsupported { measure, element }) => {
+                write!(f, "unsupported {element} in measure {measure}")
+            }
+        }
+    }
+}
+
+/// A single voice part read from MusicXML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub elements: Vec<Element>,
+    /// What the file contains that is left out of the elements
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl Part {
+    pub fn staff_elements(&self) -> Vec<StaffElement<'_>> {
+        self.elements
+            .iter()
+            .map(Element::as_staff_element)
+            .collect()
+    }
+}
+
+fn children<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(Node::is_element)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    children(node).find(|child| child.has_tag_name(name))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)?.text().map(str::trim)
+}
+
+fn step(letter: &str) -> Option<i32> {
+    match letter {
+        "C" => Some(0),
+        "D" => Some(1),
+        "E" => Some(2),
+        "F" => Some(3),
+        "G" => Some(4),
+        "A" => Some(5),
+        "B" => Some(6),
+        _ => None,
+    }
+}
+
+fn letter(note: Note) -> char {
+    match note {
+        Note::A(..) => 'A',
+        Note::B(..) => 'B',
+        Note::C(..) => 'C',
+        Note::D(..) => 'D',
+        Note::E(..) => 'E',
+        Note::F(..) => 'F',
+        Note::G(..) => 'G',
+    }
+}
+
+/// A note, chord or rest that the notes after it can still be added to as a chord.
+struct Event {
+    music: Music,
+    marks: Vec<Music>,
+    tie: bool,
+    slur_start: bool,
+    slur_stop: bool,
+}
+
+struct Reader {
+    measure: String,
+    /// Divisions of a quarter note that durations are given in
+    divisions: u32,
+    /// The voice that is read, the first one in the part
+    voice: Option<String>,
+    /// Skips the rest of the measure after moving back or forward in time
+    skipping: bool,
+    elements: Vec<Element>,
+    music: Vec<Music>,
+    /// Marks of the next note or rest
+    marks: Vec<Music>,
+    event: Option<Event>,
+    slurred: bool,
+    /// Tie or slur at the end of the previous music, that the next music starts with
+    continued: Option<Music>,
+    unsupported: Vec<Unsupported>,
+}
+
+impl Reader {
+    fn new() -> Self {
+        Self {
+            measure: String::new(),
+            divisions: 1,
+            voice: None,
+            skipping: false,
+            elements: Vec::new(),
+            music: Vec::new(),
+            marks: Vec::new(),
+            event: None,
+            slurred: false,
+            continued: None,
+            unsupported: Vec::new(),
+        }
+    }
+
+    fn here(&self, element: &str) -> Unsupported {
+        Unsupported {
+            measure: self.measure.clone(),
+            element: element.to_owned(),
+        }
+    }
+
+    fn invalid(&self, element: &str) -> MusicXmlError {
+        MusicXmlError::Invalid(self.here(element))
+    }
+
+    fn unsupported(&mut self, element: &str) {
+        let unsupported = self.here(element);
+        if !self.unsupported.contains(&unsupported) {
+            self.unsupported.push(unsupported);
+        }
+    }
+
+    fn flush_event(&mut self) {
+        let Some(event) = self.event.take() else {
+            return;
+        };
+
+        if self.music.is_empty() {
+            self.music.extend(self.continued.take());
+        }
+        if self.slurred && self.music.last() != Some(&Music::Slur) {
+            self.music.push(Music::Slur);
+        }
+        self.music.extend(event.marks);
+        self.music.push(event.music);
+        if event.tie {
+            self.music.push(Music::Tie);
+        }
+
+        if event.slur_stop {
+            self.slurred = false;
+        }
+        if event.slur_start {
+            self.slurred = true;
+        }
+    }
+
+    /// Ends the music before a barline or an attribute change. Like the measures of a passage,
+    /// music that ends in a tie or slur is continued by the next music starting with it.
+    fn flush_music(&mut self) {
+        self.flush_event();
+        if !self.music.is_empty() {
+            if self.slurred && self.music.last() != Some(&Music::Slur) {
+                self.music.push(Music::Slur);
+            }
+            self.continued = self
+                .music
+                .last()
+                .copied()
+                .filter(|last| matches!(last, Music::Tie | Music::Slur));
+
+            self.elements
+                .push(Element::Music(mem::take(&mut self.music)));
+        }
+    }
+
+    fn measure(&mut self, measure: Node) -> Result<(), MusicXmlError> {
+        self.measure = measure.attribute("number").unwrap_or_default().to_string();
+        self.skipping = false;
+
+        for node in children(measure) {
+            match node.tag_name().name() {
+                "attributes" => self.attributes(node)?,
+                "note" => self.note(node)?,
+                "direction" => self.direction(node),
+                "harmony" => self.harmony(node)?,
+                "barline" => {
+                    for child in children(node) {
+                        if matches!(child.tag_name().name(), "repeat" | "ending") {
+                            self.unsupported(child.tag_name().name());
+                        }
+                    }
+                }
+                // Notes after moving in time belong to another voice, or would be misplaced
+                name @ ("backup" | "forward") => {
+                    self.unsupported(name);
+                    self.skipping = true;
+                }
+                // Layout and playback only
+                "print" | "sound" | "bookmark" => {}
+                name => self.unsupported(name),
+            }
+        }
+
+        self.flush_event();
+        Ok(())
+    }
+
+    fn attributes(&mut self, attributes: Node) -> Result<(), MusicXmlError> {
+        let mut clef = None;
+        let mut key = None;
+        let mut time = None;
+
+        for node in children(attributes) {
+            match node.tag_name().name() {
+                "divisions" => {
+                    self.divisions = node
+                        .text()
+                        .and_then(|text| text.trim().parse().ok())
+                        .filter(|&divisions| divisions > 0)
+                        .ok_or_else(|| self.invalid("divisions"))?;
+                }
+                "key" => key = self.key(node),
+                "time" => time = self.time(node),
+                "clef" => clef = self.clef(node),
+                "staves" => {
+                    if node.text().map(str::trim) != Some("1") {
+                        self.unsupported("staves");
+                    }
+                }
+                // Layout only
+                "instruments" | "staff-details" | "measure-style" | "part-symbol" => {}
+                name => self.unsupported(name),
+            }
+        }
+
+        if clef.is_some() || key.is_some() || time.is_some() {
+            self.flush_music();
+            self.elements.extend(clef.map(Element::Clef));
+            self.elements.extend(key.map(Element::KeySignature));
+            self.elements.extend(time.map(Element::TimeSignature));
+        }
+
+        Ok(())
+    }
+
+    fn key(&mut self, node: Node) -> Option<Key> {
+        let mode = match child_text(node, "mode").unwrap_or("major") {
+            "ionian" | "none" => Some(Mode::Major),
+            "aeolian" => Some(Mode::Minor),
+            name => MODES
+                .into_iter()
+                .find_map(|(mode, mode_name)| (mode_name == name).then_some(mode)),
+        };
+        let key = child_text(node, "fifths")
+            .and_then(|fifths| fifths.parse().ok())
+            .zip(mode)
+            .and_then(|(fifths, mode)| Key::from_fifths(fifths, mode));
+
+        if key.is_none() {
+            self.unsupported("key");
+        }
+        key
+    }
+
+    fn time(&mut self, node: Node) -> Option<TimeSignature> {
+        let beats = child_text(node, "beats").and_then(|beats| beats.parse().ok());
+        let beat_value = child_text(node, "beat-type").and_then(|value| value.parse().ok());
+
+        let time_signature = beats
+            .zip(beat_value)
+            .and_then(|(beats, beat_value)| TimeSignature::new(beats, beat_value));
+        if time_signature.is_none() {
+            self.unsupported("time");
+        }
+        time_signature
+    }
+
+    fn clef(&mut self, node: Node) -> Option<Clef> {
+        if child_text(node, "clef-octave-change").is_some_and(|change| change != "0") {
+            self.unsupported("clef-octave-change");
+        }
+
+        let clef = match (child_text(node, "sign"), child_text(node, "line")) {
+            (Some("G"), None | Some("2")) => Some(Clef::Treble),
+            (Some("F"), None | Some("4")) => Some(Clef::Bass),
+            (Some("C"), None | Some("3")) => Some(Clef::Alto),
+            (Some("C"), Some("4")) => Some(Clef::Tenor),
+            _ => None,
+        };
+
+        if clef.is_none() {
+            self.unsupported("clef");
+        }
+        clef
+    }
+
+    fn pitch(&self, pitch: Node) -> Result<Note, MusicXmlError> {
+        let step = child_text(pitch, "step")
+            .and_then(step)
+            .ok_or_else(|| self.invalid("step"))?;
+        // MusicXML has octaves 0 to 9, other octaves would overflow when the note is drawn
+        let octave: i32 = child_text(pitch, "octave")
+            .and_then(|octave| octave.parse().ok())
+            .filter(|octave| (0..=9).contains(octave))
+            .ok_or_else(|| self.invalid("octave"))?;
+        let alter = match child_text(pitch, "alter") {
+            Some(alter) => alter.parse().map_err(|_| self.invalid("alter"))?,
+            None => 0.0f32,
+        };
+
+        // Quarter tones have no accidental
+        let accidental = Some(alter)
+            .filter(|alter| alter.fract() == 0.0)
+            .and_then(|alter| Accidental::from_semitones(alter as i32))
+            .ok_or_else(|| self.invalid("alter"))?;
+
+        Ok(Note::from_diatonic_step(step + octave * 7, accidental))
+    }
+
+    fn duration(&self, note: Node) -> Result<Option<Duration>, MusicXmlError> {
+        let ticks = child_text(note, "duration")
+            .and_then(|duration| duration.parse::<u32>().ok())
+            .and_then(|divisions| divisions.checked_mul(Ticks::PER_QUARTER))
+            .ok_or_else(|| self.invalid("duration"))?;
+
+        if !ticks.is_multiple_of(self.divisions) {
+            return Ok(None);
+        }
+
+        Ok(Duration::from_ticks(Ticks(ticks / self.divisions)))
+    }
+
+    fn note(&mut self, note: Node) -> Result<(), MusicXmlError> {
+        if self.skipping {
+            return Ok(());
+        }
+
+        if let Some(voice) = child_text(note, "voice") {
+            match &self.voice {
+                None => self.voice = Some(voice.to_owned()),
+                Some(first) if first != voice => {
+                    self.unsupported("voice");
+                    return Ok(());
+                }
+                Some(_) => {}
+            }
+        }
+        if child_text(note, "staff").is_some_and(|staff| staff != "1") {
+            self.unsupported("staff");
+            return Ok(());
+        }
+        for name in ["grace", "cue", "unpitched"] {
+            if child(note, name).is_some() {
+                self.unsupported(name);
+                return Ok(());
+            }
+        }
+
+        let Some(duration) = self.duration(note)? else {
+            self.unsupported("duration");
+            return Ok(());
+        };
+        let pitch = child(note, "pitch")
+            .map(|pitch| self.pitch(pitch))
+            .transpose()?;
+
+        let mut marks = Vec::new();
+        let mut tie = false;
+        let mut slur_start = false;
+        let mut slur_stop = false;
+
+        for node in children(note) {
+            match node.tag_name().name() {
+                "tie" => tie |= node.attribute("type") == Some("start"),
+                "notehead" => match node.text().map(str::trim) {
+                    Some("x") => marks.push(Music::Articulation(Articulation::Ghost)),
+                    Some("normal") => {}
+                    _ => self.unsupported("notehead"),
+                },
+                "notations" => {
+                    for notation in children(node) {
+                        match notation.tag_name().name() {
+                            "tied" => tie |= notation.attribute("type") == Some("start"),
+                            "slur" => match notation.attribute("type") {
+                                Some("start") => slur_start = true,
+                                Some("stop") => slur_stop = true,
+                                _ => {}
+                            },
+                            // Drawn from the duration
+                            "tuplet" => {}
+                            "articulations" => {
+                                for articulation in children(notation) {
+                                    let name = articulation.tag_name().name();
+                                    match ARTICULATIONS.iter().find(|(_, n)| *n == name) {
+                                        Some((articulation, _)) => {
+                                            marks.push(Music::Articulation(*articulation))
+                                        }
+                                        None => self.unsupported(name),
+                                    }
+                                }
+                            }
+                            name => self.unsupported(name),
+                        }
+                    }
+                }
+                // Drawn from the pitch and duration, or layout only
+                "chord" | "pitch" | "rest" | "duration" | "voice" | "type" | "dot"
+                | "accidental" | "time-modification" | "stem" | "staff" | "beam" | "instrument" => {
+                }
+                name => self.unsupported(name),
+            }
+        }
+
+        if let (Some(note), Some(_)) = (pitch, child(note, "chord")) {
+            if let Some(event) = self.event.as_mut() {
+                let chord = match event.music {
+                    Music::Note(first, first_duration) if first_duration == duration => {
+                        Chord::new(&[first, note])
+                    }
+                    Music::Chord(chord, chord_duration)
+                        if chord_duration == duration && chord.notes().len() < Chord::MAX_NOTES =>
+                    {
+                        let mut notes = chord.notes().to_vec();
+                        notes.push(note);
+                        Chord::new(&notes)
+                    }
+                    _ => None,
+                };
+
+                match chord {
+                    Some(chord) => {
+                        event.music = Music::Chord(chord, duration);
+                        for mark in marks {
+                            if !event.marks.contains(&mark) {
+                                event.marks.push(mark);
+                            }
+                        }
+                        event.tie |= tie;
+                        event.slur_start |= slur_start;
+                        event.slur_stop |= slur_stop;
+                    }
+                    None => self.unsupported("chord"),
+                }
+                return Ok(());
+            }
+        }
+
+        self.flush_event();
+        let mut event_marks = mem::take(&mut self.marks);
+        event_marks.extend(marks);
+        self.event = Some(Event {
+            music: match pitch {
+                Some(note) => Music::Note(note, duration),
+                None => Music::Rest(duration),
+            },
+            marks: event_marks,
+            tie,
+            slur_start,
+            slur_stop,
+        });
+
+        Ok(())
+    }
+
+    fn direction(&mut self, direction: Node) {
+        self.flush_event();
+
+        for node in children(direction) {
+            match node.tag_name().name() {
+                "direction-type" => {}
+                // Placement and playback only
+                "offset" | "staff" | "voice" | "sound" => continue,
+                name => {
+                    self.unsupported(name);
+                    continue;
+                }
+            }
+
+            for kind in children(node) {
+                match kind.tag_name().name() {
+                    "dynamics" => {
+                        for dynamic in children(kind) {
+                            let name = dynamic.tag_name().name();
+                            match DYNAMICS.iter().find(|(_, n)| *n == name) {
+                                Some((dynamic, _)) => self.marks.push(Music::Dynamic(*dynamic)),
+                                None => self.unsupported(name),
+                            }
+                        }
+                    }
+                    "wedge" => match kind.attribute("type") {
+                        Some("crescendo") => self.marks.push(Music::Hairpin(Hairpin::Crescendo)),
+                        Some("diminuendo") => self.marks.push(Music::Hairpin(Hairpin::Decrescendo)),
+                        // A hairpin ends at the next dynamic
+                        _ => {}
+                    },
+                    name => self.unsupported(name),
+                }
+            }
+        }
+    }
+
+    fn harmony_note(&self, node: Node, kind: &str) -> Result<Note, MusicXmlError> {
+        let step_name = std::format!("{kind}-step");
+        let alter_name = std::format!("{kind}-alter");
+
+        let step = child_text(node, &step_name)
+            .and_then(step)
+            .ok_or_else(|| self.invalid(&step_name))?;
+        let accidental = match child_text(node, &alter_name) {
+            Some(alter) => alter
+                .parse()
+                .ok()
+                .and_then(Accidental::from_semitones)
+                .ok_or_else(|| self.invalid(&alter_name))?,
+            None => Accidental::Natural,
+        };
+
+        Ok(Note::from_diatonic_step(
+            step + CHORD_SYMBOL_OCTAVE * 7,
+            accidental,
+        ))
+    }
+
+    fn harmony(&mut self, harmony: Node) -> Result<(), MusicXmlError> {
+        self.flush_event();
+
+        if child(harmony, "degree").is_some() {
+            self.unsupported("degree");
+            return Ok(());
+        }
+
+        let root = child(harmony, "root").ok_or_else(|| self.invalid("root"))?;
+        let root = self.harmony_note(root, "root")?;
+        let kind = child_text(harmony, "kind").unwrap_or_default();
+        let Some((quality, _)) = CHORD_KINDS.iter().find(|(_, name)| *name == kind) else {
+            self.unsupported("kind");
+            return Ok(());
+        };
+
+        let mut symbol = ChordSymbol::new(root, *quality);
+        if let Some(bass) = child(harmony, "bass") {
+            symbol = symbol.over(self.harmony_note(bass, "bass")?);
+        }
+        self.marks.push(Music::ChordSymbol(symbol));
+
+        Ok(())
+    }
+}
+
+/// Reads the part with the given id, or the first part, of a partwise MusicXML score. Notes of
+/// other voices and anything else the staff cannot show are left out, and listed in the part.
+pub fn read(text: &str, part: Option<&str>) -> Result<Part, MusicXmlError> {
+    // Files written by notation programs start with the document type of MusicXML
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(text, options)?;
+    let score = document.root_element();
+    if !score.has_tag_name("score-partwise") {
+        return Err(MusicXmlError::NotPartwise);
+    }
+
+    let part = children(score)
+        .filter(|node| node.has_tag_name("part"))
+        .find(|node| part.is_none_or(|id| node.attribute("id") == Some(id)))
+        .ok_or(MusicXmlError::NoSuchPart)?;
+
+    let mut reader = Reader::new();
+    let measures: Vec<Node> = children(part)
+        .filter(|node| node.has_tag_name("measure"))
+        .collect();
+
+    for (index, &measure) in measures.iter().enumerate() {
+        reader.measure(measure)?;
+        reader.flush_music();
+        if index + 1 < measures.len() {
+            reader.elements.push(Element::Barline);
+        }
+    }
+
+    Ok(Part {
+        elements: reader.elements,
+        unsupported: reader.unsupported,
+    })
+}
+
+enum Item<'a> {
+    Music(&'a Music),
+    Barline,
+    KeySignature(Key),
+    TimeSignature(TimeSignature),
+    Clef(Clef),
+}
+
+fn is_mark(music: &Music) -> bool {
+    matches!(
+        music,
+        Music::Articulation(_) | Music::Dynamic(_) | Music::Hairpin(_) | Music::ChordSymbol(_)
+    )
+}
+
+struct Writer<'a> {
+    items: Vec<Item<'a>>,
+    xml: String,
+    measure: u32,
+    in_measure: bool,
+    divisions_written: bool,
+    clef: Option<Clef>,
+    key: Option<Key>,
+    time: Option<TimeSignature>,
+    /// Marks of the next note or rest
+    marks: Vec<&'a Music>,
+    tied: bool,
+    slurred: bool,
+    wedge: bool,
+}
+
+impl<'a> Writer<'a> {
+    fn line(&mut self, depth: usize, line: impl fmt::Display) {
+        // Writing to a string does not fail
+        let _ = writeln!(self.xml, "{:indent$}{line}", "", indent = depth * 2);
+    }
+
+    /// The next note, chord, rest, tie or slur after the item at the given index.
+    fn next_music(&self, index: usize) -> Option<&'a Music> {
+        self.items[index + 1..].iter().find_map(|item| match item {
+            Item::Music(music) if !is_mark(music) => Some(*music),
+            _ => None,
+        })
+    }
+
+    fn open_measure(&mut self) {
+        if self.in_measure {
+            return;
+        }
+
+        let number = self.measure;
+        self.line(2, format_args!(r#"<measure number="{number}">"#));
+        self.in_measure = true;
+    }
+
+    fn close_measure(&mut self) {
+        self.open_measure();
+        self.write_attributes();
+        self.line(2, "</measure>");
+        self.in_measure = false;
+        self.measure += 1;
+    }
+
+    fn write_attributes(&mut self) {
+        if self.divisions_written
+            && self.clef.is_none()
+            && self.key.is_none()
+            && self.time.is_none()
+        {
+            return;
+        }
+
+        self.line(3, "<attributes>");
+        if !self.divisions_written {
+            self.line(4, format_args!("<divisions>{DIVISIONS}</divisions>"));
+            self.divisions_written = true;
+        }
+        if let Some(key) = self.key.take() {
+            let mode = MODES
+                .iter()
+                .find(|(mode, _)| *mode == key.mode())
+                .map_or("major", |(_, name)| name);
+            self.line(4, "<key>");
+            self.line(5, format_args!("<fifths>{}</fifths>", key.fifths()));
+            self.line(5, format_args!("<mode>{mode}</mode>"));
+            self.line(4, "</key>");
+        }
+        if let Some(time) = self.time.take() {
+            self.line(4, "<time>");
+            self.line(5, format_args!("<beats>{}</beats>", time.beats()));
+            self.line(
+                5,
+                format_args!("<beat-type>{}</beat-type>", time.beat_value()),
+            );
+            self.line(4, "</time>");
+        }
+        if let Some(clef) = self.clef.take() {
+            let (sign, line) = match clef {
+                Clef::Treble => ('G', 2),
+                Clef::Bass => ('F', 4),
+                Clef::Alto => ('C', 3),
+                Clef::Tenor => ('C', 4),
+            };
+            self.line(4, "<clef>");
+            self.line(5, format_args!("<sign>{sign}</sign>"));
+            self.line(5, format_args!("<line>{line}</line>"));
+            self.line(4, "</clef>");
+        }
+        self.line(3, "</attributes>");
+    }
+
+    fn write_wedge(&mut self, wedge: &str) {
+        self.line(3, r#"<direction placement="below">"#);
+        self.line(4, "<direction-type>");
+        self.line(5, format_args!(r#"<wedge type="{wedge}"/>"#));
+        self.line(4, "</direction-type>");
+        self.line(3, "</direction>");
+    }
+
+    fn write_harmony_note(&mut self, kind: &str, note: Note) {
+        self.line(4, format_args!("<{kind}>"));
+        self.line(
+            5,
+            format_args!("<{kind}-step>{}</{kind}-step>", letter(note)),
+        );
+        let alter = note.accidental().semitones();
+        if alter != 0 {
+            self.line(5, format_args!("<{kind}-alter>{alter}</{kind}-alter>"));
+        }
+        self.line(4, format_args!("</{kind}>"));
+    }
+
+    /// Writes the marks that are directions before the note or rest they mark.
+    fn write_directions(&mut self) {
+        for mark in mem::take(&mut self.marks) {
+            match mark {
+                Music::Dynamic(dynamic) => {
+                    if mem::take(&mut self.wedge) {
+                        self.write_wedge("stop");
+                    }
+                    let name = DYNAMICS
+                        .iter()
+                        .find(|(d, _)| d == dynamic)
+                        .map_or("mf", |(_, name)| name);
+                    self.line(3, r#"<direction placement="below">"#);
+                    self.line(4, "<direction-type>");
+                    self.line(5, format_args!("<dynamics><{name}/></dynamics>"));
+                    self.line(4, "</direction-type>");
+                    self.line(3, "</direction>");
+                }
+                Music::Hairpin(hairpin) => {
+                    if mem::take(&mut self.wedge) {
+                        self.write_wedge("stop");
+                    }
+                    self.write_wedge(match hairpin {
+                        Hairpin::Crescendo => "crescendo",
+                        Hairpin::Decrescendo => "diminuendo",
+                    });
+                    self.wedge = true;
+                }
+                Music::ChordSymbol(symbol) => {
+                    let kind = CHORD_KINDS
+                        .iter()
+                        .find(|(quality, _)| *quality == symbol.quality())
+                        .map_or("major", |(_, name)| name);
+                    self.line(3, "<harmony>");
+                    self.write_harmony_note("root", symbol.root());
+                    self.line(4, format_args!("<kind>{kind}</kind>"));
+                    if let Some(bass) = symbol.bass() {
+                        self.write_harmony_note("bass", bass);
+                    }
+                    self.line(3, "</harmony>");
+                }
+                // Articulations are written in the note
+                _ => self.marks.push(mark),
+            }
+        }
+    }
+
+    fn write_note(
+        &mut self,
+        note: Option<Note>,
+        duration: Duration,
+        in_chord: bool,
+        tie: (bool, bool),
+        slur: Option<&str>,
+    ) {
+        self.line(3, "<note>");
+        if in_chord {
+            self.line(4, "<chord/>");
+        }
+        match note {
+            Some(note) => {
+                self.line(4, "<pitch>");
+                self.line(5, format_args!("<step>{}</step>", letter(note)));
+                let alter = note.accidental().semitones();
+                if alter != 0 {
+                    self.line(5, format_args!("<alter>{alter}</alter>"));
+                }
+                self.line(5, format_args!("<octave>{}</octave>", note.octave()));
+                self.line(4, "</pitch>");
+            }
+            None => self.line(4, "<rest/>"),
+        }
+        self.line(
+            4,
+            format_args!("<duration>{}</duration>", duration.ticks().0),
+        );
+
+        let (tie_stop, tie_start) = tie;
+        if tie_stop {
+            self.line(4, r#"<tie type="stop"/>"#);
+        }
+        if tie_start {
+            self.line(4, r#"<tie type="start"/>"#);
+        }
+
+        self.line(4, "<voice>1</voice>");
+        let kind = match duration.written() {
+            Duration::Breve => "breve",
+            Duration::Whole | Duration::DottedWhole => "whole",
+            Duration::Half | Duration::DottedHalf | Duration::DoubleDottedHalf => "half",
+            Duration::Eighth | Duration::DottedEighth | Duration::DoubleDottedEighth => "eighth",
+            Duration::Sixteenth | Duration::DottedSixteenth => "16th",
+            Duration::ThirtySecond => "32nd",
+            _ => "quarter",
+        };
+        self.line(4, format_args!("<type>{kind}</type>"));
+        for _ in 0..duration.dots() {
+            self.line(4, "<dot/>");
+        }
+        if let Some((actual, _)) = duration.tuplet() {
+            let normal = if actual == 3 { 2 } else { 4 };
+            self.line(4, "<time-modification>");
+            self.line(5, format_args!("<actual-notes>{actual}</actual-notes>"));
+            self.line(5, format_args!("<normal-notes>{normal}</normal-notes>"));
+            self.line(4, "</time-modification>");
+        }
+        if note.is_some()
+            && self
+                .marks
+                .contains(&&Music::Articulation(Articulation::Ghost))
+        {
+            self.line(4, "<notehead>x</notehead>");
+        }
+
+        let articulations: Vec<&str> = self
+            .marks
+            .iter()
+            .filter_map(|mark| match mark {
+                Music::Articulation(articulation) => ARTICULATIONS
+                    .iter()
+                    .find(|(a, _)| a == articulation)
+                    .map(|(_, name)| *name),
+                _ => None,
+            })
+            .filter(|_| !in_chord)
+            .collect();
+
+        if tie_stop || tie_start || slur.is_some() || !articulations.is_empty() {
+            self.line(4, "<notations>");
+            if tie_stop {
+                self.line(5, r#"<tied type="stop"/>"#);
+            }
+            if tie_start {
+                self.line(5, r#"<tied type="start"/>"#);
+            }
+            if let Some(slur) = slur {
+                self.line(5, format_args!(r#"<slur type="{slur}" number="1"/>"#));
+            }
+            if !articulations.is_empty() {
+                self.line(5, "<articulations>");
+                for articulation in articulations {
+                    self.line(6, format_args!("<{articulation}/>"));
+                }
+                self.line(5, "</articulations>");
+            }
+            self.line(4, "</notations>");
+        }
+
+        self.line(3, "</note>");
+    }
+
+    fn write_music(&mut self, index: usize, music: &'a Music) {
+        let (notes, duration): (&[Note], Duration) = match music {
+            Music::Note(note, duration) => (core::slice::from_ref(note), *duration),
+            Music::Chord(chord, duration) => (chord.notes(), *duration),
+            Music::Rest(duration) => (&[], *duration),
+            Music::Tie | Music::Slur => return,
+            mark => {
+                self.marks.push(mark);
+                return;
+            }
+        };
+
+        self.open_measure();
+        self.write_attributes();
+        self.write_directions();
+
+        let next = self.next_music(index);
+        let tie = (self.tied, next == Some(&Music::Tie));
+        self.tied = tie.1;
+
+        let slur_next = next == Some(&Music::Slur);
+        let slur = match (self.slurred, slur_next) {
+            (false, true) => Some("start"),
+            (true, false) => Some("stop"),
+            _ => None,
+        };
+        self.slurred = slur_next;
+
+        if notes.is_empty() {
+            self.write_note(None, duration, false, tie, None);
+        }
+        for (i, &note) in notes.iter().enumerate() {
+            let slur = if i == 0 { slur } else { None };
+            self.write_note(Some(note), duration, i > 0, tie, slur);
+        }
+
+        self.marks.clear();
+    }
+}
+
+/// Writes a staff as a partwise MusicXML score with a single part. Barlines start new measures.
+/// Returns an error for music that a single voice cannot contain, e.g. two voices on the staff.
+pub fn write(elements: &[StaffElement]) -> Result<String, MusicXmlError> {
+    let mut items = Vec::new();
+    let mut measure = 1;
+    for element in elements {
+        match *element {
+            StaffElement::Music(music) => items.extend(music.iter().map(Item::Music)),
+            StaffElement::Barline => {
+                items.push(Item::Barline);
+                measure += 1;
+            }
+            StaffElement::KeySignature(key) => items.push(Item::KeySignature(key)),
+            StaffElement::TimeSignature(time) => items.push(Item::TimeSignature(time)),
+            StaffElement::Clef(clef) => items.push(Item::Clef(clef)),
+            // Counting is not part of the music
+            StaffElement::Counting(_) => {}
+            StaffElement::Voices(..) => {
+                return Err(MusicXmlError::Unsupported(Unsupported {
+                    measure: measure.to_string(),
+                    element: "voices".to_owned(),
+                }))
+            }
+        }
+    }
+
+    let mut writer = Writer {
+        items,
+        xml: String::new(),
+        measure: 1,
+        in_measure: false,
+        divisions_written: false,
+        clef: None,
+        key: None,
+        time: None,
+        marks: Vec::new(),
+        tied: false,
+        slurred: false,
+        wedge: false,
+    };
+
+    writer.line(
+        0,
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#,
+    );
+    writer.line(
+        0,
+        r#"<!DOCTYPE score-partwise PUBLIC "-//Recordare//DTD MusicXML 4.0 Partwise//EN" "http://www.musicxml.org/dtds/partwise.dtd">"#,
+    );
+    writer.line(0, r#"<score-partwise version="4.0">"#);
+    writer.line(1, "<part-list>");
+    writer.line(2, r#"<score-part id="P1">"#);
+    writer.line(3, "<part-name>Music</part-name>");
+    writer.line(2, "</score-part>");
+    writer.line(1, "</part-list>");
+    writer.line(1, r#"<part id="P1">"#);
+
+    for index in 0..writer.items.len() {
+        match writer.items[index] {
+            Item::Music(music) => writer.write_music(index, music),
+            Item::Barline => writer.close_measure(),
+            Item::KeySignature(key) => writer.key = Some(key),
+            Item::TimeSignature(time) => writer.time = Some(time),
+            Item::Clef(clef) => writer.clef = Some(clef),
+        }
+    }
+
+    if writer.wedge {
+        writer.open_measure();
+        writer.write_wedge("stop");
+    }
+    if writer.in_measure || writer.measure == 1 {
+        writer.close_measure();
+    }
+
+    writer.line(1, "</part>");
+    writer.line(0, "</score-partwise>");
+
+    Ok(writer.xml)
+}
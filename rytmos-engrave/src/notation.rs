@@ -0,0 +1,842 @@
+use core::{
+    fmt::{self, Write},
+    ops::Range,
+};
+
+use heapless::Vec;
+
+use crate::staff::{
+    Accidental, Articulation, Chord, ChordQuality, ChordSymbol, Duration, Dynamic, Hairpin, Music,
+    Note,
+};
+
+/// Octave of notes written without `'` or `,`, so that `c` is the C below middle C.
+const BASE_OCTAVE: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A character that does not start a note, rest, chord or mark
+    UnexpectedCharacter,
+    /// A word that is not a rest or a note name like `fis` or `bes`
+    UnknownName,
+    UnknownCommand,
+    /// A length that is not 1, 2, 4, 8, 16, 32 or `\breve`
+    InvalidDuration,
+    /// A length with dots, or in a tuplet, that is not one of the durations of the staff
+    UnsupportedDuration,
+    /// A tuplet other than `3/2` or `5/4`
+    UnsupportedTuplet,
+    NestedTuplet,
+    UnclosedTuplet,
+    UnmatchedBrace,
+    UnclosedChord,
+    EmptyChord,
+    TooManyChordNotes,
+    /// A tie that does not connect two notes
+    MisplacedTie,
+    /// A slur opened twice, or closed without being opened
+    UnmatchedSlur,
+    UnclosedSlur,
+    /// A mark without a note or rest to mark
+    MisplacedMark,
+    InvalidChordSymbol,
+    UnterminatedString,
+    /// More music than fits in the parsed sequence
+    TooManySymbols,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParseErrorKind::UnexpectedCharacter => "unexpected character",
+            ParseErrorKind::UnknownName => "unknown note name",
+            ParseErrorKind::UnknownCommand => "unknown command",
+            ParseErrorKind::InvalidDuration => "invalid duration",
+            ParseErrorKind::UnsupportedDuration => "unsupported duration",
+            ParseErrorKind::UnsupportedTuplet => "unsupported tuplet",
+            ParseErrorKind::NestedTuplet => "tuplet inside a tuplet",
+            ParseErrorKind::UnclosedTuplet => "tuplet is not closed",
+            ParseErrorKind::UnmatchedBrace => "closing brace without a tuplet",
+            ParseErrorKind::UnclosedChord => "chord is not closed",
+            ParseErrorKind::EmptyChord => "chord without notes",
+            ParseErrorKind::TooManyChordNotes => "too many notes in chord",
+            ParseErrorKind::MisplacedTie => "tie does not connect two notes",
+            ParseErrorKind::UnmatchedSlur => "unmatched slur",
+            ParseErrorKind::UnclosedSlur => "slur is not closed",
+            ParseErrorKind::MisplacedMark => "mark without a note or rest",
+            ParseErrorKind::InvalidChordSymbol => "invalid chord symbol",
+            ParseErrorKind::UnterminatedString => "string is not closed",
+            ParseErrorKind::TooManySymbols => "too many symbols",
+        })
+    }
+}
+
+/// What is wrong with the text, and the bytes of the text where it is wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Range<usize>,
+}
+
+impl ParseError {
+    /// Line and column in the text where the error starts, both counting from 1.
+    pub fn line_column(&self, text: &str) -> (usize, usize) {
+        let before = text.get(..self.span.start).unwrap_or(text);
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+
+        (line, column)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.kind, self.span.start, self.span.end)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tuplet {
+    Triplet,
+    Quintuplet,
+}
+
+impl Tuplet {
+    /// Number of notes in the tuplet and the number of notes whose time they take.
+    fn ratio(self) -> (u8, u8) {
+        match self {
+            Tuplet::Triplet => (3, 2),
+            Tuplet::Quintuplet => (5, 4),
+        }
+    }
+
+    fn of(duration: Duration) -> Option<Self> {
+        match duration.tuplet()? {
+            (3, _) => Some(Tuplet::Triplet),
+            (5, _) => Some(Tuplet::Quintuplet),
+            _ => None,
+        }
+    }
+
+    /// The duration of a note written as the given duration inside this tuplet.
+    fn apply(self, written: Duration) -> Option<Duration> {
+        match (self, written) {
+            (Tuplet::Triplet, Duration::Quarter) => Some(Duration::QuarterTriplet),
+            (Tuplet::Triplet, Duration::Eighth) => Some(Duration::EighthTriplet),
+            (Tuplet::Triplet, Duration::Sixteenth) => Some(Duration::SixteenthTriplet),
+            (Tuplet::Quintuplet, Duration::Sixteenth) => Some(Duration::SixteenthQuintuplet),
+            _ => None,
+        }
+    }
+}
+
+fn dotted(written: Duration, dots: usize) -> Option<Duration> {
+    match (written, dots) {
+        (written, 0) => Some(written),
+        (Duration::Whole, 1) => Some(Duration::DottedWhole),
+        (Duration::Half, 1) => Some(Duration::DottedHalf),
+        (Duration::Half, 2) => Some(Duration::DoubleDottedHalf),
+        (Duration::Quarter, 1) => Some(Duration::DottedQuarter),
+        (Duration::Quarter, 2) => Some(Duration::DoubleDottedQuarter),
+        (Duration::Eighth, 1) => Some(Duration::DottedEighth),
+        (Duration::Eighth, 2) => Some(Duration::DoubleDottedEighth),
+        (Duration::Sixteenth, 1) => Some(Duration::DottedSixteenth),
+        _ => None,
+    }
+}
+
+/// Diatonic step within the octave and accidental of a note name, e.g. `fis` or `bes`.
+fn pitch(name: &str) -> Option<(i32, Accidental)> {
+    let letter = name.chars().next()?;
+    let step = match letter {
+        'c' => 0,
+        'd' => 1,
+        'e' => 2,
+        'f' => 3,
+        'g' => 4,
+        'a' => 5,
+        'b' => 6,
+        _ => return None,
+    };
+
+    // `es` and `as` are short for `ees` and `aes`
+    let accidental = match (letter, &name[1..]) {
+        (_, "") => Accidental::Natural,
+        (_, "is") => Accidental::Sharp,
+        (_, "isis") => Accidental::DoubleSharp,
+        (_, "es") | ('e' | 'a', "s") => Accidental::Flat,
+        (_, "eses") | ('e' | 'a', "ses") => Accidental::DoubleFlat,
+        _ => return None,
+    };
+
+    Some((step, accidental))
+}
+
+/// Note of a chord symbol, e.g. `Bb`, and the text after it.
+fn symbol_note(text: &str) -> Option<(Note, &str)> {
+    let step = match text.chars().next()? {
+        'C' => 0,
+        'D' => 1,
+        'E' => 2,
+        'F' => 3,
+        'G' => 4,
+        'A' => 5,
+        'B' => 6,
+        _ => return None,
+    };
+
+    let rest = &text[1..];
+    let (accidental, rest) = [
+        ("##", Accidental::DoubleSharp),
+        ("#", Accidental::Sharp),
+        ("bb", Accidental::DoubleFlat),
+        ("b", Accidental::Flat),
+    ]
+    .into_iter()
+    .find_map(|(written, accidental)| Some((accidental, rest.strip_prefix(written)?)))
+    .unwrap_or((Accidental::Natural, rest));
+
+    Some((
+        Note::from_diatonic_step(step + BASE_OCTAVE * 7, accidental),
+        rest,
+    ))
+}
+
+/// Reads a chord symbol as it is written above the staff, e.g. `Am7` or `C/E`.
+fn chord_symbol(text: &str) -> Option<ChordSymbol> {
+    let (chord, bass) = match text.split_once('/') {
+        Some((chord, bass)) => match symbol_note(bass)? {
+            (bass, "") => (chord, Some(bass)),
+            _ => return None,
+        },
+        None => (text, None),
+    };
+
+    let (root, suffix) = symbol_note(chord)?;
+    let quality = ChordQuality::ALL
+        .into_iter()
+        .find(|quality| quality.suffix() == suffix)?;
+
+    let symbol = ChordSymbol::new(root, quality);
+    Some(match bass {
+        Some(bass) => symbol.over(bass),
+        None => symbol,
+    })
+}
+
+/// The note, chord or rest that marks written after it belong to.
+#[derive(Debug, Clone, Copy)]
+struct Event {
+    /// Where in the music the marks of the event go, in front of it
+    index: usize,
+    is_note: bool,
+}
+
+struct Parser<'a, const N: usize> {
+    text: &'a str,
+    position: usize,
+    music: Vec<Music, N>,
+    /// Duration of notes and rests that are written without one
+    duration: Duration,
+    tuplet: Option<(Tuplet, Range<usize>)>,
+    last: Option<Event>,
+    /// A tie waiting for the note after it
+    tie: Option<Range<usize>>,
+    slur: Option<Range<usize>>,
+    /// Whether a note or rest came after the opening of the slur
+    slur_reached: bool,
+    ghost: Option<Range<usize>>,
+}
+
+impl<'a, const N: usize> Parser<'a, N> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            position: 0,
+            music: Vec::new(),
+            duration: Duration::Quarter,
+            tuplet: None,
+            last: None,
+            tie: None,
+            slur: None,
+            slur_reached: false,
+            ghost: None,
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind, span: Range<usize>) -> ParseError {
+        ParseError { kind, span }
+    }
+
+    /// Error for the character at the current position.
+    fn unexpected(&self) -> ParseError {
+        let length = self.text[self.position..]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+
+        self.error(
+            ParseErrorKind::UnexpectedCharacter,
+            self.position..self.position + length,
+        )
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let eaten = self.peek() == Some(byte);
+        if eaten {
+            self.position += 1;
+        }
+        eaten
+    }
+
+    fn take_while(&mut self, f: impl Fn(u8) -> bool) -> Range<usize> {
+        let start = self.position;
+        while self.peek().is_some_and(&f) {
+            self.position += 1;
+        }
+        start..self.position
+    }
+
+    /// Skips whitespace, comments from a `%` to the end of the line and bar checks.
+    fn skip_space(&mut self) {
+        loop {
+            self.take_while(|byte| byte.is_ascii_whitespace());
+            if self.eat(b'%') {
+                self.take_while(|byte| byte != b'\n');
+            } else if !self.eat(b'|') {
+                break;
+            }
+        }
+    }
+
+    fn push(&mut self, music: Music, span: Range<usize>) -> Result<(), ParseError> {
+        let error = self.error(ParseErrorKind::TooManySymbols, span);
+        self.music.push(music).map_err(|_| error)
+    }
+
+    /// Adds a mark in front of the last note, chord or rest.
+    fn mark(&mut self, music: Music, span: Range<usize>) -> Result<(), ParseError> {
+        let Some(last) = self.last.as_mut() else {
+            return Err(self.error(ParseErrorKind::MisplacedMark, span));
+        };
+
+        let index = last.index;
+        last.index += 1;
+        let error = self.error(ParseErrorKind::TooManySymbols, span);
+        self.music.insert(index, music).map_err(|_| error)
+    }
+
+    fn parse(mut self) -> Result<Vec<Music, N>, ParseError> {
+        loop {
+            self.skip_space();
+            let start = self.position;
+            let Some(byte) = self.peek() else {
+                break;
+            };
+
+            match byte {
+                b'a'..=b'z' => self.note_or_rest()?,
+                b'<' => self.chord()?,
+                b'~' => {
+                    self.position += 1;
+                    let span = start..self.position;
+                    // A tie before the first note continues it from before the music
+                    match self.last {
+                        Some(Event { is_note: true, .. }) | None if self.tie.is_none() => {
+                            self.push(Music::Tie, span.clone())?;
+                            self.tie = Some(span);
+                        }
+                        _ => return Err(self.error(ParseErrorKind::MisplacedTie, span)),
+                    }
+                }
+                b'(' => {
+                    self.position += 1;
+                    let span = start..self.position;
+                    // A slur before the first note continues it from before the music
+                    if self.slur.is_some() {
+                        return Err(self.error(ParseErrorKind::UnmatchedSlur, span));
+                    }
+                    self.slur = Some(span);
+                    self.slur_reached = false;
+                }
+                b')' => {
+                    self.position += 1;
+                    if self.slur.take().is_none() {
+                        return Err(self.error(ParseErrorKind::UnmatchedSlur, start..self.position));
+                    }
+                }
+                b'-' => {
+                    self.position += 1;
+                    let articulation = match self.peek() {
+                        Some(b'>') => Articulation::Accent,
+                        Some(b'.') => Articulation::Staccato,
+                        Some(b'-') => Articulation::Tenuto,
+                        _ => return Err(self.unexpected()),
+                    };
+                    self.position += 1;
+                    self.mark(Music::Articulation(articulation), start..self.position)?;
+                }
+                b'^' => {
+                    self.position += 1;
+                    if !self.eat(b'"') {
+                        return Err(self.unexpected());
+                    }
+                    let text = self.take_while(|byte| byte != b'"');
+                    if !self.eat(b'"') {
+                        return Err(
+                            self.error(ParseErrorKind::UnterminatedString, start..self.position)
+                        );
+                    }
+                    let symbol = chord_symbol(&self.text[text]).ok_or_else(|| {
+                        self.error(ParseErrorKind::InvalidChordSymbol, start..self.position)
+                    })?;
+                    self.mark(Music::ChordSymbol(symbol), start..self.position)?;
+                }
+                b'\\' => self.command()?,
+                b'}' => {
+                    self.position += 1;
+                    if self.tuplet.take().is_none() {
+                        return Err(
+                            self.error(ParseErrorKind::UnmatchedBrace, start..self.position)
+                        );
+                    }
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+
+        // A tie or slur after the last note continues it after the music, like the measures of a
+        // passage do over a barline
+        if let Some(tie) = self.tie.take() {
+            if self.last.is_none() {
+                return Err(self.error(ParseErrorKind::MisplacedTie, tie));
+            }
+        }
+        if let Some(slur) = self.slur.take() {
+            if self.slur_reached || self.last.is_none() {
+                return Err(self.error(ParseErrorKind::UnclosedSlur, slur));
+            }
+            self.push(Music::Slur, slur)?;
+        }
+        if let Some((_, tuplet)) = self.tuplet.take() {
+            return Err(self.error(ParseErrorKind::UnclosedTuplet, tuplet));
+        }
+        if let Some(ghost) = self.ghost.take() {
+            return Err(self.error(ParseErrorKind::MisplacedMark, ghost));
+        }
+
+        Ok(self.music)
+    }
+
+    fn note(&mut self) -> Result<Note, ParseError> {
+        let name = self.take_while(|byte| byte.is_ascii_lowercase());
+        let (step, accidental) = pitch(&self.text[name.clone()])
+            .ok_or_else(|| self.error(ParseErrorKind::UnknownName, name))?;
+
+        let mut octave = BASE_OCTAVE;
+        loop {
+            if self.eat(b'\'') {
+                octave += 1;
+            } else if self.eat(b',') {
+                octave -= 1;
+            } else {
+                break;
+            }
+        }
+
+        Ok(Note::from_diatonic_step(step + octave * 7, accidental))
+    }
+
+    fn note_or_rest(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+
+        let name = self.take_while(|byte| byte.is_ascii_lowercase());
+        if &self.text[name] == "r" {
+            let duration = self.duration(start)?;
+            return self.event(Music::Rest(duration), start);
+        }
+
+        self.position = start;
+        let note = self.note()?;
+        let duration = self.duration(start)?;
+        self.event(Music::Note(note, duration), start)
+    }
+
+    fn chord(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+        self.position += 1;
+
+        let mut notes: Vec<Note, { Chord::MAX_NOTES }> = Vec::new();
+        loop {
+            self.skip_space();
+            match self.peek() {
+                Some(b'>') => break,
+                Some(b'a'..=b'z') => {
+                    let note_start = self.position;
+                    let note = self.note()?;
+                    if notes.push(note).is_err() {
+                        return Err(self
+                            .error(ParseErrorKind::TooManyChordNotes, note_start..self.position));
+                    }
+                }
+                Some(_) => return Err(self.unexpected()),
+                None => return Err(self.error(ParseErrorKind::UnclosedChord, start..self.position)),
+            }
+        }
+        self.position += 1;
+
+        let chord = Chord::new(&notes)
+            .ok_or_else(|| self.error(ParseErrorKind::EmptyChord, start..self.position))?;
+        let duration = self.duration(start)?;
+        self.event(Music::Chord(chord, duration), start)
+    }
+
+    /// Reads the length after a note, chord or rest that starts at the given position.
+    fn duration(&mut self, start: usize) -> Result<Duration, ParseError> {
+        let length_start = self.position;
+        let written = if self.text[self.position..].starts_with("\\breve") {
+            self.position += "\\breve".len();
+            Some(Duration::Breve)
+        } else {
+            let digits = self.take_while(|byte| byte.is_ascii_digit());
+            match &self.text[digits.clone()] {
+                "" => None,
+                "1" => Some(Duration::Whole),
+                "2" => Some(Duration::Half),
+                "4" => Some(Duration::Quarter),
+                "8" => Some(Duration::Eighth),
+                "16" => Some(Duration::Sixteenth),
+                "32" => Some(Duration::ThirtySecond),
+                _ => return Err(self.error(ParseErrorKind::InvalidDuration, digits)),
+            }
+        };
+        let dots = self.take_while(|byte| byte == b'.').len();
+        let length = length_start..self.position;
+
+        let duration = match written {
+            Some(written) => dotted(written, dots)
+                .ok_or_else(|| self.error(ParseErrorKind::UnsupportedDuration, length))?,
+            None if dots == 0 => self.duration,
+            None => return Err(self.error(ParseErrorKind::InvalidDuration, length)),
+        };
+        self.duration = duration;
+
+        match self.tuplet {
+            Some((tuplet, _)) => tuplet.apply(duration).ok_or_else(|| {
+                self.error(ParseErrorKind::UnsupportedDuration, start..self.position)
+            }),
+            None => Ok(duration),
+        }
+    }
+
+    /// Adds a note, chord or rest that starts at the given position.
+    fn event(&mut self, music: Music, start: usize) -> Result<(), ParseError> {
+        let span = start..self.position;
+        let is_note = !matches!(music, Music::Rest(_));
+
+        if let Some(tie) = self.tie.take() {
+            if !is_note {
+                return Err(self.error(ParseErrorKind::MisplacedTie, tie));
+            }
+        }
+        if self.slur.is_some() {
+            self.push(Music::Slur, span.clone())?;
+            self.slur_reached = true;
+        }
+
+        let index = self.music.len();
+        if let Some(ghost) = self.ghost.take() {
+            if !is_note {
+                return Err(self.error(ParseErrorKind::MisplacedMark, ghost));
+            }
+            self.push(Music::Articulation(Articulation::Ghost), span.clone())?;
+        }
+        self.push(music, span)?;
+
+        self.last = Some(Event { index, is_note });
+        Ok(())
+    }
+
+    fn command(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+        self.position += 1;
+
+        let hairpin = match self.peek() {
+            Some(b'<') => Some(Hairpin::Crescendo),
+            Some(b'>') => Some(Hairpin::Decrescendo),
+            _ => None,
+        };
+        if let Some(hairpin) = hairpin {
+            self.position += 1;
+            return self.mark(Music::Hairpin(hairpin), start..self.position);
+        }
+
+        let name = self.take_while(|byte| byte.is_ascii_alphabetic());
+        let span = start..self.position;
+        let dynamic = match &self.text[name] {
+            "pp" => Dynamic::Pianissimo,
+            "p" => Dynamic::Piano,
+            "mp" => Dynamic::MezzoPiano,
+            "mf" => Dynamic::MezzoForte,
+            "f" => Dynamic::Forte,
+            "ff" => Dynamic::Fortissimo,
+            "xNote" => {
+                if self.ghost.is_some() {
+                    return Err(self.error(ParseErrorKind::MisplacedMark, span));
+                }
+                self.ghost = Some(span);
+                return Ok(());
+            }
+            "tuplet" => return self.tuplet(start),
+            _ => return Err(self.error(ParseErrorKind::UnknownCommand, span)),
+        };
+
+        self.mark(Music::Dynamic(dynamic), span)
+    }
+
+    /// Reads the ratio and opening brace of a tuplet, after `\tuplet`.
+    fn tuplet(&mut self, start: usize) -> Result<(), ParseError> {
+        self.skip_space();
+        let ratio_start = self.position;
+        let notes = self.take_while(|byte| byte.is_ascii_digit());
+        if notes.is_empty() {
+            return Err(self.unexpected());
+        }
+        if !self.eat(b'/') {
+            return Err(self.unexpected());
+        }
+        let time = self.take_while(|byte| byte.is_ascii_digit());
+        if time.is_empty() {
+            return Err(self.unexpected());
+        }
+
+        let tuplet = match (&self.text[notes], &self.text[time]) {
+            ("3", "2") => Tuplet::Triplet,
+            ("5", "4") => Tuplet::Quintuplet,
+            _ => {
+                return Err(self.error(
+                    ParseErrorKind::UnsupportedTuplet,
+                    ratio_start..self.position,
+                ))
+            }
+        };
+
+        self.skip_space();
+        if !self.eat(b'{') {
+            return Err(self.unexpected());
+        }
+
+        let span = start..self.position;
+        if self.tuplet.is_some() {
+            return Err(self.error(ParseErrorKind::NestedTuplet, span));
+        }
+        self.tuplet = Some((tuplet, span));
+
+        Ok(())
+    }
+}
+
+/// Reads music written in a syntax like that of LilyPond, e.g. `a,8. b,16 r8 fis8~ fis8`:
+///
+/// - Notes are written with Dutch names (`c`, `fis`, `bes`, `es`, `cisis`), in the octave
+///   below middle C, raised by each `'` and lowered by each `,` after the name. Rests are `r`.
+/// - The length follows the note: `1`, `2`, `4`, `8`, `16`, `32` or `\breve`, with dots.
+///   Without a length, a note is as long as the note or rest before it.
+/// - `<c e g>4` is a chord, `\tuplet 3/2 { c8 d e }` and `\tuplet 5/4 { ... }` are tuplets.
+/// - After a note, `~` ties it to the next note and `(` slurs it up to the note before `)`.
+///   Before the first note they continue a tie or slur from before the music, and after the
+///   last note they continue it after the music, as the measures of a passage do. `)(` closes
+///   a slur and continues it after the music.
+/// - After a note or rest: articulations `->`, `-.`, `--`, dynamics `\pp` to `\ff`, hairpins
+///   `\<` and `\>`, and chord symbols like `^"Am7"`. `\xNote` before a note makes it a ghost
+///   note.
+/// - Bar checks `|` and comments from `%` to the end of the line are skipped.
+pub fn parse<const N: usize>(text: &str) -> Result<Vec<Music, N>, ParseError> {
+    Parser::new(text).parse()
+}
+
+fn is_mark(music: &Music) -> bool {
+    matches!(
+        music,
+        Music::Articulation(_) | Music::Dynamic(_) | Music::Hairpin(_) | Music::ChordSymbol(_)
+    )
+}
+
+fn write_note(f: &mut impl Write, note: Note) -> fmt::Result {
+    let letter = match note {
+        Note::A(..) => 'a',
+        Note::B(..) => 'b',
+        Note::C(..) => 'c',
+        Note::D(..) => 'd',
+        Note::E(..) => 'e',
+        Note::F(..) => 'f',
+        Note::G(..) => 'g',
+    };
+    let short = matches!(letter, 'a' | 'e');
+    let accidental = match note.accidental() {
+        Accidental::DoubleFlat if short => "ses",
+        Accidental::DoubleFlat => "eses",
+        Accidental::Flat if short => "s",
+        Accidental::Flat => "es",
+        Accidental::Natural => "",
+        Accidental::Sharp => "is",
+        Accidental::DoubleSharp => "isis",
+    };
+    write!(f, "{letter}{accidental}")?;
+
+    let octaves = note.octave() - BASE_OCTAVE;
+    let mark = if octaves > 0 { '\'' } else { ',' };
+    for _ in 0..octaves.unsigned_abs() {
+        f.write_char(mark)?;
+    }
+
+    Ok(())
+}
+
+fn write_duration(f: &mut impl Write, duration: Duration) -> fmt::Result {
+    match duration.written() {
+        Duration::Breve => f.write_str("\\breve")?,
+        Duration::Whole | Duration::DottedWhole => f.write_char('1')?,
+        Duration::Half | Duration::DottedHalf | Duration::DoubleDottedHalf => f.write_char('2')?,
+        Duration::Eighth | Duration::DottedEighth | Duration::DoubleDottedEighth => {
+            f.write_char('8')?
+        }
+        Duration::Sixteenth | Duration::DottedSixteenth => f.write_str("16")?,
+        Duration::ThirtySecond => f.write_str("32")?,
+        _ => f.write_char('4')?,
+    }
+
+    for _ in 0..duration.dots() {
+        f.write_char('.')?;
+    }
+
+    Ok(())
+}
+
+fn write_mark(f: &mut impl Write, mark: &Music) -> fmt::Result {
+    match mark {
+        Music::Articulation(Articulation::Accent) => f.write_str("->"),
+        Music::Articulation(Articulation::Staccato) => f.write_str("-."),
+        Music::Articulation(Articulation::Tenuto) => f.write_str("--"),
+        Music::Dynamic(dynamic) => f.write_str(match dynamic {
+            Dynamic::Pianissimo => "\\pp",
+            Dynamic::Piano => "\\p",
+            Dynamic::MezzoPiano => "\\mp",
+            Dynamic::MezzoForte => "\\mf",
+            Dynamic::Forte => "\\f",
+            Dynamic::Fortissimo => "\\ff",
+        }),
+        Music::Hairpin(Hairpin::Crescendo) => f.write_str("\\<"),
+        Music::Hairpin(Hairpin::Decrescendo) => f.write_str("\\>"),
+        Music::ChordSymbol(symbol) => write!(f, "^\"{symbol}\""),
+        _ => Ok(()),
+    }
+}
+
+/// Writes music in the syntax read by `parse`, so that parsing it gives the same music back.
+/// Marks at the end of the music, without a note or rest after them, are left out.
+pub fn write(f: &mut impl Write, music: &[Music]) -> fmt::Result {
+    let mut first = true;
+    let mut tuplet = None;
+    let mut slur = false;
+    // Marks are written after the note or rest that they are in front of
+    let mut marks_start = 0;
+
+    for (index, item) in music.iter().enumerate() {
+        let duration = match item {
+            Music::Note(_, duration) | Music::Chord(_, duration) | Music::Rest(duration) => {
+                *duration
+            }
+            Music::Tie => {
+                f.write_char('~')?;
+                continue;
+            }
+            Music::Slur => {
+                // A slur after the last note is closed and opened again on it, to tell it from
+                // a slur that is not closed
+                let last = music[index + 1..].iter().all(|music| {
+                    !matches!(music, Music::Note(..) | Music::Chord(..) | Music::Rest(_))
+                });
+                if slur && last {
+                    f.write_str(")(")?;
+                } else if !slur {
+                    f.write_char('(')?;
+                    slur = true;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let item_tuplet = Tuplet::of(duration);
+        if item_tuplet != tuplet {
+            if tuplet.is_some() {
+                f.write_str(" }")?;
+            }
+            if let Some(item_tuplet) = item_tuplet {
+                let (notes, time) = item_tuplet.ratio();
+                if !first {
+                    f.write_char(' ')?;
+                }
+                write!(f, "\\tuplet {notes}/{time} {{")?;
+                first = false;
+            }
+            tuplet = item_tuplet;
+        }
+
+        if !first {
+            f.write_char(' ')?;
+        }
+        first = false;
+
+        let marks = &music[marks_start..index];
+        marks_start = index + 1;
+
+        match item {
+            Music::Note(note, _) => {
+                if marks.contains(&Music::Articulation(Articulation::Ghost)) {
+                    f.write_str("\\xNote ")?;
+                }
+                write_note(f, *note)?;
+            }
+            Music::Chord(chord, _) => {
+                if marks.contains(&Music::Articulation(Articulation::Ghost)) {
+                    f.write_str("\\xNote ")?;
+                }
+                f.write_char('<')?;
+                for (i, &note) in chord.notes().iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write_note(f, note)?;
+                }
+                f.write_char('>')?;
+            }
+            _ => f.write_char('r')?,
+        }
+        write_duration(f, duration)?;
+
+        for mark in marks {
+            write_mark(f, mark)?;
+        }
+
+        let slurred = music[index + 1..]
+            .iter()
+            .find(|music| !is_mark(music))
+            .is_some_and(|music| *music == Music::Slur);
+        if slur && !slurred {
+            f.write_char(')')?;
+            slur = false;
+        }
+    }
+
+    if tuplet.is_some() {
+        f.write_str(" }")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use core::ops::Range;
+
+use embedded_graphics::{draw_target::DrawTarget, pixelcolor::BinaryColor};
+use heapless::Vec;
+
+use crate::layout::Layout;
+use crate::staff::{
+    Clef, Duration, EngraveError, Key, Music, Staff, StaffElement, Ticks, TimeSignature,
+    DEFAULT_SYMBOL_CAPACITY,
+};
+
+pub const MAX_MEASURES: usize = 16;
+
+/// Capacity of a measure of a passage, room for the notes and rests that fit on a staff with
+/// the default capacity and for the ties, slurs and marks between them.
+pub const DEFAULT_MEASURE_CAPACITY: usize = DEFAULT_SYMBOL_CAPACITY * 2;
+
+/// Durations that notes crossing a barline are split into, longest first.
+const SPLIT_DURATIONS: [Duration; 15] = [
+    Duration::Breve,
+    Duration::DottedWhole,
+    Duration::Whole,
+    Duration::DoubleDottedHalf,
+    Duration::DottedHalf,
+    Duration::Half,
+    Duration::DoubleDottedQuarter,
+    Duration::DottedQuarter,
+    Duration::Quarter,
+    Duration::DoubleDottedEighth,
+    Duration::DottedEighth,
+    Duration::Eighth,
+    Duration::DottedSixteenth,
+    Duration::Sixteenth,
+    Duration::ThirtySecond,
+];
+
+/// Music of any length, split into measures according to its time signature and laid out over
+/// as many staff systems as needed. Only one system fits on the display, so the systems are
+/// drawn one at a time and can be scrolled through. A measure holds at most `MEASURE` music
+/// elements, see `Passage::new_in`.
+#[derive(Debug)]
+pub struct Passage<const MEASURE: usize = DEFAULT_MEASURE_CAPACITY> {
+    measures: Vec<Vec<Music, MEASURE>, MAX_MEASURES>,
+    clef: Clef,
+    key: Key,
+    time_signature: TimeSignature,
+}
+
+impl Passage {
+    pub fn new(
+        music: &[Music],
+        clef: Clef,
+        key: Key,
+        time_signature: TimeSignature,
+    ) -> Result<Self, EngraveError> {
+        Self::new_in(music, clef, key, time_signature)
+    }
+}
+
+impl<const MEASURE: usize> Passage<MEASURE> {
+    /// Splits the music into measures. Notes that cross a barline are split into notes that are
+    /// tied over the barline, rests are split without ties. Every measure holds at most `MEASURE`
+    /// music elements including ties, slurs and marks, so to draw measures of `SYMBOLS` notes on
+    /// a `Staff::<SYMBOLS, _>` the capacity needs to be about twice that, e.g.
+    /// `Passage::<64>::new_in(..)` for a staff of 32.
+    ///
+    /// Tuplets are not split, a tuplet note that crosses a barline cannot be written as tied
+    /// regular notes and gives `EngraveError::DurationNotRepresentable`. Tuplet groups have to
+    /// end before the barline.
+    pub fn new_in(
+        music: &[Music],
+        clef: Clef,
+        key: Key,
+        time_signature: TimeSignature,
+    ) -> Result<Self, EngraveError> {
+        let mut passage = Self {
+            measures: Vec::new(),
+            clef,
+            key,
+            time_signature,
+        };
+
+        let measure_length = time_signature.measure_length();
+        let mut measure: Vec<Music, MEASURE> = Vec::new();
+        let mut position = Ticks(0);
+
+        for &element in music {
+            let duration = match element {
+                Music::Note(_, duration) | Music::Chord(_, duration) | Music::Rest(duration) => {
+                    duration
+                }
+                Music::Tie | Music::Slur => {
+                    measure
+                        .push(element)
+                        .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?;
+                    continue;
+                }
+                Music::Articulation(_)
+                | Music::Dynamic(_)
+                | Music::Hairpin(_)
+                | Music::ChordSymbol(_) => {
+                    // Articulations, dynamics and chord symbols go with the next note, which
+                    // starts a new measure if this one is full
+                    if position == measure_length {
+                        measure = passage.next_measure(measure)?;
+                        position = Ticks(0);
+                    }
+                    measure
+                        .push(element)
+                        .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?;
+                    continue;
+                }
+            };
+
+            let mut remaining = duration.ticks();
+            let mut first = true;
+
+            while remaining > Ticks(0) {
+                if !first && matches!(element, Music::Note(..) | Music::Chord(..)) {
+                    measure
+                        .push(Music::Tie)
+                        .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?;
+                }
+
+                if position == measure_length {
+                    measure = passage.next_measure(measure)?;
+                    position = Ticks(0);
+                }
+
+                let space = measure_length - position;
+                let piece = if first && remaining <= space {
+                    duration
+                } else {
+                    let length = remaining.min(space);
+                    SPLIT_DURATIONS
+                        .into_iter()
+                        .find(|duration| duration.ticks() <= length)
+                        .ok_or(EngraveError::DurationNotRepresentable)?
+                };
+
+                let piece_element = match element {
+                    Music::Note(note, _) => Music::Note(note, piece),
+                    Music::Chord(chord, _) => Music::Chord(chord, piece),
+                    _ => Music::Rest(piece),
+                };
+                measure
+                    .push(piece_element)
+                    .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?;
+
+                position += piece.ticks();
+                remaining = remaining - piece.ticks();
+                first = false;
+            }
+        }
+
+        if !measure.is_empty() {
+            passage
+                .measures
+                .push(measure)
+                .map_err(|_| EngraveError::TooManyMeasures)?;
+        }
+
+        Ok(passage)
+    }
+
+    /// Stores the finished measure and starts the next one, which continues a trailing tie
+    /// or slur.
+    fn next_measure(
+        &mut self,
+        measure: Vec<Music, MEASURE>,
+    ) -> Result<Vec<Music, MEASURE>, EngraveError> {
+        let continued = measure
+            .last()
+            .copied()
+            .filter(|last| matches!(last, Music::Tie | Music::Slur));
+
+        self.measures
+            .push(measure)
+            .map_err(|_| EngraveError::TooManyMeasures)?;
+
+        let mut next = Vec::new();
+        if let Some(continued) = continued {
+            // Fits, the new measure is empty
+            let _ = next.push(continued);
+        }
+
+        Ok(next)
+    }
+
+    pub fn measures(&self) -> impl Iterator<Item = &[Music]> {
+        self.measures.iter().map(|measure| measure.as_slice())
+    }
+
+    /// Divides the measures over systems of the width of the staff, each system holds as many
+    /// measures as fit. Returns the range of measures of every system.
+    pub fn systems<const SYMBOLS: usize, const BEAM_GROUP: usize>(
+        &self,
+        staff: &Staff<SYMBOLS, BEAM_GROUP>,
+    ) -> Vec<Range<usize>, MAX_MEASURES> {
+        let width = staff.width();
+        let mut systems = Vec::new();
+        let mut start = 0;
+        let mut used = self.header_width(true);
+
+        for (index, measure) in self.measures.iter().enumerate() {
+            let previous = index
+                .checked_sub(1)
+                .map(|previous| self.measures[previous].as_slice());
+            let needed =
+                Music::minimum_width::<SYMBOLS>(measure, previous.as_slice(), self.clef, self.key)
+                    as u32
+                    + Staff::BARLINE_WIDTH;
+
+            if used + needed > width && index > start {
+                // There are at most as many systems as measures
+                let _ = systems.push(start..index);
+                start = index;
+                used = self.header_width(false);
+            }
+
+            used += needed;
+        }
+
+        if start < self.measures.len() {
+            let _ = systems.push(start..self.measures.len());
+        }
+
+        systems
+    }
+
+    /// Width of the clef and key signature at the start of a system,
+    /// the first system also shows the time signature.
+    fn header_width(&self, first: bool) -> u32 {
+        let time_signature_width = if first {
+            self.time_signature.width()
+        } else {
+            0
+        };
+
+        self.clef.width() + self.key.width() + time_signature_width
+    }
+
+    /// Draws the system with the given index (see `systems`) on the staff, with a barline after
+    /// every measure. Draws an empty staff if there is no such system. Returns where the notes
+    /// and rests were drawn, timed from the start of the system.
+    pub fn draw_system<D, const SYMBOLS: usize, const BEAM_GROUP: usize>(
+        &self,
+        target: &mut D,
+        staff: &Staff<SYMBOLS, BEAM_GROUP>,
+        system: usize,
+    ) -> Result<Layout, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let systems = self.systems(staff);
+        let measures = systems.get(system).cloned().unwrap_or_default();
+
+        let mut elements: Vec<StaffElement, { MAX_MEASURES * 2 + 3 }> = Vec::new();
+
+        // Capacity fits the header and a music element and barline per measure
+        let _ = elements.push(StaffElement::Clef(self.clef));
+        let _ = elements.push(StaffElement::KeySignature(self.key));
+        if measures.start == 0 {
+            let _ = elements.push(StaffElement::TimeSignature(self.time_signature));
+        }
+
+        for measure in &self.measures[measures] {
+            let _ = elements.push(StaffElement::Music(measure));
+            let _ = elements.push(StaffElement::Barline);
+        }
+
+        staff.draw(target, &elements)
+    }
+}
@@ -1,21 +1,30 @@
+use core::fmt::{self, Write};
+use core::ops::{Add, AddAssign, Rem, Sub};
+
 use embedded_graphics::{
     draw_target::DrawTarget,
     geometry::Point,
     pixelcolor::BinaryColor,
     prelude::Size,
     primitives::{Line, Primitive, PrimitiveStyle, Rectangle, StyledDrawable},
-    Drawable,
+    Drawable, Pixel,
 };
 use fixed::types::{I1F15, U14F2};
-use heapless::{FnvIndexMap, Vec};
+use heapless::{String, Vec};
 use log::{debug, error};
 
 use rytmos_symbols::{
-    BASS_CLEF, DOTTED_EIGHTH_REST, DOTTED_HALF_REST, DOTTED_QUARTER_REST, EIGHTH_REST, EIGHT_FLAG,
-    EMPTY_NOTEHEAD, FILLED_NOTEHEAD, HALF_REST, QUARTER_REST, SIXTEENTH_FLAG, SIXTEENTH_REST,
-    WHOLE_REST,
+    ACCENT, BASS_CLEF, BEAT_A, BEAT_AND, BEAT_E, BEAT_NUMBERS, BREVE_NOTEHEAD, BREVE_REST, C_CLEF,
+    DOTTED_EIGHTH_REST, DOTTED_HALF_REST, DOTTED_QUARTER_REST, DOTTED_SIXTEENTH_REST,
+    DOTTED_WHOLE_REST, DOUBLE_DOTTED_EIGHTH_REST, DOUBLE_DOTTED_HALF_REST,
+    DOUBLE_DOTTED_QUARTER_REST, DOUBLE_FLAT, DOUBLE_SHARP, DYNAMIC_F, DYNAMIC_M, DYNAMIC_P,
+    EIGHTH_REST, EIGHT_FLAG, EMPTY_NOTEHEAD, FILLED_NOTEHEAD, FLAT, GHOST_NOTEHEAD, HALF_REST,
+    NATURAL, NUMERALS, QUARTER_REST, SHARP, SIXTEENTH_FLAG, SIXTEENTH_REST, SMALL_NUMERALS,
+    STACCATO, TENUTO, THIRTY_SECOND_FLAG, THIRTY_SECOND_REST, TREBLE_CLEF, WHOLE_REST,
 };
 
+use crate::layout::{Inverted, Layout, LayoutTarget};
+
 use crate::frequencies::{
     MIDI_FREQUENCIES, MIDI_INCREMENTS_12000, MIDI_INCREMENTS_24000, MIDI_INCREMENTS_44100,
     MIDI_INCREMENTS_48000,
@@ -28,21 +37,362 @@ pub enum EngraveError {
     NotEnoughSpaceForSymbols,
     Impossible,
     BeatMapInsertError,
+    TooManyMeasures,
+    DurationNotRepresentable,
+}
+
+/// Default maximum amount of notes, rests and chords in a single music element.
+pub const DEFAULT_SYMBOL_CAPACITY: usize = 16;
+
+/// Default maximum amount of symbols in a single beam group,
+/// enough for six sixteenths in a dotted quarter.
+pub const DEFAULT_BEAM_GROUP_CAPACITY: usize = 6;
+
+/// How the width of a music element is divided over its notes and rests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spacing {
+    /// Every symbol gets the same space.
+    #[default]
+    Even,
+    /// Symbols get space in proportion to their duration, but at least the minimum space.
+    Proportional,
 }
 
 #[derive(Clone, Copy)]
 pub enum StaffElement<'a> {
     Music(&'a [Music]),
+    /// Two voices sharing the staff, the first above the second
+    Voices(&'a [Music], &'a [Music]),
     Barline,
     KeySignature(Key),
+    TimeSignature(TimeSignature),
     Clef(Clef),
+    /// Whether the music after it is counted, with the beat or subdivision (1 e + a) that
+    /// every note and rest starts on written below the staff
+    Counting(bool),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Music {
     Note(Note, Duration),
+    /// Several notes sharing a duration and a stem
+    Chord(Chord, Duration),
     Rest(Duration),
     Tie,
+    /// Connects the notes before and after it, e.g. a hammer-on or pull-off
+    Slur,
+    /// Marks the note or chord after it, several articulations can mark the same note
+    Articulation(Articulation),
+    /// Loudness from the note or rest after it on
+    Dynamic(Dynamic),
+    /// Change in loudness from the note or rest after it up to the next dynamic, or up to the
+    /// end of the music
+    Hairpin(Hairpin),
+    /// Harmony from the note or rest after it on, written above the staff
+    ChordSymbol(ChordSymbol),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Articulation {
+    Accent,
+    Staccato,
+    Tenuto,
+    /// Drawn as a note with an x notehead
+    Ghost,
+}
+
+impl Articulation {
+    /// Marks drawn next to the noteheads, from closest to furthest from the heads.
+    const MARKS: [Articulation; 3] = [
+        Articulation::Staccato,
+        Articulation::Tenuto,
+        Articulation::Accent,
+    ];
+
+    /// The mark and its horizontal offset from the notehead, `None` for ghost notes.
+    fn mark(self) -> Option<((u32, &'static [u32]), i32)> {
+        match self {
+            Articulation::Accent => Some((ACCENT, 0)),
+            Articulation::Staccato => Some((STACCATO, 1)),
+            Articulation::Tenuto => Some((TENUTO, 0)),
+            Articulation::Ghost => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Dynamic {
+    Pianissimo,
+    Piano,
+    MezzoPiano,
+    MezzoForte,
+    Forte,
+    Fortissimo,
+}
+
+impl Dynamic {
+    const LETTER_SPACING: i32 = 1;
+
+    fn letters(self) -> &'static [(u32, &'static [u32])] {
+        match self {
+            Dynamic::Pianissimo => &[DYNAMIC_P, DYNAMIC_P],
+            Dynamic::Piano => &[DYNAMIC_P],
+            Dynamic::MezzoPiano => &[DYNAMIC_M, DYNAMIC_P],
+            Dynamic::MezzoForte => &[DYNAMIC_M, DYNAMIC_F],
+            Dynamic::Forte => &[DYNAMIC_F],
+            Dynamic::Fortissimo => &[DYNAMIC_F, DYNAMIC_F],
+        }
+    }
+
+    fn width(self) -> i32 {
+        self.letters()
+            .iter()
+            .map(|&(width, _)| width as i32 + Self::LETTER_SPACING)
+            .sum::<i32>()
+            - Self::LETTER_SPACING
+    }
+
+    fn draw<D>(self, target: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut x = 0;
+        for &letter in self.letters() {
+            rytmos_symbols::draw_symbol(target, position + Point::new(x, 0), letter)?;
+            x += letter.0 as i32 + Self::LETTER_SPACING;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hairpin {
+    Crescendo,
+    Decrescendo,
+}
+
+/// Harmony written as text, e.g. Am7 or C/E. The octaves of the notes are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChordSymbol {
+    root: Note,
+    quality: ChordQuality,
+    bass: Option<Note>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Augmented,
+    Diminished,
+    Suspended2,
+    Suspended4,
+    Power,
+    Sixth,
+    MinorSixth,
+    Dominant7,
+    Major7,
+    Minor7,
+    HalfDiminished7,
+    Diminished7,
+    Dominant9,
+}
+
+impl ChordQuality {
+    pub(crate) const ALL: [ChordQuality; 15] = [
+        ChordQuality::Major,
+        ChordQuality::Minor,
+        ChordQuality::Augmented,
+        ChordQuality::Diminished,
+        ChordQuality::Suspended2,
+        ChordQuality::Suspended4,
+        ChordQuality::Power,
+        ChordQuality::Sixth,
+        ChordQuality::MinorSixth,
+        ChordQuality::Dominant7,
+        ChordQuality::Major7,
+        ChordQuality::Minor7,
+        ChordQuality::HalfDiminished7,
+        ChordQuality::Diminished7,
+        ChordQuality::Dominant9,
+    ];
+
+    /// What is written after the root of a chord symbol of this quality, e.g. m7 for Am7.
+    pub(crate) fn suffix(self) -> &'static str {
+        match self {
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Augmented => "+",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Suspended2 => "sus2",
+            ChordQuality::Suspended4 => "sus4",
+            ChordQuality::Power => "5",
+            ChordQuality::Sixth => "6",
+            ChordQuality::MinorSixth => "m6",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::HalfDiminished7 => "m7b5",
+            ChordQuality::Diminished7 => "dim7",
+            ChordQuality::Dominant9 => "9",
+        }
+    }
+}
+
+impl ChordSymbol {
+    pub const fn new(root: Note, quality: ChordQuality) -> Self {
+        Self {
+            root,
+            quality,
+            bass: None,
+        }
+    }
+
+    /// The chord with another note than its root in the bass, written after a slash.
+    pub const fn over(self, bass: Note) -> Self {
+        Self {
+            bass: Some(bass),
+            ..self
+        }
+    }
+
+    pub fn root(&self) -> Note {
+        self.root
+    }
+
+    pub fn quality(&self) -> ChordQuality {
+        self.quality
+    }
+
+    pub fn bass(&self) -> Option<Note> {
+        self.bass
+    }
+
+    /// Draws the chord symbol, returns the width used.
+    pub fn draw<D>(self, target: &mut D, position: Point) -> Result<u32, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut text: String<16> = String::new();
+        // The longest chord symbol is 11 characters long
+        let _ = write!(text, "{}", self);
+
+        rytmos_symbols::draw_text(target, position, &text)
+    }
+
+    fn write_note(f: &mut fmt::Formatter<'_>, note: Note) -> fmt::Result {
+        let letter = match note {
+            Note::A(..) => 'A',
+            Note::B(..) => 'B',
+            Note::C(..) => 'C',
+            Note::D(..) => 'D',
+            Note::E(..) => 'E',
+            Note::F(..) => 'F',
+            Note::G(..) => 'G',
+        };
+        let accidental = match note.accidental() {
+            Accidental::DoubleFlat => "bb",
+            Accidental::Flat => "b",
+            Accidental::Natural => "",
+            Accidental::Sharp => "#",
+            Accidental::DoubleSharp => "##",
+        };
+
+        write!(f, "{letter}{accidental}")
+    }
+}
+
+impl fmt::Display for ChordSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::write_note(f, self.root)?;
+        f.write_str(self.quality.suffix())?;
+
+        if let Some(bass) = self.bass {
+            f.write_str("/")?;
+            Self::write_note(f, bass)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Set of articulations on a symbol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Articulations(u8);
+
+impl Articulations {
+    fn insert(&mut self, articulation: Articulation) {
+        self.0 |= 1 << articulation as u8;
+    }
+
+    fn contains(self, articulation: Articulation) -> bool {
+        self.0 & (1 << articulation as u8) != 0
+    }
+}
+
+/// Notes that sound together, drawn as noteheads on a single stem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chord {
+    notes: [Note; Chord::MAX_NOTES],
+    len: usize,
+}
+
+impl Chord {
+    pub const MAX_NOTES: usize = 4;
+
+    /// Returns `None` if there are no notes or more than `MAX_NOTES`.
+    pub fn new(notes: &[Note]) -> Option<Self> {
+        let first = *notes.first()?;
+        if notes.len() > Self::MAX_NOTES {
+            return None;
+        }
+
+        let mut chord = Self {
+            notes: [first; Self::MAX_NOTES],
+            len: notes.len(),
+        };
+        chord.notes[..notes.len()].copy_from_slice(notes);
+
+        Some(chord)
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes[..self.len]
+    }
+
+    /// Notes on or above the middle line get their stem down, notes below it up. For chords the
+    /// note furthest from the middle line decides.
+    fn default_stem_direction(&self, clef: Clef) -> StemDirection {
+        let steps = self.notes().iter().map(|note| note.diatonic_step());
+        let top = steps.clone().max().unwrap_or_default();
+        let bottom = steps.min().unwrap_or_default();
+        let middle = clef.middle_line_step();
+
+        if middle - bottom > top - middle {
+            StemDirection::Up
+        } else {
+            StemDirection::Down
+        }
+    }
+}
+
+impl From<Note> for Chord {
+    fn from(note: Note) -> Self {
+        Self {
+            notes: [note; Self::MAX_NOTES],
+            len: 1,
+        }
+    }
+}
+
+/// Voices that share a staff get their stems forced and their rests moved out of the way:
+/// the upper voice has its stems up and its rests raised, the lower voice the opposite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Voice {
+    Single,
+    Upper,
+    Lower,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -52,40 +402,355 @@ enum StemDirection {
     NotApplicable,
 }
 
+/// A notehead of a note or chord.
+#[derive(Debug, Copy, Clone, Default)]
+struct Head {
+    y: i32,
+    /// Accidental to draw in front of the notehead, if any
+    accidental: Option<Accidental>,
+    /// Column of the accidental counted away from the noteheads, accidentals of heads that are
+    /// close together are drawn in separate columns
+    accidental_column: usize,
+}
+
 #[derive(Debug, Copy, Clone)]
 struct MusicSymbol {
+    /// Y of the top notehead, or of the rest
     y: i32,
     x: Option<i32>,
     stem_direction: StemDirection,
     stem_length: i32,
+    /// Duration as it is written, see `Duration::written`
     kind: Duration,
+    /// Duration as it is played
+    duration: Duration,
     rest: bool,
     tied: bool,
+    /// Whether a slur starts at this symbol
+    slurred: bool,
+    articulations: Articulations,
+    /// Dynamic drawn below this symbol
+    dynamic: Option<Dynamic>,
+    /// Hairpin that starts at this symbol
+    hairpin: Option<Hairpin>,
+    /// Chord symbol written above this symbol
+    chord_symbol: Option<ChordSymbol>,
+    /// Noteheads from top to bottom, only the first `head_count` are used
+    heads: [Head; Chord::MAX_NOTES],
+    head_count: usize,
+}
+
+impl MusicSymbol {
+    /// Horizontal distance of a notehead shifted to the other side of the stem.
+    const SHIFTED_HEAD_OFFSET: i32 = 3;
+    const DEFAULT_STEM_LENGTH: i32 = 9;
+
+    /// Only notes that have flags can be joined by beams.
+    fn beamable(&self) -> bool {
+        !self.rest && self.kind.flags() > 0
+    }
+
+    /// Whole notes and longer have no stem.
+    fn has_stem(&self) -> bool {
+        !matches!(
+            self.kind,
+            Duration::Breve | Duration::Whole | Duration::DottedWhole
+        )
+    }
+
+    /// Notes with more than two flags get longer stems to fit them.
+    fn default_stem_length(kind: Duration) -> i32 {
+        Self::DEFAULT_STEM_LENGTH + 2 * (kind.flags() as i32 - 2).max(0)
+    }
+
+    fn heads(&self) -> &[Head] {
+        &self.heads[..self.head_count]
+    }
+
+    /// Distance from the top to the bottom notehead.
+    fn span(&self) -> i32 {
+        self.heads().last().map(|head| head.y - self.y).unwrap_or(0)
+    }
+
+    /// Y of the notehead at the end of the stem, stem lengths are measured from this head.
+    fn stem_end_y(&self) -> i32 {
+        if self.stem_direction == StemDirection::Down {
+            self.y + self.span()
+        } else {
+            self.y
+        }
+    }
+
+    /// Whether two noteheads are a second apart, one of them is then drawn on the other side of
+    /// the stem.
+    fn has_seconds(&self) -> bool {
+        self.heads()
+            .windows(2)
+            .any(|pair| pair[1].y - pair[0].y == Staff::LINE_SPACING / 2)
+    }
+
+    /// Which noteheads are drawn on the other side of the stem. Starting from the head at the
+    /// start of the stem, a head a second away from an unshifted head is shifted.
+    fn shifted_heads(&self) -> [bool; Chord::MAX_NOTES] {
+        let mut shifted = [false; Chord::MAX_NOTES];
+        let heads = self.heads();
+        let mut previous: Option<(i32, bool)> = None;
+
+        let mut shift = |index: usize| {
+            let y = heads[index].y;
+            shifted[index] = previous.is_some_and(|(previous_y, previous_shifted)| {
+                !previous_shifted && (previous_y - y).abs() == Staff::LINE_SPACING / 2
+            });
+            previous = Some((y, shifted[index]));
+        };
+
+        if self.stem_direction == StemDirection::Down {
+            (0..heads.len()).for_each(&mut shift);
+        } else {
+            (0..heads.len()).rev().for_each(&mut shift);
+        }
+
+        shifted
+    }
+
+    /// The articulation marks with their position relative to the top notehead, stacked on the
+    /// side of the heads away from the stem.
+    fn articulation_marks(&self) -> impl Iterator<Item = ((u32, &'static [u32]), Point)> + '_ {
+        let below = self.stem_direction == StemDirection::Up;
+        // Row next to the heads where the next mark starts, leaving a row of space
+        let mut edge = if below { self.span() + 6 } else { -2 };
+
+        Articulation::MARKS
+            .into_iter()
+            .filter(|&articulation| self.articulations.contains(articulation))
+            .filter_map(Articulation::mark)
+            .map(move |(glyph, x)| {
+                let height = glyph.1.len() as i32;
+                let y = if below { edge } else { edge - height + 1 };
+                edge = if below { y + height + 1 } else { y - 2 };
+
+                (glyph, Point::new(x, y))
+            })
+    }
+
+    /// Y of a slur above or below the heads relative to the top notehead, outside the
+    /// articulation marks on that side.
+    fn slur_offset(&self, below: bool) -> i32 {
+        let marks_below = self.stem_direction == StemDirection::Up;
+        let offset = if below { self.span() + 6 } else { -2 };
+
+        if below != marks_below {
+            return offset;
+        }
+
+        self.articulation_marks()
+            .fold(offset, |offset, ((_, rows), mark)| {
+                if below {
+                    offset.max(mark.y + rows.len() as i32 + 1)
+                } else {
+                    offset.min(mark.y - 2)
+                }
+            })
+    }
+
+    /// Box around the noteheads, stem, accidentals and articulations, or around the rest,
+    /// relative to the position the music is drawn at. Only meaningful once the symbol has an x.
+    fn bounding_box(&self) -> Rectangle {
+        let x = self.x.unwrap_or_default();
+
+        if self.rest {
+            let (width, rows) = Music::rest_glyph(self.kind).unwrap_or((0, &[]));
+            return Rectangle::new(Point::new(x, self.y), Size::new(width, rows.len() as u32));
+        }
+
+        let bottom_head = self.y + self.span();
+        let (top, bottom) = match (self.kind, self.stem_direction) {
+            (Duration::Breve, _) => (self.y - 1, bottom_head + 5),
+            _ if !self.has_stem() => (self.y, bottom_head + 4),
+            (_, StemDirection::Up) => (self.y - self.stem_length + 1, bottom_head + 4),
+            _ => (self.y, bottom_head + self.stem_length + 3),
+        };
+        let (top, bottom) =
+            self.articulation_marks()
+                .fold((top, bottom), |(top, bottom), ((_, rows), offset)| {
+                    let mark_top = self.y + offset.y;
+                    (
+                        top.min(mark_top),
+                        bottom.max(mark_top + rows.len() as i32 - 1),
+                    )
+                });
+
+        let shifted_right = self.stem_direction == StemDirection::Up && self.has_seconds();
+        let right = x
+            + if self.kind == Duration::Breve { 6 } else { 5 }
+            + if shifted_right {
+                Self::SHIFTED_HEAD_OFFSET
+            } else {
+                0
+            };
+
+        let left = if self.kind == Duration::Breve {
+            x - 1
+        } else {
+            x
+        };
+
+        Rectangle::with_corners(
+            Point::new(left - self.accidental_space(), top),
+            Point::new(right, bottom),
+        )
+    }
+
+    /// Width of every accidental column, including a pixel of space.
+    fn accidental_columns(&self) -> [i32; Chord::MAX_NOTES] {
+        let mut widths = [0; Chord::MAX_NOTES];
+
+        for head in self.heads() {
+            if let Some(accidental) = head.accidental {
+                let width = accidental.glyph().0 .0 as i32 + 1;
+                widths[head.accidental_column] = widths[head.accidental_column].max(width);
+            }
+        }
+
+        widths
+    }
+
+    /// Horizontal space needed left of the noteheads to draw the accidentals,
+    /// and the heads that may be shifted to the left of a stem down.
+    fn accidental_space(&self) -> i32 {
+        let shifted_space = if self.has_seconds() {
+            Self::SHIFTED_HEAD_OFFSET
+        } else {
+            0
+        };
+
+        self.accidental_columns().iter().sum::<i32>() + shifted_space
+    }
 }
 
 #[derive(Debug)]
-struct MusicSymbolDefinitions {
-    symbols: Vec<MusicSymbol, 16>,
+struct MusicSymbolDefinitions<const SYMBOLS: usize> {
+    symbols: Vec<MusicSymbol, SYMBOLS>,
+    /// Last note per staff position whose accidental differs from the key signature
+    altered: Vec<Note, SYMBOLS>,
 }
 
-impl MusicSymbolDefinitions {
+impl<const SYMBOLS: usize> MusicSymbolDefinitions<SYMBOLS> {
     const REST_OFFSET: i32 = Staff::LEDGER_MARGIN + Staff::LINE_SPACING - 2;
-    const DEFAULT_STEM_LENGTH: i32 = 9;
-
-    fn new(music: &[Music]) -> Result<Self, EngraveError> {
+    /// Distance that the rests of a voice sharing the staff are moved up or down.
+    const VOICE_REST_SHIFT: i32 = Staff::LINE_SPACING * 2;
+    /// Vertical distance at which accidentals no longer overlap, a seventh.
+    const ACCIDENTAL_HEIGHT: i32 = Staff::LINE_SPACING * 3;
+
+    /// Assumes the music is a single measure: accidentals stay in force for a staff position
+    /// until the end of the music, on top of the accidentals of the key signature.
+    /// Music that starts with a tie continues the note tied over the barline before it.
+    /// A staff position that was altered in the previous measure (see `altered_accidentals`)
+    /// gets a courtesy accidental on its first note that is not altered again.
+    fn new(
+        music: &[Music],
+        previous: &[Note],
+        clef: Clef,
+        key: Key,
+        voice: Voice,
+    ) -> Result<Self, EngraveError> {
         let mut symbols = Vec::new();
 
+        // Last note per staff position in this measure, whose accidental is in force
+        let mut accidentals_in_force: Vec<Note, SYMBOLS> = Vec::new();
+        let mut last_notes: Vec<Note, { Chord::MAX_NOTES }> = Vec::new();
+        let mut tie_pending = false;
+        let mut articulations_pending = Articulations::default();
+        let mut dynamic_pending = None;
+        let mut hairpin_pending = None;
+        let mut chord_symbol_pending = None;
+
         for &symbol in music {
             match symbol {
-                Music::Note(note, duration) => {
+                Music::Note(_, duration) | Music::Chord(_, duration) => {
+                    let chord = match symbol {
+                        Music::Chord(chord, _) => chord,
+                        Music::Note(note, _) => Chord::from(note),
+                        _ => return Err(EngraveError::Impossible),
+                    };
+
+                    let mut notes: Vec<Note, { Chord::MAX_NOTES }> =
+                        chord.notes().iter().copied().collect();
+                    notes.sort_unstable_by_key(|note| -note.diatonic_step());
+
+                    let mut heads = [Head::default(); Chord::MAX_NOTES];
+
+                    for (head, &note) in heads.iter_mut().zip(notes.iter()) {
+                        let step = note.diatonic_step();
+                        let measure_in_force = accidentals_in_force
+                            .iter()
+                            .find(|in_force| in_force.diatonic_step() == step);
+                        let in_force =
+                            measure_in_force.map_or(key.accidental_for(note), Note::accidental);
+                        let courtesy = measure_in_force.is_none()
+                            && previous.iter().any(|altered| {
+                                altered.diatonic_step() == step
+                                    && altered.accidental() != note.accidental()
+                            });
+
+                        // The continuation of a tied note never repeats the accidental, music that
+                        // starts with a tie continues a note from the previous measure
+                        let continues_tie =
+                            tie_pending && (last_notes.is_empty() || last_notes.contains(&note));
+
+                        let accidental =
+                            if (note.accidental() != in_force || courtesy) && !continues_tie {
+                                Some(note.accidental())
+                            } else {
+                                None
+                            };
+
+                        // The continuation of a tie doesn't put its accidental in force, after a
+                        // note tied over the barline the next note shows the accidental again
+                        match accidentals_in_force
+                            .iter_mut()
+                            .find(|in_force| in_force.diatonic_step() == step)
+                        {
+                            _ if continues_tie => {}
+                            Some(in_force) => *in_force = note,
+                            None => accidentals_in_force
+                                .push(note)
+                                .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?,
+                        }
+
+                        *head = Head {
+                            y: note.y_offset(clef),
+                            accidental,
+                            accidental_column: 0,
+                        };
+                    }
+
+                    Self::stack_accidentals(&mut heads[..notes.len()]);
+
+                    last_notes = notes.clone();
+                    tie_pending = false;
+                    let articulations = core::mem::take(&mut articulations_pending);
+
                     let head = MusicSymbol {
-                        y: note.y_offset(),
+                        y: notes[0].y_offset(clef),
                         x: None,
-                        stem_direction: note.default_stem_direction(),
-                        stem_length: Self::DEFAULT_STEM_LENGTH,
-                        kind: duration,
+                        stem_direction: match voice {
+                            Voice::Single => chord.default_stem_direction(clef),
+                            Voice::Upper => StemDirection::Up,
+                            Voice::Lower => StemDirection::Down,
+                        },
+                        stem_length: MusicSymbol::default_stem_length(duration.written()),
+                        kind: duration.written(),
+                        duration,
                         rest: false,
                         tied: false,
+                        slurred: false,
+                        articulations,
+                        dynamic: dynamic_pending.take(),
+                        hairpin: hairpin_pending.take(),
+                        chord_symbol: chord_symbol_pending.take(),
+                        heads,
+                        head_count: notes.len(),
                     };
                     symbols
                         .push(head)
@@ -93,25 +758,94 @@ impl MusicSymbolDefinitions {
                 }
                 Music::Rest(duration) => {
                     let head = MusicSymbol {
-                        y: Self::REST_OFFSET,
+                        y: match voice {
+                            Voice::Single => Self::REST_OFFSET,
+                            Voice::Upper => Self::REST_OFFSET - Self::VOICE_REST_SHIFT,
+                            Voice::Lower => Self::REST_OFFSET + Self::VOICE_REST_SHIFT,
+                        },
                         x: None,
                         stem_direction: StemDirection::NotApplicable,
-                        stem_length: Self::DEFAULT_STEM_LENGTH,
-                        kind: duration,
+                        stem_length: MusicSymbol::DEFAULT_STEM_LENGTH,
+                        kind: duration.written(),
+                        duration,
                         rest: true,
                         tied: false,
+                        slurred: false,
+                        articulations: Articulations::default(),
+                        dynamic: dynamic_pending.take(),
+                        hairpin: hairpin_pending.take(),
+                        chord_symbol: chord_symbol_pending.take(),
+                        heads: [Head::default(); Chord::MAX_NOTES],
+                        head_count: 0,
                     };
                     symbols
                         .push(head)
                         .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?;
+                    last_notes.clear();
+                    tie_pending = false;
+                    articulations_pending = Articulations::default();
                 }
                 Music::Tie => {
                     symbols.last_mut().map(|s| s.tied = true);
+                    tie_pending = true;
+                }
+                Music::Slur => {
+                    if let Some(symbol) = symbols.last_mut() {
+                        symbol.slurred = true;
+                    }
+                }
+                Music::Articulation(articulation) => articulations_pending.insert(articulation),
+                Music::Dynamic(dynamic) => dynamic_pending = Some(dynamic),
+                Music::Hairpin(hairpin) => hairpin_pending = Some(hairpin),
+                Music::ChordSymbol(chord_symbol) => chord_symbol_pending = Some(chord_symbol),
+            }
+        }
+
+        let altered = accidentals_in_force
+            .into_iter()
+            .filter(|note| note.accidental() != key.accidental_for(*note))
+            .collect();
+
+        Ok(Self { symbols, altered })
+    }
+
+    /// The notes of the voices of a measure that alter the key signature at its end, for the
+    /// courtesy accidentals of the measure after it. Voices that can't be engraved alter nothing.
+    fn altered_accidentals(voices: &[&[Music]], clef: Clef, key: Key) -> Vec<Note, SYMBOLS> {
+        let mut altered: Vec<Note, SYMBOLS> = Vec::new();
+
+        for voice in voices {
+            let Ok(symbols) = Self::new(voice, &[], clef, key, Voice::Single) else {
+                continue;
+            };
+            for note in symbols.altered {
+                if !altered.contains(&note) {
+                    // Courtesy accidentals are left out once there are too many
+                    let _ = altered.push(note);
                 }
             }
         }
 
-        Ok(Self { symbols })
+        altered
+    }
+
+    /// Places the accidentals of a chord in columns, from top to bottom every accidental goes
+    /// in the first column where it does not overlap the accidentals above it.
+    fn stack_accidentals(heads: &mut [Head]) {
+        // Lowest head with an accidental per column
+        let mut column_bottoms: [Option<i32>; Chord::MAX_NOTES] = [None; Chord::MAX_NOTES];
+
+        for head in heads.iter_mut().filter(|head| head.accidental.is_some()) {
+            let column = column_bottoms
+                .iter()
+                .position(|bottom| {
+                    bottom.is_none_or(|bottom| head.y - bottom >= Self::ACCIDENTAL_HEIGHT)
+                })
+                .unwrap_or(0);
+
+            column_bottoms[column] = Some(head.y);
+            head.accidental_column = column;
+        }
     }
 }
 
@@ -119,19 +853,243 @@ impl MusicSymbolDefinitions {
 struct SpacedMusicSymbol {
     symbol: MusicSymbol,
     space: i32,
+    /// Part of the space before the notehead, for the accidentals of this symbol
+    /// and of symbols of another voice that are aligned with it
+    lead: i32,
 }
 
 #[derive(Debug)]
-struct SpacedMusicSymbols {
-    symbols: Vec<SpacedMusicSymbol, 16>,
+pub(crate) struct SpacedMusicSymbols<const SYMBOLS: usize> {
+    symbols: Vec<SpacedMusicSymbol, SYMBOLS>,
 }
 
-impl SpacedMusicSymbols {
+impl<const SYMBOLS: usize> SpacedMusicSymbols<SYMBOLS> {
     pub const MINIMUM_SYMBOL_SPACE: i32 = 7;
-    fn new(symbols: MusicSymbolDefinitions, width: i32) -> Result<Self, EngraveError> {
+    /// Divides the width over the symbols, after reserving the space their accidentals need.
+    fn new(
+        symbols: MusicSymbolDefinitions<SYMBOLS>,
+        width: i32,
+        spacing: Spacing,
+    ) -> Result<Self, EngraveError> {
         let mut spaced_symbols = Vec::new();
 
-        let n = symbols.symbols.len() as i32;
+        let accidentals_space: i32 = symbols
+            .symbols
+            .iter()
+            .map(|symbol| symbol.accidental_space())
+            .sum();
+        let width = width - accidentals_space;
+
+        let durations: Vec<Ticks, SYMBOLS> = symbols
+            .symbols
+            .iter()
+            .map(|symbol| symbol.duration.ticks())
+            .collect();
+        let spaces = Self::spaces(width, &durations, spacing)?;
+
+        for (symbol, space) in symbols.symbols.into_iter().zip(spaces) {
+            let spaced_symbol = SpacedMusicSymbol {
+                space: space + symbol.accidental_space(),
+                lead: symbol.accidental_space(),
+                symbol,
+            };
+            spaced_symbols
+                .push(spaced_symbol)
+                .map_err(|_| EngraveError::MoreMusicSymbolsThanSpacedSymbolsAccountedFor)?;
+        }
+
+        Ok(Self {
+            symbols: spaced_symbols,
+        })
+    }
+
+    /// Spaces two voices such that symbols that start at the same time are drawn above each
+    /// other. Every start time in either voice gets a column, the width is divided over the
+    /// columns after reserving the space the accidentals of each column need. A column lasts
+    /// until the next column starts, or until the longest voice ends.
+    fn aligned(
+        voices: [MusicSymbolDefinitions<SYMBOLS>; 2],
+        width: i32,
+        spacing: Spacing,
+    ) -> Result<[Self; 2], EngraveError> {
+        let columns = Self::columns(&voices)?;
+
+        let accidentals_space: i32 = columns.iter().map(|(_, space)| space).sum();
+        let width = width - accidentals_space;
+
+        let end = voices
+            .iter()
+            .map(|voice| {
+                voice
+                    .symbols
+                    .iter()
+                    .fold(Ticks(0), |end, symbol| end + symbol.duration.ticks())
+            })
+            .max()
+            .unwrap_or_default();
+        let durations: Vec<Ticks, SYMBOLS> = columns
+            .iter()
+            .enumerate()
+            .map(|(index, (start, _))| {
+                let next = columns.get(index + 1).map(|(next, _)| *next);
+                next.unwrap_or(end) - *start
+            })
+            .collect();
+        let spaces = Self::spaces(width, &durations, spacing)?;
+
+        // X of the start of every column
+        let mut column_x: Vec<i32, SYMBOLS> = Vec::new();
+        let mut end_x = 0;
+        for ((_, accidentals_space), space) in columns.iter().zip(spaces) {
+            // As many as there are columns, pushing cannot fail
+            let _ = column_x.push(end_x);
+            end_x += space + accidentals_space;
+        }
+
+        // Music that ends at a time without a column ends at the end of the width
+        let x_of = |time: Ticks| {
+            columns
+                .iter()
+                .position(|(start, _)| *start == time)
+                .map(|column| column_x[column])
+                .unwrap_or(end_x)
+        };
+        let lead_of = |time: Ticks| {
+            columns
+                .iter()
+                .find(|(start, _)| *start == time)
+                .map(|(_, space)| *space)
+                .unwrap_or_default()
+        };
+
+        let [upper, lower] = voices.map(|voice| {
+            let mut spaced_symbols = Vec::new();
+            let mut start = Ticks(0);
+
+            for symbol in voice.symbols {
+                let end = start + symbol.duration.ticks();
+
+                // As many as the symbols of the voice, pushing cannot fail
+                let _ = spaced_symbols.push(SpacedMusicSymbol {
+                    space: x_of(end) - x_of(start),
+                    lead: lead_of(start),
+                    symbol,
+                });
+                start = end;
+            }
+
+            Self {
+                symbols: spaced_symbols,
+            }
+        });
+
+        Ok([upper, lower])
+    }
+
+    /// Start time of every column of two aligned voices, with the space that the accidentals
+    /// in the column need. The voices together have at most `SYMBOLS` start times.
+    fn columns(
+        voices: &[MusicSymbolDefinitions<SYMBOLS>; 2],
+    ) -> Result<Vec<(Ticks, i32), SYMBOLS>, EngraveError> {
+        let mut columns: Vec<(Ticks, i32), SYMBOLS> = Vec::new();
+
+        for voice in voices {
+            let mut start = Ticks(0);
+            for symbol in &voice.symbols {
+                match columns.iter_mut().find(|(time, _)| *time == start) {
+                    Some((_, space)) => *space = (*space).max(symbol.accidental_space()),
+                    None => columns
+                        .push((start, symbol.accidental_space()))
+                        .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?,
+                }
+                start += symbol.duration.ticks();
+            }
+        }
+
+        columns.sort_unstable_by_key(|(time, _)| *time);
+
+        Ok(columns)
+    }
+
+    /// Space of every symbol or column with the given durations when dividing the width over them.
+    pub(crate) fn spaces(
+        width: i32,
+        durations: &[Ticks],
+        spacing: Spacing,
+    ) -> Result<Vec<i32, SYMBOLS>, EngraveError> {
+        let n = durations.len() as i32;
+
+        match spacing {
+            Spacing::Even => (0..n)
+                .map(|index| Self::even_space(width, n, index))
+                .collect(),
+            Spacing::Proportional => Self::proportional_spaces(width, durations),
+        }
+    }
+
+    /// Divides the whole width in proportion to the durations. Symbols whose share would be less
+    /// than the minimum space get the minimum space, the others divide the rest of the width.
+    fn proportional_spaces(
+        width: i32,
+        durations: &[Ticks],
+    ) -> Result<Vec<i32, SYMBOLS>, EngraveError> {
+        if width < durations.len() as i32 * Self::MINIMUM_SYMBOL_SPACE {
+            return Err(EngraveError::NotEnoughSpaceForSymbols);
+        }
+
+        let mut minimal: Vec<bool, SYMBOLS> = durations.iter().map(|_| false).collect();
+
+        // Every round either sets more symbols to the minimum space or settles the shares.
+        // The longest remaining symbol always gets at least the minimum, so some are left over.
+        let (free_width, free_ticks) = loop {
+            let minimal_count = minimal.iter().filter(|minimal| **minimal).count() as i32;
+            let free_width = width - minimal_count * Self::MINIMUM_SYMBOL_SPACE;
+            let free_ticks: u32 = durations
+                .iter()
+                .zip(minimal.iter())
+                .filter(|(_, minimal)| !**minimal)
+                .map(|(duration, _)| duration.0)
+                .sum();
+
+            let mut settled = true;
+            for (duration, minimal) in durations.iter().zip(minimal.iter_mut()) {
+                let share = free_width as i64 * duration.0 as i64;
+                if !*minimal && share < Self::MINIMUM_SYMBOL_SPACE as i64 * free_ticks as i64 {
+                    *minimal = true;
+                    settled = false;
+                }
+            }
+
+            if settled {
+                break (free_width as i64, free_ticks.max(1) as i64);
+            }
+        };
+
+        // Round the ends of the symbols rather than their spaces, to fill the width exactly
+        let mut spaces = Vec::new();
+        let mut ticks = 0;
+        let mut x = 0;
+        for (duration, minimal) in durations.iter().zip(minimal) {
+            let space = if minimal {
+                Self::MINIMUM_SYMBOL_SPACE
+            } else {
+                ticks += duration.0 as i64;
+                let end_x = (free_width * ticks / free_ticks) as i32;
+                let space = end_x - x;
+                x = end_x;
+                space
+            };
+
+            // As many as there are durations, pushing cannot fail
+            let _ = spaces.push(space);
+        }
+
+        Ok(spaces)
+    }
+
+    /// Space of the symbol with the given index when dividing the width evenly over n symbols,
+    /// the remainder is spread over the symbols.
+    fn even_space(width: i32, n: i32, index: i32) -> Result<i32, EngraveError> {
         let base_space = width / n;
         let extra_space = width % n;
 
@@ -141,87 +1099,87 @@ impl SpacedMusicSymbols {
             width
         };
 
-        for (index, symbol) in symbols.symbols.into_iter().enumerate() {
-            let space = if index as i32 % spaced == 0 {
-                base_space + 1
-            } else {
-                base_space
-            };
-
-            if space < Self::MINIMUM_SYMBOL_SPACE {
-                return Err(EngraveError::NotEnoughSpaceForSymbols);
-            }
+        let space = if index % spaced == 0 {
+            base_space + 1
+        } else {
+            base_space
+        };
 
-            let spaced_symbol = SpacedMusicSymbol { symbol, space };
-            spaced_symbols
-                .push(spaced_symbol)
-                .map_err(|_| EngraveError::MoreMusicSymbolsThanSpacedSymbolsAccountedFor)?;
+        if space < Self::MINIMUM_SYMBOL_SPACE {
+            return Err(EngraveError::NotEnoughSpaceForSymbols);
         }
 
-        Ok(Self {
-            symbols: spaced_symbols,
-        })
+        Ok(space)
     }
 }
 
 /// Collection of symbols that should be rendered as a single glyph,
 /// e.g. a single note, a single rest, or a group of beamed notes.
 #[derive(Debug)]
-struct GlyphDefinition {
-    symbols: Vec<SpacedMusicSymbol, 4>,
+struct GlyphDefinition<const BEAM_GROUP: usize> {
+    symbols: Vec<SpacedMusicSymbol, BEAM_GROUP>,
     beamed: bool,
 }
 
+/// Where the beam of a level above the first goes from a note of a beamed glyph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SecondaryBeam {
+    /// To the next note, which has a beam of the level as well
+    ToNext,
+    /// Broken beam facing right, for the first note of the glyph
+    BrokenRight,
+    /// Broken beam facing left
+    BrokenLeft,
+}
+
 #[derive(Debug)]
-struct Glyphs {
-    glyphs: Vec<GlyphDefinition, 16>,
+struct Glyphs<const SYMBOLS: usize, const BEAM_GROUP: usize> {
+    glyphs: Vec<GlyphDefinition<BEAM_GROUP>, SYMBOLS>,
 }
 
-impl Glyphs {
-    fn new(symbols: SpacedMusicSymbols) -> Result<Self, EngraveError> {
-        let mut beat_tracker = 0.;
+impl<const SYMBOLS: usize, const BEAM_GROUP: usize> Glyphs<SYMBOLS, BEAM_GROUP> {
+    /// Groups the symbols per beam group of the time signature, symbols are beamed within a group.
+    fn new(
+        symbols: SpacedMusicSymbols<SYMBOLS>,
+        time_signature: TimeSignature,
+    ) -> Result<Self, EngraveError> {
+        let mut beat_tracker = Ticks(0);
 
-        // assign a beat to each symbol
-        let mut beat_map: FnvIndexMap<u32, Vec<SpacedMusicSymbol, 4>, 16> = FnvIndexMap::new();
+        // Assign a beam group to each symbol, the symbols are in chronological order so a symbol
+        // is either in the last beam group or starts the next one
+        let mut beats: Vec<(u32, Vec<SpacedMusicSymbol, BEAM_GROUP>), SYMBOLS> = Vec::new();
 
         for symbol in symbols.symbols.into_iter() {
-            let duration_value = symbol.symbol.kind.value();
+            let duration_ticks = symbol.symbol.duration.ticks();
+            let index = time_signature.beam_group_index(beat_tracker);
 
-            let entry = beat_map.entry(beat_tracker as u32);
-
-            match entry {
-                heapless::Entry::Occupied(mut o) => {
-                    o.get_mut()
-                        .push(symbol)
-                        .map_err(|_| EngraveError::BeatMapInsertError)?;
-                }
-                heapless::Entry::Vacant(e) => {
-                    let mut new_vec = Vec::new();
-                    new_vec
-                        .push(symbol)
+            match beats.last_mut() {
+                Some((last_index, beat)) if *last_index == index => beat
+                    .push(symbol)
+                    .map_err(|_| EngraveError::BeatMapInsertError)?,
+                _ => {
+                    let mut beat = Vec::new();
+                    beat.push(symbol)
                         .map_err(|_| EngraveError::BeatMapInsertError)?;
-                    e.insert(new_vec)
+                    beats
+                        .push((index, beat))
                         .map_err(|_| EngraveError::BeatMapInsertError)?;
                 }
             }
 
-            beat_tracker += duration_value;
+            beat_tracker += duration_ticks;
         }
 
-        let mut glyphs: Vec<(u32, Vec<GlyphDefinition, 16>), 4> = Vec::new();
+        let mut glyphs: Vec<GlyphDefinition<BEAM_GROUP>, SYMBOLS> = Vec::new();
 
-        for (idx, beat) in beat_map.into_iter() {
-            let beat_glyphs = Self::beat_to_glyphs(beat)?;
-            glyphs
-                .push((idx, beat_glyphs))
-                .map_err(|_| EngraveError::NotEnoughSpaceForSymbols)?;
+        for (_, beat) in beats {
+            for glyph in Self::beat_to_glyphs(beat)? {
+                glyphs
+                    .push(glyph)
+                    .map_err(|_| EngraveError::NotEnoughSpaceForSymbols)?;
+            }
         }
 
-        glyphs.sort_unstable_by_key(|(idx, _)| *idx);
-
-        let mut glyphs: Vec<GlyphDefinition, 16> =
-            glyphs.into_iter().flat_map(|(_, v)| v).collect();
-
         for glyph in glyphs.iter_mut() {
             Self::fix_stems(glyph);
         }
@@ -229,17 +1187,19 @@ impl Glyphs {
         Ok(Self { glyphs })
     }
 
+    /// Beams consecutive beamable notes in a beam group, rests and longer notes get their own
+    /// glyph.
     fn beat_to_glyphs(
-        beat: Vec<SpacedMusicSymbol, 4>,
-    ) -> Result<Vec<GlyphDefinition, 16>, EngraveError> {
+        beat: Vec<SpacedMusicSymbol, BEAM_GROUP>,
+    ) -> Result<Vec<GlyphDefinition<BEAM_GROUP>, BEAM_GROUP>, EngraveError> {
         let mut glyphs = Vec::new();
 
-        let mut glyph_symbols: Vec<SpacedMusicSymbol, 4> = Vec::new();
+        let mut glyph_symbols: Vec<SpacedMusicSymbol, BEAM_GROUP> = Vec::new();
         for symbol in beat.into_iter() {
             let glyph_is_notes =
-                !glyph_symbols.is_empty() && !glyph_symbols.first().unwrap().symbol.rest;
+                !glyph_symbols.is_empty() && glyph_symbols.first().unwrap().symbol.beamable();
 
-            let symbol_is_note = !symbol.symbol.rest;
+            let symbol_is_note = symbol.symbol.beamable();
 
             if glyph_is_notes && symbol_is_note {
                 // if we're doing notes and this is a note, add it to the symbols and continue
@@ -247,7 +1207,7 @@ impl Glyphs {
                     .push(symbol)
                     .map_err(|_| EngraveError::NotEnoughSpaceForSymbols)?;
             } else if glyph_is_notes && !symbol_is_note {
-                // if we're doing notes and this is a rest, add a glyph and clear glyph_symbols, then add the rest glyph
+                // if we're doing notes and this is a rest or longer note, add a glyph and clear glyph_symbols, then add its own glyph
                 // Manual drain implementation
                 let mut moved_symbols = Vec::new();
                 while !glyph_symbols.is_empty() {
@@ -275,7 +1235,7 @@ impl Glyphs {
                     })
                     .map_err(|_| EngraveError::NotEnoughSpaceForSymbols)?;
             } else if glyph_symbols.is_empty() {
-                if symbol.symbol.rest {
+                if !symbol_is_note {
                     let mut rest_symbol = Vec::new();
                     rest_symbol
                         .push(symbol)
@@ -297,7 +1257,7 @@ impl Glyphs {
                     glyph_is_notes,
                     symbol_is_note,
                     !glyph_symbols.is_empty(),
-                    !glyph_symbols.first().unwrap().symbol.rest,
+                    glyph_symbols.first().unwrap().symbol.beamable(),
                     &glyph_symbols
                 );
 
@@ -319,13 +1279,13 @@ impl Glyphs {
     }
 
     // TODO: unwraps in this function...
-    fn fix_stems(glyph: &mut GlyphDefinition) -> &mut GlyphDefinition {
+    fn fix_stems(glyph: &mut GlyphDefinition<BEAM_GROUP>) -> &mut GlyphDefinition<BEAM_GROUP> {
         if glyph.symbols.len() == 1 {
             return glyph;
         }
 
         // Set the direction of the stems to the same value for each
-        let mut stem_directions: Vec<StemDirection, 4> = Vec::new();
+        let mut stem_directions: Vec<StemDirection, BEAM_GROUP> = Vec::new();
         for symbol in glyph.symbols.iter_mut() {
             stem_directions.push(symbol.symbol.stem_direction).unwrap();
         }
@@ -356,10 +1316,10 @@ impl Glyphs {
         } else {
             1
         };
-        let stem_endpoints: Vec<i32, 4> = glyph
+        let stem_endpoints: Vec<i32, BEAM_GROUP> = glyph
             .symbols
             .iter()
-            .map(|s| s.symbol.y + stem_offset * MusicSymbolDefinitions::DEFAULT_STEM_LENGTH)
+            .map(|s| s.symbol.stem_end_y() + stem_offset * s.symbol.stem_length)
             .collect();
 
         let most_extreme_endpoint = if direction == StemDirection::Up {
@@ -368,101 +1328,616 @@ impl Glyphs {
             stem_endpoints.iter().max().unwrap()
         };
 
-        for symbol in glyph.symbols.iter_mut() {
-            symbol.symbol.stem_length = stem_offset * (most_extreme_endpoint - symbol.symbol.y);
+        for symbol in glyph.symbols.iter_mut() {
+            symbol.symbol.stem_length =
+                stem_offset * (most_extreme_endpoint - symbol.symbol.stem_end_y());
+        }
+
+        glyph
+    }
+}
+
+/// Where the text and marks around the music go, shared by the voices on a staff so that they
+/// line up.
+#[derive(Debug, Clone, Copy)]
+struct Annotations {
+    /// Y of the bottom of chord symbols, a few pixels above the top staff line unless the music
+    /// reaches higher
+    above: i32,
+    /// Y of the top of the counting and dynamics, a few pixels below the bottom staff line unless
+    /// the music reaches lower
+    below: i32,
+    /// Time signature to count the music in, if it is counted
+    counting: Option<TimeSignature>,
+}
+
+impl Annotations {
+    const ABOVE_OFFSET: i32 = Staff::LEDGER_MARGIN - 3;
+    const BELOW_OFFSET: i32 = Staff::LEDGER_MARGIN + Staff::LINE_SPACING * 4 + 7;
+    const TEXT_HEIGHT: i32 = 5;
+    const COUNT_HEIGHT: i32 = 4;
+
+    fn new<const SYMBOLS: usize, const BEAM_GROUP: usize>(
+        voices: &[&Glyphs<SYMBOLS, BEAM_GROUP>],
+        counting: Option<TimeSignature>,
+    ) -> Self {
+        let bounding_boxes = || {
+            voices
+                .iter()
+                .flat_map(|glyphs| glyphs.glyphs.iter())
+                .flat_map(|glyph| glyph.symbols.iter())
+                .map(|symbol| symbol.symbol.bounding_box())
+        };
+
+        let above = bounding_boxes()
+            .map(|bounding_box| bounding_box.top_left.y - 2)
+            .fold(Self::ABOVE_OFFSET, i32::min)
+            .max(Self::TEXT_HEIGHT - 1);
+        let below = bounding_boxes()
+            .map(|bounding_box| bounding_box.top_left.y + bounding_box.size.height as i32 + 1)
+            .fold(Self::BELOW_OFFSET, i32::max);
+
+        Self {
+            above,
+            below,
+            counting,
+        }
+    }
+}
+
+impl Music {
+    /// Draws notes, rests, ties, and other music defining notation.
+    /// Notes are placed on the staff according to the given clef,
+    /// and beamed according to the beam groups of the given time signature.
+    /// The voices of the measure before give courtesy accidentals, see `Staff::draw`.
+    /// The positions of the notes and rests are added to the layout.
+    /// x Makes a collection of noteheads that should be rendered
+    /// x Calculates the free space that nodeheads can maximally move to the left and right
+    ///     x This is the range that the notehead can exist in
+    /// x Calculates the Y drawing position of the notehead (relative to the given position)
+    /// x Assert that all ranges are at least 7 pixels
+    /// x Determines which notes should be tied using the following criteria:
+    ///     x The notes must start in the same beam group (assumes that vec start = bar start)
+    ///     x The notes are eighth, dotted eigtht, or sixteenth duration
+    ///     x The notes are consecutive
+    ///     x Does NOT take tie-ing into account, any group of notes that follow these criteria are beamed
+    /// x This results in a list of glyphs, each containing a vec of symbols
+    ///     with their available space. If the Vec contains more than one note, it is beamed
+    /// - Actually performs the drawing of the glyps, here finally the position argument is applied to everything:
+    ///     - For each notehead, compute the necessary space:
+    ///         - whole, half, and quarter notes don't need space for flags
+    ///         - Beamed notes may need some space dependending on their position and orientation
+    ///     - Move noteheads around to provide necessary space
+    ///     - Notes without beams can be drawn trivially using their symbol (with stem in correct direction)
+    ///     - Beamed notes need 4 drawing steps
+    ///         - 1) Draw the noteheads
+    ///         - Determine the direction of all stems (follow majority)
+    ///         - Determine the y position of the beam (all beams are strictly horizontal)
+    ///         - 2) Draw all stems starting at the notehead up (down) to the computed y
+    ///         - 3) Draw a horizontal line for the beam
+    ///         - 4) Draw additional horizontal lines for sixteenths
+    ///     - Rests are drawn using their symbol at a specific hard coded y position
+    /// TODO: ledger lines for high / low notes
+    /// TODO: interactive rhythm definition is simulatable using button events and showing toggle switch state in console (or on screen)
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw<D, const SYMBOLS: usize, const BEAM_GROUP: usize>(
+        target: &mut D,
+        position: Point,
+        width: i32,
+        music: &[Music],
+        previous: &[&[Music]],
+        clef: Clef,
+        key: Key,
+        time_signature: TimeSignature,
+        spacing: Spacing,
+        counting: bool,
+        layout: &mut Layout,
+    ) -> Result<u32, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let previous = MusicSymbolDefinitions::<SYMBOLS>::altered_accidentals(previous, clef, key);
+        let symbols =
+            MusicSymbolDefinitions::<SYMBOLS>::new(music, &previous, clef, key, Voice::Single)
+                .and_then(|symbols| SpacedMusicSymbols::new(symbols, width, spacing))
+                .and_then(|symbols| Glyphs::<SYMBOLS, BEAM_GROUP>::new(symbols, time_signature));
+
+        // Music that can't be engraved leaves its width empty
+        let glyphs = match symbols {
+            Ok(glyphs) => glyphs,
+            Err(error) => {
+                error!("Error engraving symbols: {:?}", error);
+                layout.extend(Self::length(music));
+                return Ok(width as u32);
+            }
+        };
+        let annotations = Annotations::new(&[&glyphs], counting.then_some(time_signature));
+        Self::draw_glyphs(target, position, width, music, glyphs, annotations, layout)?;
+        layout.extend(Self::length(music));
+
+        Ok(width as u32)
+    }
+
+    /// Draws two voices on the same staff, the upper voice with its stems up and the lower voice
+    /// with its stems down. Notes and rests that start at the same time in both voices are
+    /// aligned. Accidentals are shown per voice, courtesy accidentals for both voices of the
+    /// measure before. The positions of the notes and rests of both
+    /// voices are added to the layout, those of the upper voice first.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_voices<D, const SYMBOLS: usize, const BEAM_GROUP: usize>(
+        target: &mut D,
+        position: Point,
+        width: i32,
+        upper: &[Music],
+        lower: &[Music],
+        previous: &[&[Music]],
+        clef: Clef,
+        key: Key,
+        time_signature: TimeSignature,
+        spacing: Spacing,
+        counting: bool,
+        layout: &mut Layout,
+    ) -> Result<u32, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let previous = MusicSymbolDefinitions::<SYMBOLS>::altered_accidentals(previous, clef, key);
+        let symbols =
+            MusicSymbolDefinitions::<SYMBOLS>::new(upper, &previous, clef, key, Voice::Upper)
+                .and_then(|upper_symbols| {
+                    MusicSymbolDefinitions::new(lower, &previous, clef, key, Voice::Lower)
+                        .map(|lower_symbols| [upper_symbols, lower_symbols])
+                })
+                .and_then(|voices| SpacedMusicSymbols::aligned(voices, width, spacing))
+                .and_then(|[upper_symbols, lower_symbols]| {
+                    Ok([
+                        Glyphs::<SYMBOLS, BEAM_GROUP>::new(upper_symbols, time_signature)?,
+                        Glyphs::<SYMBOLS, BEAM_GROUP>::new(lower_symbols, time_signature)?,
+                    ])
+                });
+
+        let [upper_glyphs, lower_glyphs] = match symbols {
+            Ok(glyphs) => glyphs,
+            Err(error) => {
+                error!("Error engraving voices: {:?}", error);
+                layout.extend(Self::length(upper).max(Self::length(lower)));
+                return Ok(width as u32);
+            }
+        };
+        let annotations = Annotations::new(
+            &[&upper_glyphs, &lower_glyphs],
+            counting.then_some(time_signature),
+        );
+        Self::draw_glyphs(
+            target,
+            position,
+            width,
+            upper,
+            upper_glyphs,
+            annotations,
+            layout,
+        )?;
+        Self::draw_glyphs(
+            target,
+            position,
+            width,
+            lower,
+            lower_glyphs,
+            annotations,
+            layout,
+        )?;
+        layout.extend(Self::length(upper).max(Self::length(lower)));
+
+        Ok(width as u32)
+    }
+
+    /// Draws the glyphs of a voice, followed by its ties, tuplets and annotations.
+    fn draw_glyphs<D, const SYMBOLS: usize, const BEAM_GROUP: usize>(
+        target: &mut D,
+        position: Point,
+        width: i32,
+        music: &[Music],
+        mut glyphs: Glyphs<SYMBOLS, BEAM_GROUP>,
+        annotations: Annotations,
+        layout: &mut Layout,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut x = 0;
+        let mut start = Ticks(0);
+        for glyph in glyphs.glyphs.iter_mut() {
+            for symbol in glyph.symbols.iter_mut() {
+                let head_x = x + symbol.lead;
+                Self::draw_spaced_music_symbol(target, position, head_x, symbol, glyph.beamed)?;
+                symbol.symbol.x = Some(head_x);
+
+                let bounding_box = symbol.symbol.bounding_box();
+                layout.push(
+                    start,
+                    symbol.symbol.duration,
+                    symbol.symbol.rest,
+                    Rectangle::new(position + bounding_box.top_left, bounding_box.size),
+                );
+
+                x += symbol.space;
+                start += symbol.symbol.duration.ticks();
+            }
+
+            if glyph.beamed {
+                Self::draw_beams(target, position, glyph)?;
+            }
+        }
+
+        // Draw ties and other things that might need x info
+        let mut last_symbol: Option<&SpacedMusicSymbol> = None;
+        for glyph in glyphs.glyphs.iter_mut() {
+            for symbol in glyph.symbols.iter() {
+                let tied = if let Some(symbol) = last_symbol {
+                    symbol.symbol.tied
+                } else {
+                    false
+                };
+
+                if tied {
+                    // Unwrap is safe because tied can only be true if last symbol is some
+                    Self::draw_tie(target, position, last_symbol.unwrap(), symbol)?;
+                }
+
+                if let Some(last_symbol) = last_symbol.filter(|last| last.symbol.slurred) {
+                    Self::draw_slur(target, position, last_symbol, symbol)?;
+                }
+
+                last_symbol = Some(symbol)
+            }
+        }
+
+        // Ties and slurs over the barlines around this music end at the edges of the given width
+        let symbols = || glyphs.glyphs.iter().flat_map(|glyph| glyph.symbols.iter());
+        if let (Some(Music::Tie | Music::Slur), Some(first)) = (music.first(), symbols().next()) {
+            let edge = SpacedMusicSymbol {
+                symbol: MusicSymbol {
+                    x: Some(-5),
+                    ..first.symbol
+                },
+                space: 0,
+                lead: 0,
+            };
+            if music.first() == Some(&Music::Tie) {
+                Self::draw_tie(target, position, &edge, first)?;
+            } else {
+                Self::draw_slur(target, position, &edge, first)?;
+            }
+        }
+        if let Some(last) = symbols().last() {
+            let edge = SpacedMusicSymbol {
+                symbol: MusicSymbol {
+                    x: Some(width - 2),
+                    ..last.symbol
+                },
+                space: 0,
+                lead: 0,
+            };
+            if last.symbol.tied {
+                Self::draw_tie(target, position, last, &edge)?;
+            }
+            if last.symbol.slurred {
+                Self::draw_slur(target, position, last, &edge)?;
+            }
+        }
+
+        Self::draw_annotations(target, position, symbols(), annotations)?;
+        Self::draw_dynamics(target, position, width, symbols(), annotations)?;
+        Self::draw_tuplets(target, position, &glyphs)?;
+
+        Ok(())
+    }
+
+    /// Draws the chord symbols of the symbols above the staff, and counts the symbols below the
+    /// staff if the music is counted.
+    fn draw_annotations<'a, D>(
+        target: &mut D,
+        position: Point,
+        symbols: impl Iterator<Item = &'a SpacedMusicSymbol>,
+        annotations: Annotations,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut start = Ticks(0);
+
+        for symbol in symbols {
+            let x = symbol.symbol.x.unwrap_or(0);
+
+            if let Some(chord_symbol) = symbol.symbol.chord_symbol {
+                let top = annotations.above - Annotations::TEXT_HEIGHT + 1;
+                chord_symbol.draw(target, position + Point::new(x, top))?;
+            }
+
+            if let Some(time_signature) = annotations.counting {
+                let count = time_signature.count(start);
+                // Centered below the notehead
+                let count_width = count
+                    .iter()
+                    .map(|(width, _)| *width as i32 + 1)
+                    .sum::<i32>();
+                let mut count_x = x + 3 - count_width / 2;
+                for glyph in count {
+                    let count_position = Point::new(count_x, annotations.below);
+                    rytmos_symbols::draw_symbol(target, position + count_position, glyph)?;
+                    count_x += glyph.0 as i32 + 1;
+                }
+            }
+
+            start += symbol.symbol.duration.ticks();
+        }
+
+        Ok(())
+    }
+
+    /// Draws the dynamics and hairpins of the symbols below the staff, below the counting if the
+    /// music is counted. A hairpin ends before the next dynamic or hairpin, or at the end of the
+    /// given width.
+    fn draw_dynamics<'a, D>(
+        target: &mut D,
+        position: Point,
+        width: i32,
+        symbols: impl Iterator<Item = &'a SpacedMusicSymbol> + Clone,
+        annotations: Annotations,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+
+        let y = match annotations.counting {
+            Some(_) => annotations.below + Annotations::COUNT_HEIGHT + 2,
+            None => annotations.below,
+        };
+
+        for (index, symbol) in symbols.clone().enumerate() {
+            let x = symbol.symbol.x.unwrap_or(0);
+            let mut start = x;
+
+            if let Some(dynamic) = symbol.symbol.dynamic {
+                dynamic.draw(target, position + Point::new(x, y))?;
+                start += dynamic.width() + 2;
+            }
+
+            let Some(hairpin) = symbol.symbol.hairpin else {
+                continue;
+            };
+
+            let end = symbols
+                .clone()
+                .skip(index + 1)
+                .find(|next| next.symbol.dynamic.is_some() || next.symbol.hairpin.is_some())
+                .and_then(|next| next.symbol.x)
+                .unwrap_or(width)
+                - 2;
+
+            // Lines that meet at the middle of the letters, open by a letter height at the other
+            // end
+            let middle = y + 3;
+            let (closed, open) = match hairpin {
+                Hairpin::Crescendo => (start, end),
+                Hairpin::Decrescendo => (end, start),
+            };
+            for side in [-2, 2] {
+                Line::new(
+                    position + Point::new(closed, middle),
+                    position + Point::new(open, middle + side),
+                )
+                .draw_styled(&style, target)?;
+            }
         }
 
-        glyph
+        Ok(())
     }
-}
 
-impl Music {
-    /// Draws notes, rests, ties, and other music defining notation.
-    /// Assumes a beat consists of a quarter note.
-    /// x Makes a collection of noteheads that should be rendered
-    /// x Calculates the free space that nodeheads can maximally move to the left and right
-    ///     x This is the range that the notehead can exist in
-    /// x Calculates the Y drawing position of the notehead (relative to the given position)
-    /// x Assert that all ranges are at least 7 pixels
-    /// x Determines which notes should be tied using the following criteria:
-    ///     x The notes must start in the same beat (assumes 4/4, and that vec start = bar start)
-    ///     x The notes are eighth, dotted eigtht, or sixteenth duration
-    ///     x The notes are consecutive
-    ///     x Does NOT take tie-ing into account, any group of notes that follow these criteria are beamed
-    /// x This results in a list of glyphs, each containing a vec of symbols
-    ///     with their available space. If the Vec contains more than one note, it is beamed
-    /// - Actually performs the drawing of the glyps, here finally the position argument is applied to everything:
-    ///     - For each notehead, compute the necessary space:
-    ///         - whole, half, and quarter notes don't need space for flags
-    ///         - Beamed notes may need some space dependending on their position and orientation
-    ///     - Move noteheads around to provide necessary space
-    ///     - Notes without beams can be drawn trivially using their symbol (with stem in correct direction)
-    ///     - Beamed notes need 4 drawing steps
-    ///         - 1) Draw the noteheads
-    ///         - Determine the direction of all stems (follow majority)
-    ///         - Determine the y position of the beam (all beams are strictly horizontal)
-    ///         - 2) Draw all stems starting at the notehead up (down) to the computed y
-    ///         - 3) Draw a horizontal line for the beam
-    ///         - 4) Draw additional horizontal lines for sixteenths
-    ///     - Rests are drawn using their symbol at a specific hard coded y position
-    /// TODO: ledger lines for high / low notes
-    /// TODO: interactive rhythm definition is simulatable using button events and showing toggle switch state in console (or on screen)
-    pub fn draw<D>(
+    /// Time that the music takes.
+    fn length(music: &[Music]) -> Ticks {
+        music
+            .iter()
+            .map(|element| match element {
+                Music::Note(_, duration) | Music::Chord(_, duration) | Music::Rest(duration) => {
+                    duration.ticks()
+                }
+                Music::Tie
+                | Music::Slur
+                | Music::Articulation(_)
+                | Music::Dynamic(_)
+                | Music::Hairpin(_)
+                | Music::ChordSymbol(_) => Ticks(0),
+            })
+            .fold(Ticks(0), |length, ticks| length + ticks)
+    }
+
+    /// The least width that the music can be drawn in, after the given measure.
+    pub(crate) fn minimum_width<const SYMBOLS: usize>(
+        music: &[Music],
+        previous: &[&[Music]],
+        clef: Clef,
+        key: Key,
+    ) -> i32 {
+        let previous = MusicSymbolDefinitions::<SYMBOLS>::altered_accidentals(previous, clef, key);
+        MusicSymbolDefinitions::<SYMBOLS>::new(music, &previous, clef, key, Voice::Single)
+            .map(|symbols| {
+                symbols
+                    .symbols
+                    .iter()
+                    .map(|symbol| {
+                        SpacedMusicSymbols::<SYMBOLS>::MINIMUM_SYMBOL_SPACE
+                            + symbol.accidental_space()
+                    })
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The least width that two voices can be drawn in, see `draw_voices`.
+    pub(crate) fn minimum_voices_width<const SYMBOLS: usize>(
+        upper: &[Music],
+        lower: &[Music],
+        previous: &[&[Music]],
+        clef: Clef,
+        key: Key,
+    ) -> i32 {
+        let previous = MusicSymbolDefinitions::<SYMBOLS>::altered_accidentals(previous, clef, key);
+        MusicSymbolDefinitions::<SYMBOLS>::new(upper, &previous, clef, key, Voice::Upper)
+            .and_then(|upper_symbols| {
+                MusicSymbolDefinitions::new(lower, &previous, clef, key, Voice::Lower)
+                    .map(|lower_symbols| [upper_symbols, lower_symbols])
+            })
+            .and_then(|voices| SpacedMusicSymbols::columns(&voices))
+            .map(|columns| {
+                columns
+                    .iter()
+                    .map(|(_, space)| SpacedMusicSymbols::<SYMBOLS>::MINIMUM_SYMBOL_SPACE + space)
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Draws the number of every tuplet group, with a bracket unless the group is exactly
+    /// one beamed glyph with stems up, in which case the number sits on the beam.
+    /// A group ends once its notes fill the time of the tuplet, or at a different duration.
+    fn draw_tuplets<D, const SYMBOLS: usize, const BEAM_GROUP: usize>(
         target: &mut D,
         position: Point,
-        width: i32,
-        music: &[Music],
-    ) -> Result<u32, D::Error>
+        glyphs: &Glyphs<SYMBOLS, BEAM_GROUP>,
+    ) -> Result<(), D::Error>
     where
         D: DrawTarget<Color = BinaryColor>,
     {
-        // TODO: fix all the unwraps by drawing different things
-        let symbols = MusicSymbolDefinitions::new(music)
-            .and_then(|symbols| SpacedMusicSymbols::new(symbols, width))
-            .and_then(Glyphs::new);
-
-        if symbols.is_err() {
-            error!("Error engraving symbols: {:?}", symbols);
-        }
+        let mut group: Vec<(usize, &SpacedMusicSymbol), SYMBOLS> = Vec::new();
+        let mut group_time = Ticks(0);
 
-        let mut glyphs = symbols.unwrap();
+        let symbols = glyphs
+            .glyphs
+            .iter()
+            .enumerate()
+            .flat_map(|(index, glyph)| glyph.symbols.iter().map(move |symbol| (index, symbol)));
 
-        let mut x = 0;
-        for glyph in glyphs.glyphs.iter_mut() {
-            let glyph_start_x = x;
+        for (index, symbol) in symbols {
+            let tuplet = symbol.symbol.duration.tuplet();
 
-            for symbol in glyph.symbols.iter_mut() {
-                Self::draw_spaced_music_symbol(target, position, x, symbol, glyph.beamed)?;
-                symbol.symbol.x = Some(x);
+            let group_tuplet = group
+                .first()
+                .and_then(|(_, first)| first.symbol.duration.tuplet());
 
-                x += symbol.space;
+            if !group.is_empty() && tuplet.map(|(n, _)| n) != group_tuplet.map(|(n, _)| n) {
+                Self::draw_tuplet(target, position, glyphs, &group)?;
+                group.clear();
+                group_time = Ticks(0);
             }
 
-            if glyph.beamed {
-                Self::draw_beams(target, position, glyph_start_x, glyph)?;
+            if let Some((_, span)) = tuplet {
+                // Group is at most as long as the symbols, pushing cannot fail
+                let _ = group.push((index, symbol));
+                group_time += symbol.symbol.duration.ticks();
+
+                let (_, group_span) = group_tuplet.unwrap_or((0, span));
+                if group_time >= group_span {
+                    Self::draw_tuplet(target, position, glyphs, &group)?;
+                    group.clear();
+                    group_time = Ticks(0);
+                }
             }
         }
 
-        // Draw ties and other things that might need x info
-        let mut last_symbol: Option<&SpacedMusicSymbol> = None;
-        for glyph in glyphs.glyphs.iter_mut() {
-            for symbol in glyph.symbols.iter() {
-                let tied = if let Some(symbol) = last_symbol {
-                    symbol.symbol.tied
-                } else {
-                    false
-                };
+        if !group.is_empty() {
+            Self::draw_tuplet(target, position, glyphs, &group)?;
+        }
 
-                if tied {
-                    // Unwrap is safe because tied can only be true if last symbol is some
-                    Self::draw_tie(target, position, last_symbol.unwrap(), symbol)?;
-                }
+        Ok(())
+    }
 
-                last_symbol = Some(symbol)
-            }
+    fn draw_tuplet<D, const SYMBOLS: usize, const BEAM_GROUP: usize>(
+        target: &mut D,
+        position: Point,
+        glyphs: &Glyphs<SYMBOLS, BEAM_GROUP>,
+        group: &[(usize, &SpacedMusicSymbol)],
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let (Some((first_index, first)), Some((_, last))) = (group.first(), group.last()) else {
+            return Ok(());
+        };
+        let Some((number, _)) = first.symbol.duration.tuplet() else {
+            return Ok(());
+        };
+
+        let style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+        let off_style = PrimitiveStyle::with_stroke(BinaryColor::Off, 1);
+
+        let glyph = &glyphs.glyphs[*first_index];
+        let on_beam = glyph.beamed
+            && glyph.symbols.len() == group.len()
+            && group.iter().all(|(index, _)| index == first_index)
+            && first.symbol.stem_direction == StemDirection::Up;
+
+        // Highest point of the group, never lower than the top staff line
+        let top = group
+            .iter()
+            .map(|(_, symbol)| match symbol.symbol.stem_direction {
+                StemDirection::Up => symbol.symbol.y - symbol.symbol.stem_length + 1,
+                StemDirection::Down => symbol.symbol.y,
+                StemDirection::NotApplicable => symbol.symbol.y,
+            })
+            .fold(Staff::LEDGER_MARGIN, i32::min);
+
+        let start_x = first.symbol.x.unwrap_or_default();
+        let end_x = last.symbol.x.unwrap_or_default() + 4;
+        let center_x = (start_x + end_x) / 2;
+        let y = top - 4;
+
+        let numeral = SMALL_NUMERALS[(number % 10) as usize];
+        let numeral_position = position + Point::new(center_x - 1, y - 2);
+
+        if on_beam {
+            rytmos_symbols::draw_symbol(target, numeral_position, numeral)?;
+            return Ok(());
         }
 
-        Ok(0)
+        let left = position + Point::new(start_x, y);
+        let right = position + Point::new(end_x, y);
+        Line::new(left, right).draw_styled(&style, target)?;
+        Line::new(left, left + Point::new(0, 2)).draw_styled(&style, target)?;
+        Line::new(right, right + Point::new(0, 2)).draw_styled(&style, target)?;
+
+        // Leave a gap in the bracket for the number
+        let gap = position + Point::new(center_x - 2, y);
+        Line::new(gap, gap + Point::new(4, 0)).draw_styled(&off_style, target)?;
+        rytmos_symbols::draw_symbol(target, numeral_position, numeral)?;
+
+        Ok(())
+    }
+
+    /// Symbols only hold written durations, tuplets have no rest of their own.
+    pub(crate) fn rest_glyph(kind: Duration) -> Option<(u32, &'static [u32])> {
+        match kind {
+            Duration::Breve => Some(BREVE_REST),
+            Duration::DottedWhole => Some(DOTTED_WHOLE_REST),
+            Duration::Whole => Some(WHOLE_REST),
+            Duration::DoubleDottedHalf => Some(DOUBLE_DOTTED_HALF_REST),
+            Duration::DottedHalf => Some(DOTTED_HALF_REST),
+            Duration::Half => Some(HALF_REST),
+            Duration::DoubleDottedQuarter => Some(DOUBLE_DOTTED_QUARTER_REST),
+            Duration::DottedQuarter => Some(DOTTED_QUARTER_REST),
+            Duration::Quarter => Some(QUARTER_REST),
+            Duration::DoubleDottedEighth => Some(DOUBLE_DOTTED_EIGHTH_REST),
+            Duration::DottedEighth => Some(DOTTED_EIGHTH_REST),
+            Duration::Eighth => Some(EIGHTH_REST),
+            Duration::DottedSixteenth => Some(DOTTED_SIXTEENTH_REST),
+            Duration::Sixteenth => Some(SIXTEENTH_REST),
+            Duration::ThirtySecond => Some(THIRTY_SECOND_REST),
+            Duration::QuarterTriplet
+            | Duration::EighthTriplet
+            | Duration::SixteenthTriplet
+            | Duration::SixteenthQuintuplet => None,
+        }
     }
 
     fn draw_spaced_music_symbol<D>(
@@ -476,29 +1951,43 @@ impl Music {
         D: DrawTarget<Color = BinaryColor>,
     {
         if symbol.symbol.rest {
-            let pos = position + Point::new(x, MusicSymbolDefinitions::REST_OFFSET);
-            match symbol.symbol.kind {
-                Duration::Eighth => rytmos_symbols::draw_symbol(target, pos, EIGHTH_REST)?,
-                Duration::Whole => rytmos_symbols::draw_symbol(target, pos, WHOLE_REST)?,
-                Duration::Half => rytmos_symbols::draw_symbol(target, pos, HALF_REST)?,
-                Duration::DottedHalf => rytmos_symbols::draw_symbol(target, pos, DOTTED_HALF_REST)?,
-                Duration::Quarter => rytmos_symbols::draw_symbol(target, pos, QUARTER_REST)?,
-                Duration::DottedQuarter => {
-                    rytmos_symbols::draw_symbol(target, pos, DOTTED_QUARTER_REST)?
-                }
-                Duration::DottedEighth => {
-                    rytmos_symbols::draw_symbol(target, pos, DOTTED_EIGHTH_REST)?
-                }
-                Duration::Sixteenth => rytmos_symbols::draw_symbol(target, pos, SIXTEENTH_REST)?,
-            };
+            let pos = position + Point::new(x, symbol.symbol.y);
+            if let Some(glyph) = Self::rest_glyph(symbol.symbol.kind) {
+                rytmos_symbols::draw_symbol(target, pos, glyph)?;
+            }
         } else {
             let position = position + Point::new(x, symbol.symbol.y);
             let line_style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
             let bg_style = PrimitiveStyle::with_stroke(BinaryColor::Off, 1);
+            let span = symbol.symbol.span();
+
+            let columns = symbol.symbol.accidental_columns();
+            let shifted_space = symbol.symbol.accidental_space() - columns.iter().sum::<i32>();
+            for head in symbol.symbol.heads() {
+                if let Some(accidental) = head.accidental {
+                    let (glyph, center) = accidental.glyph();
+                    let column_x: i32 = columns[..=head.accidental_column].iter().sum();
+                    let offset = Point::new(
+                        -column_x - shifted_space,
+                        head.y - symbol.symbol.y + Note::NOTEHEAD_CENTER - center,
+                    );
+                    rytmos_symbols::draw_symbol(target, position + offset, glyph)?;
+                }
+            }
 
-            // If necessary, draw ledger lines
+            // If necessary, draw ledger lines up to the outer heads, wider for shifted heads
             let top_ledger_y = Staff::LEDGER_MARGIN;
             let bottom_ledger_y = Staff::LEDGER_MARGIN + Staff::LINE_SPACING * 4; // or 6?
+            let (ledger_left, ledger_right) = match symbol.symbol.stem_direction {
+                _ if symbol.symbol.kind == Duration::Breve => (-2, 6),
+                StemDirection::Up if symbol.symbol.has_seconds() => {
+                    (-1, 5 + MusicSymbol::SHIFTED_HEAD_OFFSET)
+                }
+                StemDirection::Down if symbol.symbol.has_seconds() => {
+                    (-1 - MusicSymbol::SHIFTED_HEAD_OFFSET, 5)
+                }
+                _ => (-1, 5),
+            };
 
             if symbol.symbol.y < top_ledger_y {
                 let amount_of_ledgers_necessary =
@@ -507,46 +1996,72 @@ impl Music {
                 let mut y = top_ledger_y - Staff::LINE_SPACING;
 
                 for _ in 0..amount_of_ledgers_necessary {
-                    let start = Point::new(position.x - 1, y);
-                    let end = Point::new(position.x + 5, y);
+                    let start = Point::new(position.x + ledger_left, y);
+                    let end = Point::new(position.x + ledger_right, y);
                     Line::new(start, end).draw_styled(&line_style, target)?;
 
                     y -= Staff::LINE_SPACING;
                 }
-            } else if symbol.symbol.y > bottom_ledger_y {
+            }
+
+            if symbol.symbol.y + span > bottom_ledger_y {
                 let amount_of_ledgers_necessary =
-                    (symbol.symbol.y - bottom_ledger_y) / Staff::LINE_SPACING + 1;
+                    (symbol.symbol.y + span - bottom_ledger_y) / Staff::LINE_SPACING + 1;
 
                 let mut y = bottom_ledger_y + Staff::LINE_SPACING;
 
                 for _ in 0..amount_of_ledgers_necessary {
-                    let start = Point::new(position.x - 1, y);
-                    let end = Point::new(position.x + 6, y);
+                    let start = Point::new(position.x + ledger_left, y);
+                    let end = Point::new(position.x + ledger_right + 1, y);
                     Line::new(start, end).draw_styled(&line_style, target)?;
 
                     y += Staff::LINE_SPACING;
                 }
             }
 
-            // Draw the head
-            match symbol.symbol.kind {
-                Duration::Whole | Duration::Half | Duration::DottedHalf => {
-                    rytmos_symbols::draw_symbol(target, position, EMPTY_NOTEHEAD)?
-                }
-                _ => rytmos_symbols::draw_symbol(target, position, FILLED_NOTEHEAD)?,
+            // Draw the heads, a head a second away from another sits on the other side of the stem
+            let shifted = symbol.symbol.shifted_heads();
+            let shift = if symbol.symbol.stem_direction == StemDirection::Down {
+                -MusicSymbol::SHIFTED_HEAD_OFFSET
+            } else {
+                MusicSymbol::SHIFTED_HEAD_OFFSET
             };
 
-            if symbol.symbol.kind != Duration::Whole {
-                // Draw stem
+            for (head, &shifted) in symbol.symbol.heads().iter().zip(shifted.iter()) {
+                let head_position = position
+                    + Point::new(if shifted { shift } else { 0 }, head.y - symbol.symbol.y);
+
+                match symbol.symbol.kind {
+                    _ if symbol.symbol.articulations.contains(Articulation::Ghost) => {
+                        rytmos_symbols::draw_symbol(target, head_position, GHOST_NOTEHEAD)?
+                    }
+                    Duration::Breve => rytmos_symbols::draw_symbol(
+                        target,
+                        head_position - Point::new(1, 1),
+                        BREVE_NOTEHEAD,
+                    )?,
+                    Duration::Whole
+                    | Duration::DottedWhole
+                    | Duration::Half
+                    | Duration::DottedHalf
+                    | Duration::DoubleDottedHalf => {
+                        rytmos_symbols::draw_symbol(target, head_position, EMPTY_NOTEHEAD)?
+                    }
+                    _ => rytmos_symbols::draw_symbol(target, head_position, FILLED_NOTEHEAD)?,
+                };
+            }
+
+            if symbol.symbol.has_stem() {
+                // Draw stem, over all heads of a chord
                 let (start_pos, end_pos) = if symbol.symbol.stem_direction == StemDirection::Up {
                     (
-                        position + Point::new(4, 0),
+                        position + Point::new(4, span),
                         position + Point::new(4, -symbol.symbol.stem_length + 1),
                     )
                 } else {
                     (
                         position + Point::new(1, 4),
-                        position + Point::new(1, symbol.symbol.stem_length + 3),
+                        position + Point::new(1, span + symbol.symbol.stem_length + 3),
                     )
                 };
 
@@ -554,41 +2069,62 @@ impl Music {
                     .into_styled(line_style)
                     .draw(target)?;
 
+                // Only clear around the stem past the heads, not through the heads of a chord
+                let bg_start = if symbol.symbol.stem_direction == StemDirection::Up {
+                    position + Point::new(4, 0)
+                } else {
+                    position + Point::new(1, span + 4)
+                };
+
                 let x_offset = Point::new(1, 0);
-                Line::new(start_pos + x_offset, end_pos + x_offset)
+                Line::new(bg_start + x_offset, end_pos + x_offset)
                     .into_styled(bg_style)
                     .draw(target)?;
 
-                Line::new(start_pos - x_offset, end_pos - x_offset)
+                Line::new(bg_start - x_offset, end_pos - x_offset)
                     .into_styled(bg_style)
                     .draw(target)?;
             }
 
-            // Draw dot, if applicable
-            if matches!(
-                symbol.symbol.kind,
-                Duration::DottedEighth | Duration::DottedQuarter | Duration::DottedHalf
-            ) {
-                // Draw two dots, one overlaps with a ledger line
-                Rectangle::new(
-                    position + Point::new(6, 2),
-                    Size {
-                        width: 1,
-                        height: 1,
-                    },
-                )
-                .into_styled(line_style)
-                .draw(target)?;
-
-                Rectangle::new(
-                    position + Point::new(6, 0),
-                    Size {
-                        width: 1,
-                        height: 1,
-                    },
-                )
-                .into_styled(line_style)
-                .draw(target)?;
+            // Draw dots, if applicable
+            for dot in 0..symbol.symbol.kind.dots() as i32 {
+                // Dots go right of any head shifted to the right of the stem
+                let dot_x = if symbol.symbol.stem_direction == StemDirection::Up
+                    && symbol.symbol.has_seconds()
+                {
+                    6 + MusicSymbol::SHIFTED_HEAD_OFFSET
+                } else {
+                    6
+                } + dot * 2;
+
+                for head in symbol.symbol.heads() {
+                    let head_y = head.y - symbol.symbol.y;
+
+                    // Draw two dots, one overlaps with a ledger line
+                    Rectangle::new(
+                        position + Point::new(dot_x, head_y + 2),
+                        Size {
+                            width: 1,
+                            height: 1,
+                        },
+                    )
+                    .into_styled(line_style)
+                    .draw(target)?;
+
+                    Rectangle::new(
+                        position + Point::new(dot_x, head_y),
+                        Size {
+                            width: 1,
+                            height: 1,
+                        },
+                    )
+                    .into_styled(line_style)
+                    .draw(target)?;
+                }
+            }
+
+            for (glyph, offset) in symbol.symbol.articulation_marks() {
+                rytmos_symbols::draw_symbol(target, position + offset, glyph)?;
             }
 
             if !beamed {
@@ -598,12 +2134,12 @@ impl Music {
                 let offset = if !flipped {
                     Point {
                         x: 6,
-                        y: -MusicSymbolDefinitions::DEFAULT_STEM_LENGTH + 1,
+                        y: -symbol.symbol.stem_length + 1,
                     }
                 } else {
                     Point {
                         x: 0,
-                        y: MusicSymbolDefinitions::DEFAULT_STEM_LENGTH,
+                        y: span + symbol.symbol.stem_length,
                     }
                 };
 
@@ -614,32 +2150,24 @@ impl Music {
 
                 let pos = position + offset + flip_offset;
 
-                match symbol.symbol.kind {
-                    Duration::Eighth => {
-                        rytmos_symbols::draw_symbol_with_direction(
-                            target, pos, EIGHT_FLAG, flipped,
-                        )?;
-                    }
-                    Duration::DottedEighth => {
-                        rytmos_symbols::draw_symbol_with_direction(
-                            target,
-                            pos,
-                            DOTTED_EIGHTH_REST,
-                            flipped,
-                        )?;
-                    }
-                    Duration::Sixteenth => {
-                        rytmos_symbols::draw_symbol_with_direction(
-                            target,
-                            pos + Point {
-                                x: 0,
-                                y: if flipped { -2 } else { 0 },
-                            },
-                            SIXTEENTH_FLAG,
-                            flipped,
-                        )?;
-                    }
-                    _ => {}
+                // Flipped flags are drawn from their bottom row, longer flags start higher
+                let flag = match symbol.symbol.kind.flags() {
+                    1 => Some((EIGHT_FLAG, 0)),
+                    2 => Some((SIXTEENTH_FLAG, -2)),
+                    3 => Some((THIRTY_SECOND_FLAG, -5)),
+                    _ => None,
+                };
+
+                if let Some((glyph, flipped_y)) = flag {
+                    rytmos_symbols::draw_symbol_with_direction(
+                        target,
+                        pos + Point {
+                            x: 0,
+                            y: if flipped { flipped_y } else { 0 },
+                        },
+                        glyph,
+                        flipped,
+                    )?;
                 }
             }
         }
@@ -647,11 +2175,10 @@ impl Music {
         Ok(())
     }
 
-    fn draw_beams<D>(
+    fn draw_beams<D, const BEAM_GROUP: usize>(
         target: &mut D,
         position: Point,
-        x: i32,
-        glyph: &GlyphDefinition,
+        glyph: &GlyphDefinition<BEAM_GROUP>,
     ) -> Result<(), D::Error>
     where
         D: DrawTarget<Color = BinaryColor>,
@@ -669,13 +2196,20 @@ impl Music {
         let last_symbol = glyph.symbols.last().unwrap().symbol;
 
         let flipped = first_symbol.stem_direction == StemDirection::Down;
+        let x = first_symbol.x.unwrap_or_default();
 
         // always draw top beam
         let top_beam_start = position
             + if flipped {
-                Point::new(x + 1, first_symbol.y + first_symbol.stem_length + 2)
+                Point::new(
+                    x + 1,
+                    first_symbol.stem_end_y() + first_symbol.stem_length + 2,
+                )
             } else {
-                Point::new(x + 4, first_symbol.y - first_symbol.stem_length + 1)
+                Point::new(
+                    x + 4,
+                    first_symbol.stem_end_y() - first_symbol.stem_length + 1,
+                )
             };
 
         let beam_length = first_symbol
@@ -701,161 +2235,134 @@ impl Music {
         )
         .draw_styled(&style, target)?;
 
-        // Draw black lines to obscure barlines
-        for pair in glyph.symbols.windows(2) {
-            if let [first, second] = pair {
-                let x_first = first.symbol.x.unwrap_or_default();
-                let x_second = second.symbol.x.unwrap_or_default();
-                let beam_length = x_second - x_first - 1;
-
-                Rectangle::new(
-                    Point {
-                        x: position.x + x_first + if flipped { 2 } else { 5 },
-                        y: top_beam_start.y + if flipped { -1 } else { 2 },
-                    },
-                    Size {
-                        width: beam_length as u32,
-                        height: 1,
-                    },
-                )
-                .draw_styled(&off_style, target)?;
-            } else {
-                panic!("Unexpected window function return")
+        let flags: Vec<u8, BEAM_GROUP> = glyph
+            .symbols
+            .iter()
+            .map(|s| s.symbol.kind.flags())
+            .collect();
+        let levels = flags.iter().copied().max().unwrap_or_default().max(2);
+
+        // Draw black lines under every beam to obscure staff lines
+        for level in 1..=levels as i32 {
+            for pair in glyph.symbols.windows(2) {
+                if let [first, second] = pair {
+                    let x_first = first.symbol.x.unwrap_or_default();
+                    let x_second = second.symbol.x.unwrap_or_default();
+                    let beam_length = x_second - x_first - 1;
+
+                    Rectangle::new(
+                        Point {
+                            x: position.x + x_first + if flipped { 2 } else { 5 },
+                            y: top_beam_start.y
+                                + if flipped { -(level * 2 - 1) } else { level * 2 },
+                        },
+                        Size {
+                            width: beam_length as u32,
+                            height: 1,
+                        },
+                    )
+                    .draw_styled(&off_style, target)?;
+                } else {
+                    panic!("Unexpected window function return")
+                }
             }
         }
 
-        // for each consecutive note pair
-        // if sixteenths - eight: draw block facing right
-        // if eight - sixteenth: draw block facing left
-        // if sixteenth - sixteenht: connect
-        for pair in glyph.symbols.windows(2) {
-            if let [first, second] = pair {
-                let x_first = first.symbol.x.unwrap_or_default();
-                let x_second = second.symbol.x.unwrap_or_default();
-                let beam_length = x_second - x_first - 1;
-
-                let disconnected_beam_length = 3u32;
-
-                // For 2 cases the previous algorithm produces an artifact, prevent that
-                let mut is_ess_or_sse = false;
-                if glyph.symbols.len() == 3 {
-                    if glyph.symbols.iter().all(|s| !s.symbol.rest) {
-                        let durations: Vec<Duration, 4> =
-                            glyph.symbols.iter().map(|s| s.symbol.kind).collect();
-
-                        if durations[0] == Duration::Eighth
-                            && durations[1] == Duration::Sixteenth
-                            && durations[2] == Duration::Sixteenth
-                        {
-                            is_ess_or_sse = true;
-                        }
+        let disconnected_beam_length = 3u32;
+        for (level, index, beam) in Self::secondary_beams(&flags) {
+            let y = top_beam_start.y
+                + if flipped {
+                    -2 * (level as i32 - 1)
+                } else {
+                    2 * level as i32 - 1
+                };
 
-                        if durations[0] == Duration::Sixteenth
-                            && durations[1] == Duration::Sixteenth
-                            && durations[2] == Duration::Eighth
-                        {
-                            is_ess_or_sse = true;
-                        }
-                    }
-                }
+            let x_symbol = glyph.symbols[index].symbol.x.unwrap_or_default();
+            let beam_x = position.x + x_symbol + if flipped { 2 } else { 5 };
 
-                match (first.symbol.kind, second.symbol.kind) {
-                    (Duration::Eighth, Duration::Eighth) => (),
-                    (Duration::Eighth, Duration::Sixteenth) => {
-                        if !is_ess_or_sse {
-                            Rectangle::new(
-                                Point {
-                                    x: position.x + x_first + beam_length
-                                        - disconnected_beam_length as i32
-                                        + if flipped { 2 } else { 5 },
-                                    y: top_beam_start.y + if flipped { -2 } else { 3 },
-                                },
-                                Size {
-                                    width: disconnected_beam_length,
-                                    height: 1,
-                                },
-                            )
-                            .draw_styled(&style, target)?;
-                        }
-                    }
-                    (Duration::DottedEighth, Duration::Sixteenth) => {
-                        Rectangle::new(
-                            Point {
-                                x: position.x + x_first + beam_length
-                                    - disconnected_beam_length as i32
-                                    + if flipped { 2 } else { 5 },
-                                y: top_beam_start.y + if flipped { -2 } else { 3 },
-                            },
-                            Size {
-                                width: disconnected_beam_length,
-                                height: 1,
-                            },
-                        )
-                        .draw_styled(&style, target)?;
-                    }
-                    (Duration::Sixteenth, Duration::Eighth) => {
-                        if !is_ess_or_sse {
-                            Rectangle::new(
-                                Point {
-                                    x: position.x + x_first + if flipped { 2 } else { 5 },
-                                    y: top_beam_start.y + if flipped { -2 } else { 3 },
-                                },
-                                Size {
-                                    width: disconnected_beam_length,
-                                    height: 1,
-                                },
-                            )
-                            .draw_styled(&style, target)?;
-                        }
-                    }
-                    (Duration::Sixteenth, Duration::DottedEighth) => {
-                        Rectangle::new(
-                            Point {
-                                x: position.x + x_first + if flipped { 2 } else { 5 },
-                                y: top_beam_start.y + if flipped { -2 } else { 3 },
-                            },
-                            Size {
-                                width: disconnected_beam_length,
-                                height: 1,
-                            },
-                        )
-                        .draw_styled(&style, target)?;
-                    }
-                    (Duration::Sixteenth, Duration::Sixteenth) => {
-                        Rectangle::new(
-                            Point {
-                                x: position.x + x_first + if flipped { 2 } else { 5 },
-                                y: top_beam_start.y + if flipped { -2 } else { 3 },
-                            },
-                            Size {
-                                width: beam_length as u32,
-                                height: 1,
-                            },
-                        )
-                        .draw_styled(&style, target)?;
-                    }
-                    _ => panic!("Unknown beaming"),
+            let (x, width) = match beam {
+                SecondaryBeam::ToNext => {
+                    let x_next = glyph.symbols[index + 1].symbol.x.unwrap_or_default();
+                    (beam_x, (x_next - x_symbol - 1) as u32)
                 }
+                SecondaryBeam::BrokenRight => (beam_x, disconnected_beam_length),
+                SecondaryBeam::BrokenLeft => (
+                    beam_x - 1 - disconnected_beam_length as i32,
+                    disconnected_beam_length,
+                ),
+            };
 
-                Rectangle::new(
-                    Point {
-                        x: position.x + x_first + if flipped { 2 } else { 5 },
-                        y: top_beam_start.y + if flipped { -3 } else { 4 },
-                    },
-                    Size {
-                        width: beam_length as u32,
-                        height: 1,
-                    },
-                )
-                .draw_styled(&off_style, target)?;
-            } else {
-                panic!("Unexpected window function return")
-            }
+            Rectangle::new(Point { x, y }, Size { width, height: 1 })
+                .draw_styled(&style, target)?;
         }
 
         Ok(())
     }
 
+    /// The beams above the first of a beamed glyph of notes with the given numbers of flags, as
+    /// level, index of the note and where its beam goes. Secondary beams connect consecutive
+    /// notes that both have them. A note that has more beams than its neighbours gets a broken
+    /// beam, facing right for the first note of the glyph and facing left otherwise.
+    fn secondary_beams(flags: &[u8]) -> impl Iterator<Item = (u8, usize, SecondaryBeam)> + '_ {
+        let levels = flags.iter().copied().max().unwrap_or_default();
+        let has_level =
+            move |index: usize, level: u8| flags.get(index).is_some_and(|&f| f >= level);
+
+        (2..=levels).flat_map(move |level| {
+            (0..flags.len()).filter_map(move |index| {
+                if !has_level(index, level) {
+                    return None;
+                }
+
+                let previous = index.checked_sub(1).is_some_and(|i| has_level(i, level));
+                let beam = match (previous, has_level(index + 1, level)) {
+                    (_, true) => SecondaryBeam::ToNext,
+                    (true, false) => return None,
+                    (false, false) if index == 0 => SecondaryBeam::BrokenRight,
+                    (false, false) => SecondaryBeam::BrokenLeft,
+                };
+
+                Some((level, index, beam))
+            })
+        })
+    }
+
+    /// Draws a slur from the first to the second symbol on the side of the heads away from the
+    /// stem of the first, like a tie but sloped when the heads differ in height.
+    fn draw_slur<D>(
+        target: &mut D,
+        position: Point,
+        first: &SpacedMusicSymbol,
+        second: &SpacedMusicSymbol,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+        let flipped = first.symbol.stem_direction == StemDirection::Down;
+
+        // Ends of the slur next to the outer head of each symbol, outside its articulations
+        let end = |symbol: &MusicSymbol, x: i32| {
+            let y = symbol.y + symbol.slur_offset(!flipped);
+            position + Point::new(symbol.x.unwrap_or(0) + x, y)
+        };
+        let left = end(&first.symbol, 4);
+        let right = end(&second.symbol, 1);
+
+        // The curve runs a pixel further from the heads than its ends
+        let curve = Point::new(0, if flipped { -1 } else { 1 });
+
+        Pixel(left, BinaryColor::On).draw(target)?;
+        Pixel(right, BinaryColor::On).draw(target)?;
+        Line::new(
+            left + Point::new(1, 0) + curve,
+            right - Point::new(1, 0) + curve,
+        )
+        .draw_styled(&style, target)?;
+
+        Ok(())
+    }
+
     fn draw_tie<D>(
         target: &mut D,
         position: Point,
@@ -873,10 +2380,17 @@ impl Music {
         let tie_length = second_x - first_x;
         let flipped = first.symbol.stem_direction == StemDirection::Down;
 
+        // Chords are tied at the head away from the stem
+        let head_y = if flipped {
+            first.symbol.y
+        } else {
+            first.symbol.y + first.symbol.span()
+        };
+
         Rectangle::new(
             Point {
                 x: position.x + first.symbol.x.unwrap_or(0) - 1 + if flipped { 3 } else { 3 },
-                y: position.y + first.symbol.y - 2 + if flipped { -3 } else { 7 },
+                y: position.y + head_y - 2 + if flipped { -3 } else { 7 },
             },
             Size {
                 width: tie_length as u32 + 2,
@@ -889,7 +2403,7 @@ impl Music {
         Rectangle::new(
             Point {
                 x: position.x + first.symbol.x.unwrap_or(0) + if flipped { 4 } else { 4 },
-                y: position.y + first.symbol.y + if flipped { -2 } else { 6 },
+                y: position.y + head_y + if flipped { -2 } else { 6 },
             },
             Size {
                 width: 1,
@@ -902,7 +2416,7 @@ impl Music {
         Rectangle::new(
             Point {
                 x: position.x + second.symbol.x.unwrap_or(0) + if flipped { 1 } else { 1 },
-                y: position.y + first.symbol.y + if flipped { -2 } else { 6 },
+                y: position.y + head_y + if flipped { -2 } else { 6 },
             },
             Size {
                 width: 1,
@@ -915,7 +2429,7 @@ impl Music {
         Rectangle::new(
             Point {
                 x: position.x + first.symbol.x.unwrap_or(0) + 1 + if flipped { 4 } else { 4 },
-                y: position.y + first.symbol.y + if flipped { -3 } else { 7 },
+                y: position.y + head_y + if flipped { -3 } else { 7 },
             },
             Size {
                 width: tie_length as u32 - 4,
@@ -930,32 +2444,185 @@ impl Music {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Duration {
+    /// Two whole notes
+    Breve,
     Whole,
+    DottedWhole,
     Half,
     DottedHalf,
+    DoubleDottedHalf,
     Quarter,
     DottedQuarter,
+    DoubleDottedQuarter,
     Eighth,
     DottedEighth,
+    DoubleDottedEighth,
     Sixteenth,
+    DottedSixteenth,
+    ThirtySecond,
+    /// Three in the time of a half note
+    QuarterTriplet,
+    /// Three in the time of a quarter note
+    EighthTriplet,
+    /// Three in the time of an eighth note
+    SixteenthTriplet,
+    /// Five in the time of a quarter note
+    SixteenthQuintuplet,
 }
 
 impl Duration {
-    /// Returns the value in beats of the duration
-    pub fn value(self) -> f64 {
+    pub(crate) const ALL: [Duration; 19] = [
+        Duration::Breve,
+        Duration::Whole,
+        Duration::DottedWhole,
+        Duration::Half,
+        Duration::DottedHalf,
+        Duration::DoubleDottedHalf,
+        Duration::Quarter,
+        Duration::DottedQuarter,
+        Duration::DoubleDottedQuarter,
+        Duration::Eighth,
+        Duration::DottedEighth,
+        Duration::DoubleDottedEighth,
+        Duration::Sixteenth,
+        Duration::DottedSixteenth,
+        Duration::ThirtySecond,
+        Duration::QuarterTriplet,
+        Duration::EighthTriplet,
+        Duration::SixteenthTriplet,
+        Duration::SixteenthQuintuplet,
+    ];
+
+    /// Returns the exact length of the duration
+    pub fn ticks(self) -> Ticks {
+        let quarter = Ticks::PER_QUARTER;
+        Ticks(match self {
+            Duration::Breve => quarter * 8,
+            Duration::Whole => quarter * 4,
+            Duration::DottedWhole => quarter * 6,
+            Duration::Half => quarter * 2,
+            Duration::DottedHalf => quarter * 3,
+            Duration::DoubleDottedHalf => quarter * 7 / 2,
+            Duration::Quarter => quarter,
+            Duration::DottedQuarter => quarter * 3 / 2,
+            Duration::DoubleDottedQuarter => quarter * 7 / 4,
+            Duration::Eighth => quarter / 2,
+            Duration::DottedEighth => quarter * 3 / 4,
+            Duration::DoubleDottedEighth => quarter * 7 / 8,
+            Duration::Sixteenth => quarter / 4,
+            Duration::DottedSixteenth => quarter * 3 / 8,
+            Duration::ThirtySecond => quarter / 8,
+            Duration::QuarterTriplet => quarter * 2 / 3,
+            Duration::EighthTriplet => quarter / 3,
+            Duration::SixteenthTriplet => quarter / 6,
+            Duration::SixteenthQuintuplet => quarter / 5,
+        })
+    }
+
+    /// The duration that is exactly the given length, if any.
+    pub fn from_ticks(ticks: Ticks) -> Option<Duration> {
+        Self::ALL
+            .into_iter()
+            .find(|duration| duration.ticks() == ticks)
+    }
+
+    /// The duration that is written on the staff, e.g. an eighth for an eighth triplet.
+    pub fn written(self) -> Duration {
+        match self {
+            Duration::QuarterTriplet => Duration::Quarter,
+            Duration::EighthTriplet => Duration::Eighth,
+            Duration::SixteenthTriplet | Duration::SixteenthQuintuplet => Duration::Sixteenth,
+            duration => duration,
+        }
+    }
+
+    /// Number of dots after the notehead or rest.
+    pub fn dots(self) -> u8 {
+        match self {
+            Duration::DottedWhole
+            | Duration::DottedHalf
+            | Duration::DottedQuarter
+            | Duration::DottedEighth
+            | Duration::DottedSixteenth => 1,
+            Duration::DoubleDottedHalf
+            | Duration::DoubleDottedQuarter
+            | Duration::DoubleDottedEighth => 2,
+            _ => 0,
+        }
+    }
+
+    /// Number of flags on the stem of a note, or of beams when it is beamed.
+    pub fn flags(self) -> u8 {
+        match self.written() {
+            Duration::Eighth | Duration::DottedEighth | Duration::DoubleDottedEighth => 1,
+            Duration::Sixteenth | Duration::DottedSixteenth => 2,
+            Duration::ThirtySecond => 3,
+            _ => 0,
+        }
+    }
+
+    /// The number written over a tuplet and the length of a complete tuplet group,
+    /// `None` for regular durations.
+    pub fn tuplet(self) -> Option<(u8, Ticks)> {
         match self {
-            Duration::Whole => 4.0,
-            Duration::Half => 2.0,
-            Duration::DottedHalf => 3.0,
-            Duration::Quarter => 1.0,
-            Duration::DottedQuarter => 1.5,
-            Duration::Eighth => 0.5,
-            Duration::DottedEighth => 0.75,
-            Duration::Sixteenth => 0.25,
+            Duration::QuarterTriplet => Some((3, Duration::Half.ticks())),
+            Duration::EighthTriplet => Some((3, Duration::Quarter.ticks())),
+            Duration::SixteenthTriplet => Some((3, Duration::Eighth.ticks())),
+            Duration::SixteenthQuintuplet => Some((5, Duration::Quarter.ticks())),
+            _ => None,
         }
     }
 }
 
+/// An exact position or length in time. Durations of all notes, including tuplets,
+/// are a whole number of ticks, so adding and comparing them never rounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, defmt::Format)]
+pub struct Ticks(pub u32);
+
+impl Ticks {
+    /// Divisible by the subdivisions of all durations, and a common MIDI resolution
+    pub const PER_QUARTER: u32 = 480;
+
+    pub const fn from_sixteenths(sixteenths: u32) -> Self {
+        Self(sixteenths * Self::PER_QUARTER / 4)
+    }
+
+    /// Whole sixteenths in this amount of ticks, rounded down.
+    pub const fn sixteenths(self) -> u32 {
+        self.0 * 4 / Self::PER_QUARTER
+    }
+}
+
+impl Add for Ticks {
+    type Output = Ticks;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Ticks(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Ticks {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Ticks {
+    type Output = Ticks;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Ticks(self.0 - rhs.0)
+    }
+}
+
+impl Rem for Ticks {
+    type Output = Ticks;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        Ticks(self.0 % rhs.0)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
 pub enum Accidental {
     Natural,
@@ -965,6 +2632,42 @@ pub enum Accidental {
     DoubleFlat,
 }
 
+impl Accidental {
+    /// The symbol of the accidental and the row of that symbol that is its vertical center.
+    fn glyph(self) -> ((u32, &'static [u32]), i32) {
+        match self {
+            Accidental::Natural => (NATURAL, 3),
+            Accidental::Sharp => (SHARP, 3),
+            Accidental::Flat => (FLAT, 4),
+            Accidental::DoubleSharp => (DOUBLE_SHARP, 2),
+            Accidental::DoubleFlat => (DOUBLE_FLAT, 4),
+        }
+    }
+
+    /// How many semitones the accidental raises a note.
+    pub(crate) fn semitones(self) -> i32 {
+        match self {
+            Accidental::DoubleFlat => -2,
+            Accidental::Flat => -1,
+            Accidental::Natural => 0,
+            Accidental::Sharp => 1,
+            Accidental::DoubleSharp => 2,
+        }
+    }
+
+    /// The accidental that raises a note by the given semitones, if there is one.
+    pub(crate) fn from_semitones(semitones: i32) -> Option<Self> {
+        match semitones {
+            -2 => Some(Accidental::DoubleFlat),
+            -1 => Some(Accidental::Flat),
+            0 => Some(Accidental::Natural),
+            1 => Some(Accidental::Sharp),
+            2 => Some(Accidental::DoubleSharp),
+            _ => None,
+        }
+    }
+}
+
 // TODO: move to a rytmos-common crate?
 #[derive(Clone, Copy, Debug, PartialEq, Eq, defmt::Format)]
 pub enum Note {
@@ -978,26 +2681,139 @@ pub enum Note {
 }
 
 impl Note {
-    const C0_OFFSET: i32 = Staff::LEDGER_MARGIN + Staff::LINE_SPACING * (5 + 7) + 2;
-    const STEM_DIRECTION_SWITCH_HEIGHT: i32 = Self::C0_OFFSET - 2 - 3 * 14; // At D3, flip
+    /// Vertical distance from the top of the notehead glyph to its center.
+    const NOTEHEAD_CENTER: i32 = 2;
 
-    fn y_offset(self) -> i32 {
+    /// Number of staff positions (lines and spaces) above C0, ignoring the accidental.
+    pub(crate) fn diatonic_step(self) -> i32 {
         match self {
-            Note::A(_, octave) => Self::C0_OFFSET - 10 - octave * 14,
-            Note::B(_, octave) => Self::C0_OFFSET - 12 - octave * 14,
-            Note::C(_, octave) => Self::C0_OFFSET - octave * 14,
-            Note::D(_, octave) => Self::C0_OFFSET - 2 - octave * 14,
-            Note::E(_, octave) => Self::C0_OFFSET - 4 - octave * 14,
-            Note::F(_, octave) => Self::C0_OFFSET - 6 - octave * 14,
-            Note::G(_, octave) => Self::C0_OFFSET - 8 - octave * 14,
+            Note::C(_, octave) => octave * 7,
+            Note::D(_, octave) => 1 + octave * 7,
+            Note::E(_, octave) => 2 + octave * 7,
+            Note::F(_, octave) => 3 + octave * 7,
+            Note::G(_, octave) => 4 + octave * 7,
+            Note::A(_, octave) => 5 + octave * 7,
+            Note::B(_, octave) => 6 + octave * 7,
         }
     }
 
-    fn default_stem_direction(&self) -> StemDirection {
-        if self.y_offset() > Self::STEM_DIRECTION_SWITCH_HEIGHT {
-            StemDirection::Up
-        } else {
-            StemDirection::Down
+    /// Y position of the top of the notehead relative to the top of the staff drawing area.
+    fn y_offset(self, clef: Clef) -> i32 {
+        let steps_below_top_line = clef.top_line_step() - self.diatonic_step();
+        Staff::LEDGER_MARGIN + steps_below_top_line * Staff::LINE_SPACING / 2
+            - Self::NOTEHEAD_CENTER
+    }
+
+    /// Note on the line or space the given number of staff positions below the top line, with
+    /// the accidental that the key gives it.
+    pub(crate) fn on_staff(steps_below_top_line: i32, clef: Clef, key: Key) -> Note {
+        let step = clef.top_line_step() - steps_below_top_line;
+        let natural = Note::from_diatonic_step(step, Accidental::Natural);
+
+        Note::from_diatonic_step(step, key.accidental_for(natural))
+    }
+
+    /// Position of the note on the line of fifths (..., Bb, F, C, G, D, ..., B, F#, ...), with C
+    /// at 0. Ignores the octave.
+    pub(crate) fn fifths(self) -> i32 {
+        let letter = match self {
+            Note::F(..) => -1,
+            Note::C(..) => 0,
+            Note::G(..) => 1,
+            Note::D(..) => 2,
+            Note::A(..) => 3,
+            Note::E(..) => 4,
+            Note::B(..) => 5,
+        };
+
+        letter + 7 * self.accidental().semitones()
+    }
+
+    /// The note with the letter on the given position of the line of fifths and the given
+    /// accidental, in octave 0. See `fifths`.
+    fn from_fifths(fifths: i32, accidental: Accidental) -> Note {
+        match (fifths + 1).rem_euclid(7) {
+            0 => Note::F(accidental, 0),
+            1 => Note::C(accidental, 0),
+            2 => Note::G(accidental, 0),
+            3 => Note::D(accidental, 0),
+            4 => Note::A(accidental, 0),
+            5 => Note::E(accidental, 0),
+            _ => Note::B(accidental, 0),
+        }
+    }
+
+    /// The note on the given position of the line of fifths that sounds as the given MIDI code.
+    /// Returns `None` if it needs more than a double sharp or flat.
+    fn from_fifths_and_midi(fifths: i32, code: u8) -> Option<Note> {
+        let accidental = Accidental::from_semitones((fifths + 1).div_euclid(7))?;
+        let natural = Note::from_fifths(fifths, Accidental::Natural);
+        let octave = (code as i32 - accidental.semitones() - natural.semitones()).div_euclid(12);
+
+        Some(Note::from_diatonic_step(
+            natural.diatonic_step() + octave * 7,
+            accidental,
+        ))
+    }
+
+    /// The note of the MIDI code as it is conventionally written in the given key: as in the
+    /// scale of the key, otherwise as in the given chord, otherwise with the accidental closest
+    /// to the key signature. In C major that gives C C# D Eb E F F# G G# A Bb B, in A minor
+    /// A Bb B C C# D D# E F F# G G#.
+    pub fn from_midi(code: u8, key: Key, chord: Option<ChordSymbol>) -> Note {
+        let pitch_class = code as i32 % 12;
+        let same_pitch_class = |note: &Note| note.semitones().rem_euclid(12) == pitch_class;
+
+        // Positions on the line of fifths that are 12 apart have the same pitch class
+        let fifths = (pitch_class * 7).rem_euclid(12);
+        let center = key.spelling_center();
+        let closest = [fifths - 12, fifths, fifths + 12]
+            .into_iter()
+            .min_by_key(|fifths| (fifths * 2 - center).abs())
+            .unwrap_or(fifths);
+
+        let in_scale = (key.fifths() - 1..=key.fifths() + 5).contains(&closest);
+        let chord_tone = chord.filter(|_| !in_scale).and_then(|chord| {
+            let root = chord.root();
+            let mut tones = chord
+                .quality()
+                .intervals()
+                .iter()
+                .filter_map(|&interval| root + interval)
+                .chain(chord.bass());
+            tones.find(same_pitch_class)
+        });
+
+        chord_tone
+            .and_then(|tone| Note::from_fifths_and_midi(tone.fifths(), code))
+            .or_else(|| Note::from_fifths_and_midi(closest, code))
+            .unwrap_or_else(|| Note::from_u8_sharp(code))
+    }
+
+    /// Note with the given accidental on the given number of staff positions above C0, see
+    /// `diatonic_step`.
+    pub(crate) fn from_diatonic_step(step: i32, accidental: Accidental) -> Note {
+        let octave = step.div_euclid(7);
+        match step.rem_euclid(7) {
+            0 => Note::C(accidental, octave),
+            1 => Note::D(accidental, octave),
+            2 => Note::E(accidental, octave),
+            3 => Note::F(accidental, octave),
+            4 => Note::G(accidental, octave),
+            5 => Note::A(accidental, octave),
+            _ => Note::B(accidental, octave),
+        }
+    }
+
+    pub fn accidental(&self) -> Accidental {
+        match self {
+            Note::A(acc, _)
+            | Note::B(acc, _)
+            | Note::C(acc, _)
+            | Note::D(acc, _)
+            | Note::E(acc, _)
+            | Note::F(acc, _)
+            | Note::G(acc, _) => *acc,
         }
     }
 
@@ -1060,31 +2876,28 @@ impl Note {
     }
 
     pub fn lookup_frequency(&self) -> U14F2 {
-        let midi_index = self.to_midi_code() as usize;
-        if midi_index >= MIDI_FREQUENCIES.len() {
-            U14F2::from_num(0.0) // or handle out-of-range index appropriately
-        } else {
-            MIDI_FREQUENCIES[midi_index]
-        }
+        self.to_midi_code()
+            .and_then(|midi_index| MIDI_FREQUENCIES.get(midi_index as usize).copied())
+            .unwrap_or(U14F2::from_num(0.0)) // or handle out-of-range index appropriately
     }
 
     pub fn lookup_increment_48000(&self) -> Option<I1F15> {
-        let midi_index = self.to_midi_code();
+        let midi_index = self.to_midi_code()?;
         MIDI_INCREMENTS_48000.get(midi_index as usize).copied()
     }
 
     pub fn lookup_increment_44100(&self) -> Option<I1F15> {
-        let midi_index = self.to_midi_code();
+        let midi_index = self.to_midi_code()?;
         MIDI_INCREMENTS_44100.get(midi_index as usize).copied()
     }
 
     pub fn lookup_increment_24000(&self) -> Option<I1F15> {
-        let midi_index = self.to_midi_code();
+        let midi_index = self.to_midi_code()?;
         MIDI_INCREMENTS_24000.get(midi_index as usize).copied()
     }
 
     pub fn lookup_increment_12000(&self) -> Option<I1F15> {
-        let midi_index = self.to_midi_code();
+        let midi_index = self.to_midi_code()?;
         MIDI_INCREMENTS_12000.get(midi_index as usize).copied()
     }
 
@@ -1132,7 +2945,13 @@ impl Note {
         (note, octave)
     }
 
-    pub fn to_midi_code(&self) -> u8 {
+    /// The MIDI code of the note, `None` for notes below C-1 or too high for a `u8`.
+    pub fn to_midi_code(&self) -> Option<u8> {
+        u8::try_from(self.semitones()).ok()
+    }
+
+    /// Number of semitones above C-1, the MIDI code of the note without its range of a `u8`.
+    pub(crate) fn semitones(&self) -> i32 {
         let (offset, octave) = match self {
             Note::A(_, octave) => (0, *octave),
             Note::B(_, octave) => (2, *octave),
@@ -1143,27 +2962,11 @@ impl Note {
             Note::G(_, octave) => (-2, *octave),
         };
 
-        let accidental_offset = match self {
-            Note::A(acc, _)
-            | Note::B(acc, _)
-            | Note::C(acc, _)
-            | Note::D(acc, _)
-            | Note::E(acc, _)
-            | Note::F(acc, _)
-            | Note::G(acc, _) => match acc {
-                Accidental::DoubleFlat => -2,
-                Accidental::Flat => -1,
-                Accidental::Natural => 0,
-                Accidental::Sharp => 1,
-                Accidental::DoubleSharp => 2,
-            },
-        };
-
-        (69 + offset + accidental_offset + (octave - 4) * 12) as u8
+        69 + offset + self.accidental().semitones() + (octave - 4) * 12
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Key {
     CMajor,
     FMajor,
@@ -1178,51 +2981,560 @@ pub enum Key {
     AMajor,
     DMajor,
     GMajor,
+    AMinor,
+    DMinor,
+    GMinor,
+    CMinor,
+    FMinor,
+    BbMinor,
+    EbMinor,
+    DsMinor,
+    GsMinor,
+    CsMinor,
+    FsMinor,
+    BMinor,
+    EMinor,
+    /// Key of one of the other modes, see `Key::new`
+    Modal(ModalKey),
 }
 
-#[derive(Clone, Copy)]
+/// Scale that a key is built on, by the degree of the major scale that it starts on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Ionian
+    Major,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    /// Aeolian
+    Minor,
+    Locrian,
+}
+
+impl Mode {
+    /// Steps on the circle of fifths from the major key with the same signature to the tonic,
+    /// e.g. D dorian has the signature of C major and D is two fifths above C.
+    fn tonic_fifths(self) -> i32 {
+        match self {
+            Mode::Lydian => -1,
+            Mode::Major => 0,
+            Mode::Mixolydian => 1,
+            Mode::Dorian => 2,
+            Mode::Minor => 3,
+            Mode::Phrygian => 4,
+            Mode::Locrian => 5,
+        }
+    }
+}
+
+/// Key in a mode other than major or minor, made by `Key::new`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModalKey {
+    fifths: i8,
+    mode: Mode,
+}
+
+impl Key {
+    /// The key with the given tonic, of which the octave is ignored, in the given mode. Returns
+    /// `None` if its signature has more than six sharps or flats.
+    pub fn new(tonic: Note, mode: Mode) -> Option<Key> {
+        Key::from_fifths(tonic.fifths() - mode.tonic_fifths(), mode)
+    }
+
+    pub fn mode(self) -> Mode {
+        match self {
+            Key::CMajor
+            | Key::FMajor
+            | Key::BbMajor
+            | Key::EbMajor
+            | Key::AbMajor
+            | Key::DbMajor
+            | Key::GbMajor
+            | Key::FsMajor
+            | Key::BMajor
+            | Key::EMajor
+            | Key::AMajor
+            | Key::DMajor
+            | Key::GMajor => Mode::Major,
+            Key::AMinor
+            | Key::DMinor
+            | Key::GMinor
+            | Key::CMinor
+            | Key::FMinor
+            | Key::BbMinor
+            | Key::EbMinor
+            | Key::DsMinor
+            | Key::GsMinor
+            | Key::CsMinor
+            | Key::FsMinor
+            | Key::BMinor
+            | Key::EMinor => Mode::Minor,
+            Key::Modal(modal) => modal.mode,
+        }
+    }
+
+    /// The tonic of the key in the given octave.
+    pub fn tonic(self, octave: i32) -> Note {
+        let fifths = self.fifths() + self.mode().tonic_fifths();
+        // Keys have at most six sharps or flats, so their tonic at most a single one
+        let accidental =
+            Accidental::from_semitones((fifths + 1).div_euclid(7)).unwrap_or(Accidental::Natural);
+
+        Note::from_fifths(fifths, accidental).map_octave(|_| octave)
+    }
+
+    /// Returns the accidental used in the key signature and how many of them it contains.
+    pub fn signature(self) -> (Accidental, usize) {
+        match self.fifths() {
+            0 => (Accidental::Natural, 0),
+            fifths if fifths > 0 => (Accidental::Sharp, fifths as usize),
+            fifths => (Accidental::Flat, fifths.unsigned_abs() as usize),
+        }
+    }
+
+    /// Position of the key on the circle of fifths: the number of sharps, or minus the number of
+    /// flats.
+    pub(crate) fn fifths(self) -> i32 {
+        match self {
+            Key::GbMajor | Key::EbMinor => -6,
+            Key::DbMajor | Key::BbMinor => -5,
+            Key::AbMajor | Key::FMinor => -4,
+            Key::EbMajor | Key::CMinor => -3,
+            Key::BbMajor | Key::GMinor => -2,
+            Key::FMajor | Key::DMinor => -1,
+            Key::CMajor | Key::AMinor => 0,
+            Key::GMajor | Key::EMinor => 1,
+            Key::DMajor | Key::BMinor => 2,
+            Key::AMajor | Key::FsMinor => 3,
+            Key::EMajor | Key::CsMinor => 4,
+            Key::BMajor | Key::GsMinor => 5,
+            Key::FsMajor | Key::DsMinor => 6,
+            Key::Modal(modal) => modal.fifths as i32,
+        }
+    }
+
+    /// The key in the given mode on the given position of the circle of fifths, see `fifths`.
+    pub(crate) fn from_fifths(fifths: i32, mode: Mode) -> Option<Key> {
+        const MAJOR: [Key; 13] = [
+            Key::GbMajor,
+            Key::DbMajor,
+            Key::AbMajor,
+            Key::EbMajor,
+            Key::BbMajor,
+            Key::FMajor,
+            Key::CMajor,
+            Key::GMajor,
+            Key::DMajor,
+            Key::AMajor,
+            Key::EMajor,
+            Key::BMajor,
+            Key::FsMajor,
+        ];
+        const MINOR: [Key; 13] = [
+            Key::EbMinor,
+            Key::BbMinor,
+            Key::FMinor,
+            Key::CMinor,
+            Key::GMinor,
+            Key::DMinor,
+            Key::AMinor,
+            Key::EMinor,
+            Key::BMinor,
+            Key::FsMinor,
+            Key::CsMinor,
+            Key::GsMinor,
+            Key::DsMinor,
+        ];
+
+        if !(-6..=6).contains(&fifths) {
+            return None;
+        }
+
+        let index = (fifths + 6) as usize;
+        Some(match mode {
+            Mode::Major => MAJOR[index],
+            Mode::Minor => MINOR[index],
+            _ => Key::Modal(ModalKey {
+                fifths: fifths as i8,
+                mode,
+            }),
+        })
+    }
+
+    /// Twice the position on the circle of fifths that chromatic notes are spelled closest to:
+    /// between the sharp fourth and the flat sixth of major keys, so that chromatic notes get
+    /// sharps on the way to the fifth and flats below it, and one fifth up in minor keys for
+    /// their raised sixth and seventh.
+    fn spelling_center(self) -> i32 {
+        match self.mode() {
+            Mode::Minor => self.fifths() * 2 + 7,
+            _ => self.fifths() * 2 + 5,
+        }
+    }
+
+    /// Returns the accidental this key applies to notes with the same letter as the given note.
+    pub fn accidental_for(self, note: Note) -> Accidental {
+        // Letters as in `Note::diatonic_step`, in the order they appear in key signatures
+        const SHARPS: [i32; 7] = [3, 0, 4, 1, 5, 2, 6];
+        const FLATS: [i32; 7] = [6, 2, 5, 1, 4, 0, 3];
+
+        let (accidental, amount) = self.signature();
+        let order = match accidental {
+            Accidental::Sharp => SHARPS,
+            Accidental::Flat => FLATS,
+            _ => return Accidental::Natural,
+        };
+
+        let letter = note.diatonic_step().rem_euclid(7);
+        if order[..amount].contains(&letter) {
+            accidental
+        } else {
+            Accidental::Natural
+        }
+    }
+
+    /// Draws the accidentals of the key signature in the order of the circle of fifths,
+    /// placed according to the given clef. Returns the width used.
+    pub fn draw<D>(self, target: &mut D, position: Point, clef: Clef) -> Result<u32, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let (accidental, amount) = self.signature();
+
+        if accidental == Accidental::Natural {
+            return Ok(0);
+        }
+
+        let (symbol, center) = accidental.glyph();
+
+        let mut x = 0;
+        for &step in clef.key_signature_steps(accidental).iter().take(amount) {
+            let y = Staff::LEDGER_MARGIN + step * Staff::LINE_SPACING / 2 - center;
+            let width = rytmos_symbols::draw_symbol(target, position + Point::new(x, y), symbol)?;
+
+            x += width as i32 + 1;
+        }
+
+        Ok(x as u32 + 1)
+    }
+
+    /// The width used by `draw`.
+    pub fn width(self) -> u32 {
+        let (accidental, amount) = self.signature();
+
+        if accidental == Accidental::Natural {
+            return 0;
+        }
+
+        let ((width, _), _) = accidental.glyph();
+        amount as u32 * (width + 1) + 1
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeSignature {
+    beats: u8,
+    beat_value: u8,
+}
+
+impl TimeSignature {
+    /// A measure of `beats` notes of the value `beat_value`, e.g. 6/8 is `new(6, 8)`. `None` for
+    /// a measure without beats, or a beat value that isn't a power of two.
+    pub const fn new(beats: u8, beat_value: u8) -> Option<Self> {
+        if beats == 0 || !beat_value.is_power_of_two() {
+            return None;
+        }
+
+        Some(Self { beats, beat_value })
+    }
+
+    pub fn beats(self) -> u8 {
+        self.beats
+    }
+
+    pub fn beat_value(self) -> u8 {
+        self.beat_value
+    }
+
+    pub fn measure_length(self) -> Ticks {
+        Ticks(self.beats as u32 * self.unit().0)
+    }
+
+    /// Length of the note given by the bottom number.
+    fn unit(self) -> Ticks {
+        Ticks(Ticks::PER_QUARTER * 4 / self.beat_value as u32)
+    }
+
+    /// The groups of a measure that notes are beamed in, in units of the bottom number.
+    /// Meters in quarters or halves group per beat, compound meters (6/8, 9/8, 12/8) per three,
+    /// and other meters in eighths or shorter in twos with a final group of three (7/8 as 2+2+3).
+    pub fn beam_groups(self) -> Vec<u8, 16> {
+        let (size, last) = if self.beat_value <= 4 {
+            (1, 1)
+        } else if self.beats.is_multiple_of(3) {
+            (3, 3)
+        } else if !self.beats.is_multiple_of(2) && self.beats > 3 {
+            (2, 3)
+        } else {
+            (self.beats.min(2), self.beats.min(2))
+        };
+
+        let mut groups = Vec::new();
+        let mut remaining = self.beats;
+        while remaining > 0 && !groups.is_full() {
+            let group = if remaining == last {
+                last
+            } else {
+                size.min(remaining)
+            };
+            // Capacity was checked, pushing cannot fail
+            let _ = groups.push(group);
+            remaining -= group;
+        }
+
+        groups
+    }
+
+    /// Syllable that a note at the given time in the measure is counted with: the number of the
+    /// beat on a beat, e, + or a on the quarters of a beat, nothing in between.
+    fn count(self, time: Ticks) -> Vec<(u32, &'static [u32]), 2> {
+        let unit = self.unit();
+        let time = time % self.measure_length();
+        let beat = time.0 / unit.0 + 1;
+        let offset = time % unit;
+
+        let mut syllable = Vec::new();
+        if offset == Ticks(0) {
+            if beat >= 10 {
+                let _ = syllable.push(BEAT_NUMBERS[(beat / 10 % 10) as usize]);
+            }
+            let _ = syllable.push(BEAT_NUMBERS[(beat % 10) as usize]);
+        } else if (offset.0 * 4).is_multiple_of(unit.0) {
+            let _ = syllable.push(match offset.0 * 4 / unit.0 {
+                1 => BEAT_E,
+                2 => BEAT_AND,
+                _ => BEAT_A,
+            });
+        }
+
+        syllable
+    }
+
+    /// Index of the beam group that the given time (since the start of the measure) falls in.
+    /// Counting continues in the next measures for music longer than one.
+    pub(crate) fn beam_group_index(self, time: Ticks) -> u32 {
+        let groups = self.beam_groups();
+
+        let measures = time.0 / self.measure_length().0;
+        let mut time = time % self.measure_length();
+        let mut index = measures * groups.len() as u32;
+
+        for &group in groups.iter() {
+            let length = Ticks(group as u32 * self.unit().0);
+            if time < length {
+                break;
+            }
+            time = time - length;
+            index += 1;
+        }
+
+        index
+    }
+
+    /// Draws the two numbers stacked on the staff, returns the width used.
+    pub fn draw<D>(self, target: &mut D, position: Point) -> Result<u32, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let top_width = Self::number_width(self.beats);
+        let bottom_width = Self::number_width(self.beat_value);
+        let width = top_width.max(bottom_width);
+
+        let top = position + Point::new((width - top_width) / 2, Staff::LEDGER_MARGIN + 1);
+        Self::draw_number(target, top, self.beats)?;
+
+        let bottom = position
+            + Point::new(
+                (width - bottom_width) / 2,
+                Staff::LEDGER_MARGIN + Staff::LINE_SPACING * 2 + 1,
+            );
+        Self::draw_number(target, bottom, self.beat_value)?;
+
+        Ok(self.width())
+    }
+
+    /// The width used by `draw`.
+    pub fn width(self) -> u32 {
+        Self::number_width(self.beats).max(Self::number_width(self.beat_value)) as u32 + 2
+    }
+
+    fn number_width(number: u8) -> i32 {
+        if number >= 10 {
+            11
+        } else {
+            5
+        }
+    }
+
+    fn draw_number<D>(target: &mut D, position: Point, number: u8) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        if number >= 10 {
+            rytmos_symbols::draw_symbol(target, position, NUMERALS[(number / 10 % 10) as usize])?;
+            let position = position + Point::new(6, 0);
+            rytmos_symbols::draw_symbol(target, position, NUMERALS[(number % 10) as usize])?;
+        } else {
+            rytmos_symbols::draw_symbol(target, position, NUMERALS[number as usize])?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        Self {
+            beats: 4,
+            beat_value: 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Clef {
     Bass,
     Treble,
+    /// C clef with middle C on the middle line
+    Alto,
+    /// C clef with middle C on the fourth line
+    Tenor,
 }
 
 impl Clef {
+    /// Draws the clef, the given position is the top left of the top staff line.
+    /// Returns the width used.
     pub fn draw<D>(self, target: &mut D, position: Point) -> Result<u32, D::Error>
     where
         D: DrawTarget<Color = BinaryColor>,
     {
         match self {
-            Clef::Bass => {
-                rytmos_symbols::draw_symbol(target, position, BASS_CLEF)?;
-                Ok(13)
+            Clef::Bass => rytmos_symbols::draw_symbol(target, position, BASS_CLEF)?,
+            Clef::Treble => {
+                rytmos_symbols::draw_symbol(target, position + Point::new(0, -6), TREBLE_CLEF)?
+            }
+            Clef::Alto => rytmos_symbols::draw_symbol(target, position, C_CLEF)?,
+            Clef::Tenor => {
+                let offset = Point::new(0, -Staff::LINE_SPACING);
+                rytmos_symbols::draw_symbol(target, position + offset, C_CLEF)?
             }
-            Clef::Treble => Ok(0),
+        };
+
+        Ok(self.width())
+    }
+
+    /// The width used by `draw`.
+    pub fn width(self) -> u32 {
+        match self {
+            Clef::Bass => 13,
+            Clef::Treble => 11,
+            Clef::Alto | Clef::Tenor => 10,
+        }
+    }
+
+    /// Diatonic step (see `Note::diatonic_step`) of the note on the top staff line.
+    fn top_line_step(self) -> i32 {
+        match self {
+            Clef::Bass => 26,   // A3
+            Clef::Treble => 38, // F5
+            Clef::Alto => 32,   // G4
+            Clef::Tenor => 30,  // E4
+        }
+    }
+
+    fn middle_line_step(self) -> i32 {
+        self.top_line_step() - 4
+    }
+
+    /// Staff positions of the key signature accidentals in circle of fifths order,
+    /// counted in half line spacings down from the top staff line.
+    fn key_signature_steps(self, accidental: Accidental) -> [i32; 7] {
+        let flats = matches!(accidental, Accidental::Flat | Accidental::DoubleFlat);
+
+        match (self, flats) {
+            // F5 C5 G5 D5 A4 E5 B4 and B4 E5 A4 D5 G4 C5 F4
+            (Clef::Treble, false) => [0, 3, -1, 2, 5, 1, 4],
+            (Clef::Treble, true) => [4, 1, 5, 2, 6, 3, 7],
+            (Clef::Bass, false) => [2, 5, 1, 4, 7, 3, 6],
+            (Clef::Bass, true) => [6, 3, 7, 4, 8, 5, 9],
+            (Clef::Alto, false) => [1, 4, 0, 3, 6, 2, 5],
+            (Clef::Alto, true) => [5, 2, 6, 3, 7, 4, 8],
+            // Tenor sharps start low on F3 to stay within the staff
+            (Clef::Tenor, false) => [6, 2, 5, 1, 4, 0, 3],
+            (Clef::Tenor, true) => [3, 0, 4, 1, 5, 2, 6],
         }
     }
 }
 
+/// A staff of the given width. The capacities bound the amount of symbols in a single music
+/// element and in a single beam group, see `Staff::with_capacity`.
 #[derive(Clone, Copy)]
-pub struct Staff {
+pub struct Staff<
+    const SYMBOLS: usize = DEFAULT_SYMBOL_CAPACITY,
+    const BEAM_GROUP: usize = DEFAULT_BEAM_GROUP_CAPACITY,
+> {
     width: i32,
     position: Point,
+    spacing: Spacing,
 }
 
 impl Staff {
-    const LINE_SPACING: i32 = 4;
-    const LEDGER_MARGIN: i32 = Self::LINE_SPACING * 5;
-    const BASS_CLEF_OFFSET: Point = Point {
+    pub(crate) const LINE_SPACING: i32 = 4;
+    pub(crate) const LEDGER_MARGIN: i32 = Self::LINE_SPACING * 5;
+    const CLEF_OFFSET: Point = Point {
         x: 0,
         y: Self::LEDGER_MARGIN,
     };
 
+    pub(crate) const BARLINE_WIDTH: u32 = 3;
+
     pub fn new(width: u32, position: Point) -> Self {
+        Self::with_capacity(width, position)
+    }
+}
+
+impl<const SYMBOLS: usize, const BEAM_GROUP: usize> Staff<SYMBOLS, BEAM_GROUP> {
+    /// Creates a staff that can hold `SYMBOLS` notes, rests and chords per music element and
+    /// `BEAM_GROUP` symbols per beam group, e.g. `Staff::<32, 8>::with_capacity(..)` for runs of
+    /// 32nd notes. Music that doesn't fit is not drawn.
+    pub fn with_capacity(width: u32, position: Point) -> Self {
         Self {
             width: width as i32,
             position,
+            spacing: Spacing::default(),
         }
     }
 
-    pub fn draw<D>(&self, target: &mut D, elements: &[StaffElement]) -> Result<(), D::Error>
+    /// The staff with music spaced as given, spaced evenly by default.
+    pub fn with_spacing(mut self, spacing: Spacing) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width as u32
+    }
+
+    /// Draws the staff lines and the elements on it from left to right. Key signatures and
+    /// music are placed according to the last clef element, which is a bass clef until one is
+    /// given. Accidentals in the music are shown relative to the last key signature element, and
+    /// notes are beamed according to the last time signature element, which is 4/4 until one is
+    /// given. Every music element is a measure, a staff position altered in one measure gets a
+    /// courtesy accidental in the music element after it.
+    /// The width left by the other elements is divided over the music elements, in proportion to
+    /// the width their symbols need at least. Returns where the notes and rests were drawn.
+    pub fn draw<D>(&self, target: &mut D, elements: &[StaffElement]) -> Result<Layout, D::Error>
     where
         D: DrawTarget<Color = BinaryColor>,
     {
@@ -1232,38 +3544,203 @@ impl Staff {
             Line::new(
                 Point::new(
                     self.position.x,
-                    Self::LEDGER_MARGIN + self.position.y + Self::LINE_SPACING * i,
+                    Staff::LEDGER_MARGIN + self.position.y + Staff::LINE_SPACING * i,
                 ),
                 Point::new(
                     self.position.x + self.width,
-                    Self::LEDGER_MARGIN + self.position.y + Self::LINE_SPACING * i,
+                    Staff::LEDGER_MARGIN + self.position.y + Staff::LINE_SPACING * i,
                 ),
             )
             .into_styled(line_style)
             .draw(target)?;
         }
 
+        // Determine the width the music elements can divide
+        let mut music_space = self.width;
+        let mut music_weight = 0;
+        let mut current_clef = Clef::Bass;
+        let mut current_key = Key::CMajor;
+        let mut previous: [&[Music]; 2] = [&[], &[]];
+
+        for element in elements {
+            match element {
+                StaffElement::Barline => music_space -= Staff::BARLINE_WIDTH as i32,
+                StaffElement::KeySignature(key) => {
+                    current_key = *key;
+                    music_space -= key.width() as i32;
+                }
+                StaffElement::TimeSignature(time_signature) => {
+                    music_space -= time_signature.width() as i32
+                }
+                StaffElement::Clef(clef) => {
+                    current_clef = *clef;
+                    music_space -= clef.width() as i32;
+                }
+                StaffElement::Counting(_) => {}
+                StaffElement::Music(music) => {
+                    music_weight += Music::minimum_width::<SYMBOLS>(
+                        music,
+                        &previous,
+                        current_clef,
+                        current_key,
+                    );
+                    previous = [music, &[]];
+                }
+                StaffElement::Voices(upper, lower) => {
+                    music_weight += Music::minimum_voices_width::<SYMBOLS>(
+                        upper,
+                        lower,
+                        &previous,
+                        current_clef,
+                        current_key,
+                    );
+                    previous = [upper, lower];
+                }
+            }
+        }
+
         let mut working_position = self.position;
+        let mut current_clef = Clef::Bass;
+        let mut current_key = Key::CMajor;
+        let mut current_time_signature = TimeSignature::default();
+        let mut current_counting = false;
+        let mut previous: [&[Music]; 2] = [&[], &[]];
+        let mut layout = Layout::new(Rectangle::new(
+            self.position + Point::new(0, Staff::LEDGER_MARGIN),
+            Size::new(self.width as u32, Staff::LINE_SPACING as u32 * 4 + 1),
+        ));
 
         for element in elements {
             let width_used = match element {
                 StaffElement::Barline => Self::draw_barline(target, working_position)?,
-                StaffElement::KeySignature(_) => todo!(),
+                StaffElement::KeySignature(key) => {
+                    current_key = *key;
+                    key.draw(target, working_position, current_clef)?
+                }
+                StaffElement::TimeSignature(time_signature) => {
+                    current_time_signature = *time_signature;
+                    time_signature.draw(target, working_position)?
+                }
                 StaffElement::Clef(clef) => {
-                    clef.draw(target, working_position + Self::BASS_CLEF_OFFSET)?
+                    current_clef = *clef;
+                    clef.draw(target, working_position + Staff::CLEF_OFFSET)?
+                }
+                StaffElement::Counting(counting) => {
+                    current_counting = *counting;
+                    0
+                }
+                StaffElement::Music(music) => {
+                    layout.set_staff(current_clef, current_key);
+                    let weight = Music::minimum_width::<SYMBOLS>(
+                        music,
+                        &previous,
+                        current_clef,
+                        current_key,
+                    );
+                    let width = Self::music_width(&mut music_space, &mut music_weight, weight);
+
+                    let width_used = Music::draw::<_, SYMBOLS, BEAM_GROUP>(
+                        target,
+                        working_position,
+                        width,
+                        music,
+                        &previous,
+                        current_clef,
+                        current_key,
+                        current_time_signature,
+                        self.spacing,
+                        current_counting,
+                        &mut layout,
+                    )?;
+                    previous = [music, &[]];
+
+                    width_used
+                }
+                StaffElement::Voices(upper, lower) => {
+                    layout.set_staff(current_clef, current_key);
+                    let weight = Music::minimum_voices_width::<SYMBOLS>(
+                        upper,
+                        lower,
+                        &previous,
+                        current_clef,
+                        current_key,
+                    );
+                    let width = Self::music_width(&mut music_space, &mut music_weight, weight);
+
+                    let width_used = Music::draw_voices::<_, SYMBOLS, BEAM_GROUP>(
+                        target,
+                        working_position,
+                        width,
+                        upper,
+                        lower,
+                        &previous,
+                        current_clef,
+                        current_key,
+                        current_time_signature,
+                        self.spacing,
+                        current_counting,
+                        &mut layout,
+                    )?;
+                    previous = [upper, lower];
+
+                    width_used
                 }
-                StaffElement::Music(music) => Music::draw(
-                    target,
-                    working_position,
-                    self.width - working_position.x,
-                    music,
-                )?,
             };
 
             working_position.x += width_used as i32;
         }
 
-        Ok(())
+        Ok(layout)
+    }
+
+    /// Where the notes and rests of the elements end up, without drawing anything.
+    pub fn layout(&self, elements: &[StaffElement]) -> Layout {
+        let mut target = LayoutTarget {
+            size: Size::new(
+                (self.position.x + self.width) as u32,
+                (self.position.y + Staff::LEDGER_MARGIN * 2 + Staff::LINE_SPACING * 4) as u32,
+            ),
+        };
+
+        match self.draw(&mut target, elements) {
+            Ok(layout) => layout,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Draws the elements like `draw`, with the symbol that sounds at the given time inverted
+    /// to highlight it. Expects the area of the staff to be cleared.
+    pub fn draw_highlighted<D>(
+        &self,
+        target: &mut D,
+        elements: &[StaffElement],
+        time: Ticks,
+    ) -> Result<Layout, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let Some(symbol) = self.layout(elements).symbol_at(time).copied() else {
+            return self.draw(target, elements);
+        };
+
+        // Grow the box a pixel so the inverted symbol doesn't touch its surroundings
+        let area = symbol.bounding_box.offset(1);
+        area.draw_styled(&PrimitiveStyle::with_fill(BinaryColor::On), target)?;
+
+        self.draw(&mut Inverted::new(target, area), elements)
+    }
+
+    /// Takes the share of the remaining music space for music of the given weight.
+    fn music_width(music_space: &mut i32, music_weight: &mut i32, weight: i32) -> i32 {
+        let width = if *music_weight > 0 {
+            *music_space * weight / *music_weight
+        } else {
+            *music_space
+        };
+        *music_space -= width;
+        *music_weight -= weight;
+
+        width
     }
 
     fn draw_barline<D>(target: &mut D, working_position: Point) -> Result<u32, D::Error>
@@ -1271,14 +3748,96 @@ impl Staff {
         D: DrawTarget<Color = BinaryColor>,
     {
         Rectangle::new(
-            Point::new(working_position.x + 1, 12),
+            Point::new(
+                working_position.x + 1,
+                working_position.y + Staff::LEDGER_MARGIN,
+            ),
             Size {
                 width: 1,
-                height: 17,
+                height: Staff::LINE_SPACING as u32 * 4 + 1,
             },
         )
         .draw_styled(&PrimitiveStyle::with_stroke(BinaryColor::On, 1), target)?;
 
-        Ok(3)
+        Ok(Staff::BARLINE_WIDTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beam_group_index_follows_time_signature() {
+        let indices = |beats, beat_value, eighths: u32| {
+            let time_signature = TimeSignature::new(beats, beat_value).unwrap();
+            (0..eighths)
+                .map(|eighth| time_signature.beam_group_index(Ticks(eighth * 240)))
+                .collect::<std::vec::Vec<u32>>()
+        };
+
+        assert_eq!(indices(3, 4, 6), [0, 0, 1, 1, 2, 2]);
+        assert_eq!(indices(6, 8, 6), [0, 0, 0, 1, 1, 1]);
+        assert_eq!(indices(7, 8, 7), [0, 0, 1, 1, 2, 2, 2]);
+
+        // The next measure continues counting
+        assert_eq!(indices(7, 8, 9)[7..], [3, 3]);
+        assert_eq!(indices(3, 4, 8)[6..], [3, 3]);
+    }
+
+    #[test]
+    fn test_secondary_beams_connect_or_break() {
+        use Duration::*;
+        use SecondaryBeam::*;
+
+        let beams = |durations: &[Duration]| {
+            let flags = durations
+                .iter()
+                .map(|duration| duration.flags())
+                .collect::<std::vec::Vec<u8>>();
+            Music::secondary_beams(&flags).collect::<std::vec::Vec<_>>()
+        };
+
+        assert_eq!(beams(&[Eighth, Eighth]), []);
+        assert_eq!(
+            beams(&[Sixteenth, Sixteenth, Eighth, Sixteenth]),
+            [(2, 0, ToNext), (2, 3, BrokenLeft)]
+        );
+        assert_eq!(
+            beams(&[Sixteenth, Eighth, Sixteenth]),
+            [(2, 0, BrokenRight), (2, 2, BrokenLeft)]
+        );
+        assert_eq!(beams(&[DottedEighth, Sixteenth]), [(2, 1, BrokenLeft)]);
+        assert_eq!(
+            beams(&[ThirtySecond, ThirtySecond, Sixteenth, Eighth]),
+            [(2, 0, ToNext), (2, 1, ToNext), (3, 0, ToNext)]
+        );
+    }
+
+    #[test]
+    fn test_count_names_beats_and_their_quarters() {
+        let count = |beats, beat_value, sixteenths| {
+            TimeSignature::new(beats, beat_value)
+                .unwrap()
+                .count(Ticks::from_sixteenths(sixteenths))
+        };
+
+        assert_eq!(count(4, 4, 0), [BEAT_NUMBERS[1]]);
+        assert_eq!(count(4, 4, 1), [BEAT_E]);
+        assert_eq!(count(4, 4, 2), [BEAT_AND]);
+        assert_eq!(count(4, 4, 3), [BEAT_A]);
+        assert_eq!(count(4, 4, 4), [BEAT_NUMBERS[2]]);
+        // Triplets fall between the syllables
+        assert!(TimeSignature::default()
+            .count(Duration::EighthTriplet.ticks())
+            .is_empty());
+
+        // Eighths are the beats of 6/8 and 12/8
+        assert_eq!(count(6, 8, 1), [BEAT_AND]);
+        assert_eq!(count(6, 8, 2), [BEAT_NUMBERS[2]]);
+        assert_eq!(count(12, 8, 18), [BEAT_NUMBERS[1], BEAT_NUMBERS[0]]);
+
+        // The next measure counts from one again
+        assert_eq!(count(3, 4, 12), [BEAT_NUMBERS[1]]);
     }
 }
@@ -0,0 +1,778 @@
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::Point,
+    pixelcolor::BinaryColor,
+    prelude::Size,
+    primitives::{Line, Primitive, PrimitiveStyle, Rectangle, StyledDrawable},
+    Drawable, Pixel,
+};
+use heapless::Vec;
+use log::error;
+
+use rytmos_symbols::{EIGHT_FLAG, SIXTEENTH_FLAG, SMALL_NUMERALS, TAB_CLEF, THIRTY_SECOND_FLAG};
+
+use crate::layout::{Layout, LayoutTarget};
+use crate::staff::{
+    Accidental, Chord, Duration, EngraveError, Music, Note, SpacedMusicSymbols, Spacing, Staff,
+    StaffElement, Ticks, TimeSignature, DEFAULT_SYMBOL_CAPACITY,
+};
+
+/// Open notes of the strings of an instrument, from the lowest string to the highest.
+#[derive(Debug, Clone, Copy)]
+pub struct Tuning {
+    strings: [Note; Tuning::MAX_STRINGS],
+    string_count: usize,
+}
+
+impl Tuning {
+    pub const MIN_STRINGS: usize = 4;
+    pub const MAX_STRINGS: usize = 6;
+    pub const MAX_FRET: u8 = 24;
+
+    /// E1 A1 D2 G2
+    pub const FOUR_STRING_BASS: Tuning = Tuning {
+        strings: [
+            Note::E(Accidental::Natural, 1),
+            Note::A(Accidental::Natural, 1),
+            Note::D(Accidental::Natural, 2),
+            Note::G(Accidental::Natural, 2),
+            Note::G(Accidental::Natural, 2),
+            Note::G(Accidental::Natural, 2),
+        ],
+        string_count: 4,
+    };
+
+    /// B0 E1 A1 D2 G2
+    pub const FIVE_STRING_BASS: Tuning = Tuning {
+        strings: [
+            Note::B(Accidental::Natural, 0),
+            Note::E(Accidental::Natural, 1),
+            Note::A(Accidental::Natural, 1),
+            Note::D(Accidental::Natural, 2),
+            Note::G(Accidental::Natural, 2),
+            Note::G(Accidental::Natural, 2),
+        ],
+        string_count: 5,
+    };
+
+    /// B0 E1 A1 D2 G2 C3
+    pub const SIX_STRING_BASS: Tuning = Tuning {
+        strings: [
+            Note::B(Accidental::Natural, 0),
+            Note::E(Accidental::Natural, 1),
+            Note::A(Accidental::Natural, 1),
+            Note::D(Accidental::Natural, 2),
+            Note::G(Accidental::Natural, 2),
+            Note::C(Accidental::Natural, 3),
+        ],
+        string_count: 6,
+    };
+
+    /// Tuning with the given open strings from low to high, `None` unless there are four to six.
+    pub fn new(strings: &[Note]) -> Option<Self> {
+        if !(Self::MIN_STRINGS..=Self::MAX_STRINGS).contains(&strings.len()) {
+            return None;
+        }
+
+        let mut tuning = Self {
+            strings: [strings[0]; Self::MAX_STRINGS],
+            string_count: strings.len(),
+        };
+        tuning.strings[..strings.len()].copy_from_slice(strings);
+
+        Some(tuning)
+    }
+
+    pub fn strings(&self) -> &[Note] {
+        &self.strings[..self.string_count]
+    }
+
+    /// String (counted from the lowest) and fret to play the note on, on a string that is not
+    /// taken yet. `None` if the note is out of reach of the free strings.
+    pub fn fret(&self, note: Note, choice: FretChoice, taken: &[usize]) -> Option<(usize, u8)> {
+        let code = note.semitones();
+
+        self.strings()
+            .iter()
+            .enumerate()
+            .filter(|(string, _)| !taken.contains(string))
+            .filter_map(|(string, open)| {
+                let fret = code - open.semitones();
+                (0..=Self::MAX_FRET as i32)
+                    .contains(&fret)
+                    .then_some((string, fret as u8))
+            })
+            .min_by_key(|&(_, fret)| (choice.distance(fret), fret))
+    }
+}
+
+impl PartialEq for Tuning {
+    fn eq(&self, other: &Self) -> bool {
+        self.strings() == other.strings()
+    }
+}
+
+impl Eq for Tuning {}
+
+/// How to choose between the strings that a note can be played on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FretChoice {
+    /// The string with the lowest fret, open strings where possible
+    #[default]
+    LowestPosition,
+    /// The string with a fret closest to the four frets from the given fret on, e.g.
+    /// `FixedPosition(5)` to play in fifth position
+    FixedPosition(u8),
+}
+
+impl FretChoice {
+    /// How far the hand moves from its position to reach the fret.
+    fn distance(self, fret: u8) -> u8 {
+        match self {
+            FretChoice::LowestPosition => fret,
+            FretChoice::FixedPosition(position) => {
+                if fret < position {
+                    position - fret
+                } else {
+                    fret.saturating_sub(position + 3)
+                }
+            }
+        }
+    }
+}
+
+/// A note, chord or rest of tablature.
+#[derive(Debug)]
+struct TabSymbol {
+    start: Ticks,
+    duration: Duration,
+    rest: bool,
+    /// String and fret of every note, the frets of notes that continue a tie are not shown
+    frets: Vec<(usize, u8), { Tuning::MAX_STRINGS }>,
+    x: i32,
+}
+
+impl TabSymbol {
+    fn beamable(&self) -> bool {
+        !self.rest && self.duration.written().flags() > 0
+    }
+}
+
+/// Tablature of the given width: a line per string with the fret of every note on it, and the
+/// rhythm of the notes as stems below the lines. The capacity bounds the amount of symbols in a
+/// single music element, like that of `Staff`.
+#[derive(Clone, Copy)]
+pub struct TabStaff<const SYMBOLS: usize = DEFAULT_SYMBOL_CAPACITY> {
+    width: i32,
+    position: Point,
+    tuning: Tuning,
+    fret_choice: FretChoice,
+    spacing: Spacing,
+}
+
+impl TabStaff {
+    const LINE_SPACING: i32 = 6;
+    const TOP_MARGIN: i32 = 8;
+    /// Distance between the bottom line and the stems.
+    const STEM_MARGIN: i32 = 3;
+    const STEM_LENGTH: i32 = 8;
+    const CLEF_WIDTH: u32 = 10;
+
+    pub fn new(width: u32, position: Point, tuning: Tuning) -> Self {
+        Self::with_capacity(width, position, tuning)
+    }
+}
+
+impl<const SYMBOLS: usize> TabStaff<SYMBOLS> {
+    pub fn with_capacity(width: u32, position: Point, tuning: Tuning) -> Self {
+        Self {
+            width: width as i32,
+            position,
+            tuning,
+            fret_choice: FretChoice::default(),
+            spacing: Spacing::default(),
+        }
+    }
+
+    /// The tablature with notes placed on strings as given, lowest position by default.
+    pub fn with_fret_choice(mut self, fret_choice: FretChoice) -> Self {
+        self.fret_choice = fret_choice;
+        self
+    }
+
+    /// The tablature with music spaced as given, spaced evenly by default.
+    pub fn with_spacing(mut self, spacing: Spacing) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width as u32
+    }
+
+    /// Draws the lines and the elements on them from left to right, like `Staff::draw`. Clefs
+    /// are drawn as a tab clef, key signatures are not drawn. Two voices share the lines, the
+    /// rhythm below the lines is that of the upper voice. Returns where the notes and rests
+    /// were drawn.
+    pub fn draw<D>(&self, target: &mut D, elements: &[StaffElement]) -> Result<Layout, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let line_style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+
+        for string in 0..self.tuning.string_count as i32 {
+            let y = self.position.y + TabStaff::TOP_MARGIN + TabStaff::LINE_SPACING * string;
+            Line::new(
+                Point::new(self.position.x, y),
+                Point::new(self.position.x + self.width, y),
+            )
+            .into_styled(line_style)
+            .draw(target)?;
+        }
+
+        // Determine the width the music elements can divide
+        let mut music_space = self.width;
+        let mut music_weight = 0;
+
+        for element in elements {
+            match element {
+                StaffElement::Barline => music_space -= Staff::BARLINE_WIDTH as i32,
+                StaffElement::KeySignature(_) => {}
+                StaffElement::TimeSignature(time_signature) => {
+                    music_space -= time_signature.width() as i32
+                }
+                StaffElement::Clef(_) => music_space -= TabStaff::CLEF_WIDTH as i32,
+                StaffElement::Counting(_) => {}
+                StaffElement::Music(music) => music_weight += self.minimum_width(&[music]),
+                StaffElement::Voices(upper, lower) => {
+                    music_weight += self.minimum_width(&[upper, lower])
+                }
+            }
+        }
+
+        let mut working_position = self.position;
+        let mut current_time_signature = TimeSignature::default();
+        let mut layout = Layout::new(Rectangle::new(
+            self.position + Point::new(0, TabStaff::TOP_MARGIN),
+            Size::new(self.width as u32, self.lines_height() as u32 + 1),
+        ));
+
+        for element in elements {
+            let width_used = match element {
+                StaffElement::Barline => self.draw_barline(target, working_position)?,
+                StaffElement::KeySignature(_) | StaffElement::Counting(_) => 0,
+                StaffElement::TimeSignature(time_signature) => {
+                    current_time_signature = *time_signature;
+
+                    // Centered on the lines rather than on the five lines of a staff
+                    let offset = TabStaff::TOP_MARGIN - Staff::LEDGER_MARGIN
+                        + (self.lines_height() - Staff::LINE_SPACING * 4) / 2;
+                    time_signature.draw(target, working_position + Point::new(0, offset))?
+                }
+                StaffElement::Clef(_) => {
+                    let offset = TabStaff::TOP_MARGIN
+                        + (self.lines_height() - (TAB_CLEF.1.len() as i32 - 1)) / 2;
+                    rytmos_symbols::draw_symbol(
+                        target,
+                        working_position + Point::new(1, offset),
+                        TAB_CLEF,
+                    )?;
+                    TabStaff::CLEF_WIDTH
+                }
+                StaffElement::Music(music) => {
+                    let voices = [*music];
+                    let weight = self.minimum_width(&voices);
+                    let width = Self::music_width(&mut music_space, &mut music_weight, weight);
+
+                    self.draw_music(
+                        target,
+                        working_position,
+                        width,
+                        &voices,
+                        current_time_signature,
+                        &mut layout,
+                    )?
+                }
+                StaffElement::Voices(upper, lower) => {
+                    let voices = [*upper, *lower];
+                    let weight = self.minimum_width(&voices);
+                    let width = Self::music_width(&mut music_space, &mut music_weight, weight);
+
+                    self.draw_music(
+                        target,
+                        working_position,
+                        width,
+                        &voices,
+                        current_time_signature,
+                        &mut layout,
+                    )?
+                }
+            };
+
+            working_position.x += width_used as i32;
+        }
+
+        Ok(layout)
+    }
+
+    /// Where the notes and rests of the elements end up, without drawing anything.
+    pub fn layout(&self, elements: &[StaffElement]) -> Layout {
+        let mut target = LayoutTarget {
+            size: Size::new(
+                (self.position.x + self.width) as u32,
+                (self.position.y + self.height()) as u32,
+            ),
+        };
+
+        match self.draw(&mut target, elements) {
+            Ok(layout) => layout,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Height of the tablature including the margin above the lines and the rhythm below them.
+    pub fn height(&self) -> i32 {
+        TabStaff::TOP_MARGIN
+            + self.lines_height()
+            + TabStaff::STEM_MARGIN
+            + TabStaff::STEM_LENGTH
+            + SMALL_NUMERALS[0].1.len() as i32
+            + 2
+    }
+
+    /// Distance between the top and the bottom line.
+    fn lines_height(&self) -> i32 {
+        TabStaff::LINE_SPACING * (self.tuning.string_count as i32 - 1)
+    }
+
+    /// Y of the line of the given string, counted from the lowest.
+    fn string_y(&self, string: usize) -> i32 {
+        TabStaff::TOP_MARGIN
+            + TabStaff::LINE_SPACING * (self.tuning.string_count - 1 - string) as i32
+    }
+
+    /// Takes the share of the remaining music space for music of the given weight.
+    fn music_width(music_space: &mut i32, music_weight: &mut i32, weight: i32) -> i32 {
+        let width = if *music_weight > 0 {
+            *music_space * weight / *music_weight
+        } else {
+            *music_space
+        };
+        *music_space -= width;
+        *music_weight -= weight;
+
+        width
+    }
+
+    /// The least width that the voices can be drawn in.
+    fn minimum_width(&self, voices: &[&[Music]]) -> i32 {
+        let columns = self
+            .symbols(voices)
+            .and_then(|voices| Self::columns(&voices))
+            .map(|columns| columns.len())
+            .unwrap_or_default();
+
+        columns as i32 * SpacedMusicSymbols::<SYMBOLS>::MINIMUM_SYMBOL_SPACE
+    }
+
+    /// The notes, chords and rests of every voice with the frets to play them at. Notes that
+    /// cannot be played with the tuning, or that don't fit on a free string of a chord, are
+    /// left out.
+    fn symbols(
+        &self,
+        voices: &[&[Music]],
+    ) -> Result<Vec<Vec<TabSymbol, SYMBOLS>, 2>, EngraveError> {
+        let mut symbols = Vec::new();
+
+        for music in voices {
+            let mut voice = Vec::new();
+            let mut start = Ticks(0);
+            let mut tie_pending = false;
+
+            for &element in music.iter() {
+                let (chord, duration) = match element {
+                    Music::Note(note, duration) => (Some(Chord::from(note)), duration),
+                    Music::Chord(chord, duration) => (Some(chord), duration),
+                    Music::Rest(duration) => (None, duration),
+                    Music::Tie => {
+                        tie_pending = true;
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                // Strings of tied notes are taken without showing their frets
+                let mut frets = Vec::new();
+                let mut taken: Vec<usize, { Tuning::MAX_STRINGS }> = Vec::new();
+                for &note in chord.as_ref().map(Chord::notes).unwrap_or_default() {
+                    let Some((string, fret)) = self.tuning.fret(note, self.fret_choice, &taken)
+                    else {
+                        continue;
+                    };
+                    let _ = taken.push(string);
+                    if !tie_pending {
+                        let _ = frets.push((string, fret));
+                    }
+                }
+
+                voice
+                    .push(TabSymbol {
+                        start,
+                        duration,
+                        rest: matches!(element, Music::Rest(_)),
+                        frets,
+                        x: 0,
+                    })
+                    .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?;
+
+                start += duration.ticks();
+                tie_pending = false;
+            }
+
+            symbols
+                .push(voice)
+                .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?;
+        }
+
+        Ok(symbols)
+    }
+
+    /// Start time of every column of the voices, symbols that start at the same time share a
+    /// column.
+    fn columns(voices: &[Vec<TabSymbol, SYMBOLS>]) -> Result<Vec<Ticks, SYMBOLS>, EngraveError> {
+        let mut columns: Vec<Ticks, SYMBOLS> = Vec::new();
+
+        for symbol in voices.iter().flatten() {
+            if !columns.contains(&symbol.start) {
+                columns
+                    .push(symbol.start)
+                    .map_err(|_| EngraveError::TooManyMusicSymbolsForVecAllocation)?;
+            }
+        }
+
+        columns.sort_unstable();
+
+        Ok(columns)
+    }
+
+    /// Places the symbols of the voices in columns dividing the width.
+    fn spaced_symbols(
+        &self,
+        voices: &[&[Music]],
+        width: i32,
+    ) -> Result<Vec<Vec<TabSymbol, SYMBOLS>, 2>, EngraveError> {
+        let mut symbols = self.symbols(voices)?;
+        let columns = Self::columns(&symbols)?;
+
+        let end = symbols
+            .iter()
+            .filter_map(|voice| voice.last())
+            .map(|symbol| symbol.start + symbol.duration.ticks())
+            .max()
+            .unwrap_or_default();
+        let durations: Vec<Ticks, SYMBOLS> = columns
+            .iter()
+            .enumerate()
+            .map(|(index, &start)| columns.get(index + 1).copied().unwrap_or(end) - start)
+            .collect();
+        let spaces = SpacedMusicSymbols::<SYMBOLS>::spaces(width, &durations, self.spacing)?;
+
+        for symbol in symbols.iter_mut().flatten() {
+            let column = columns
+                .iter()
+                .position(|&start| start == symbol.start)
+                .unwrap_or_default();
+            // Leave room for frets of two digits, which are centered on the stem
+            symbol.x = spaces[..column].iter().sum::<i32>() + 2;
+        }
+
+        Ok(symbols)
+    }
+
+    fn draw_music<D>(
+        &self,
+        target: &mut D,
+        position: Point,
+        width: i32,
+        voices: &[&[Music]],
+        time_signature: TimeSignature,
+        layout: &mut Layout,
+    ) -> Result<u32, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let symbols = match self.spaced_symbols(voices, width) {
+            Ok(symbols) => symbols,
+            Err(error) => {
+                error!("Error engraving tablature: {:?}", error);
+                return Ok(width as u32);
+            }
+        };
+
+        for (index, voice) in symbols.iter().enumerate() {
+            for symbol in voice {
+                let mut bounding_box = self.draw_frets(target, position, symbol)?;
+                if index == 0 {
+                    let stem = self.draw_rhythm(target, position, symbol)?;
+                    bounding_box = envelope(bounding_box, stem);
+                }
+                let bounding_box = bounding_box.unwrap_or(Rectangle::new(
+                    Point::new(symbol.x, TabStaff::TOP_MARGIN),
+                    Size::zero(),
+                ));
+                layout.push(
+                    symbol.start,
+                    symbol.duration,
+                    symbol.rest,
+                    Rectangle::new(position + bounding_box.top_left, bounding_box.size),
+                );
+            }
+        }
+
+        if let Some(upper) = symbols.first() {
+            self.draw_beams(target, position, upper, time_signature)?;
+        }
+
+        let length = symbols
+            .iter()
+            .filter_map(|voice| voice.last())
+            .map(|symbol| symbol.start + symbol.duration.ticks())
+            .max()
+            .unwrap_or_default();
+        layout.extend(length);
+
+        Ok(width as u32)
+    }
+
+    /// Draws the frets of the symbol on their lines, or the rest between the lines. Returns the
+    /// box around what was drawn, relative to the position.
+    fn draw_frets<D>(
+        &self,
+        target: &mut D,
+        position: Point,
+        symbol: &TabSymbol,
+    ) -> Result<Option<Rectangle>, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut bounding_box = None;
+
+        if symbol.rest {
+            if let Some(glyph) = Music::rest_glyph(symbol.duration.written()) {
+                let height = glyph.1.len() as i32;
+                let top_left = Point::new(
+                    symbol.x,
+                    TabStaff::TOP_MARGIN + (self.lines_height() - height) / 2,
+                );
+                rytmos_symbols::draw_symbol(target, position + top_left, glyph)?;
+                bounding_box = Some(Rectangle::new(top_left, Size::new(glyph.0, height as u32)));
+            }
+        }
+
+        for &(string, fret) in &symbol.frets {
+            let digits: &[u8] = if fret >= 10 {
+                &[fret / 10, fret % 10]
+            } else {
+                &[fret]
+            };
+            let digit_width = SMALL_NUMERALS[0].0 as i32;
+            let number_width = digits.len() as i32 * (digit_width + 1) - 1;
+
+            // Centered on the stem, on a cleared background that interrupts the line
+            let top_left = Point::new(symbol.x + 1 - number_width / 2, self.string_y(string) - 2);
+            let area = Rectangle::new(
+                top_left - Point::new(1, 0),
+                Size::new(number_width as u32 + 2, 5),
+            );
+            Rectangle::new(position + area.top_left, area.size)
+                .draw_styled(&PrimitiveStyle::with_fill(BinaryColor::Off), target)?;
+
+            for (index, &digit) in digits.iter().enumerate() {
+                let offset = Point::new(index as i32 * (digit_width + 1), 0);
+                rytmos_symbols::draw_symbol(
+                    target,
+                    position + top_left + offset,
+                    SMALL_NUMERALS[digit as usize],
+                )?;
+            }
+
+            bounding_box = envelope(bounding_box, Some(area));
+        }
+
+        Ok(bounding_box)
+    }
+
+    /// Draws the stem, flags and dots of a note below the lines, half notes get half a stem and
+    /// longer notes none. Returns the box around the stem, relative to the position.
+    fn draw_rhythm<D>(
+        &self,
+        target: &mut D,
+        position: Point,
+        symbol: &TabSymbol,
+    ) -> Result<Option<Rectangle>, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+        let kind = symbol.duration.written();
+        let stem_x = symbol.x + 1;
+        let bottom = self.stem_bottom();
+
+        let top = match kind {
+            _ if symbol.rest => return Ok(None),
+            Duration::Breve | Duration::Whole | Duration::DottedWhole => bottom,
+            Duration::Half | Duration::DottedHalf | Duration::DoubleDottedHalf => {
+                bottom - TabStaff::STEM_LENGTH / 2
+            }
+            _ => bottom - TabStaff::STEM_LENGTH,
+        };
+
+        if top < bottom {
+            Line::new(
+                position + Point::new(stem_x, top),
+                position + Point::new(stem_x, bottom),
+            )
+            .draw_styled(&style, target)?;
+        }
+
+        for dot in 0..kind.dots() as i32 {
+            let dot = Point::new(stem_x + 2 + dot * 2, top + 1);
+            Pixel(position + dot, BinaryColor::On).draw(target)?;
+        }
+
+        Ok(Some(Rectangle::with_corners(
+            Point::new(stem_x, top),
+            Point::new(stem_x, bottom),
+        )))
+    }
+
+    /// Beams the notes with flags per beam group of the time signature, a note that is alone in
+    /// its group gets flags instead.
+    fn draw_beams<D>(
+        &self,
+        target: &mut D,
+        position: Point,
+        symbols: &[TabSymbol],
+        time_signature: TimeSignature,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+        let bottom = self.stem_bottom();
+        let levels = |symbol: &TabSymbol| symbol.duration.written().flags() as i32;
+
+        let mut index = 0;
+        while index < symbols.len() {
+            // Consecutive notes with flags in the same beam group
+            let group = time_signature.beam_group_index(symbols[index].start);
+            let run = symbols[index..]
+                .iter()
+                .take_while(|symbol| {
+                    symbol.beamable() && time_signature.beam_group_index(symbol.start) == group
+                })
+                .count();
+
+            if run == 0 {
+                index += 1;
+                continue;
+            }
+
+            let notes = &symbols[index..index + run];
+            index += run;
+
+            if let [note] = notes {
+                let flag = match levels(note) {
+                    1 => Some(EIGHT_FLAG),
+                    2 => Some(SIXTEENTH_FLAG),
+                    3 => Some(THIRTY_SECOND_FLAG),
+                    _ => None,
+                };
+                // Flipped like the flags of stems down on a staff, ending at the end of the stem
+                if let Some(glyph) = flag {
+                    let flag_position = Point::new(note.x - 1, bottom + 1 - glyph.1.len() as i32);
+                    rytmos_symbols::draw_symbol_with_direction(
+                        target,
+                        position + flag_position,
+                        glyph,
+                        true,
+                    )?;
+                }
+                continue;
+            }
+
+            for (note_index, note) in notes.iter().enumerate() {
+                let stem_x = note.x + 1;
+                let next = notes.get(note_index + 1);
+
+                for level in 0..levels(note) {
+                    let y = bottom - level * 2;
+                    match next {
+                        Some(next) if levels(next) > level => {
+                            Line::new(
+                                position + Point::new(stem_x, y),
+                                position + Point::new(next.x + 1, y),
+                            )
+                            .draw_styled(&style, target)?;
+                        }
+                        // Notes with more beams than their neighbours get a broken beam
+                        _ if note_index == 0 || levels(&notes[note_index - 1]) <= level => {
+                            let end = if note_index == 0 {
+                                stem_x + 3
+                            } else {
+                                stem_x - 3
+                            };
+                            Line::new(
+                                position + Point::new(stem_x, y),
+                                position + Point::new(end, y),
+                            )
+                            .draw_styled(&style, target)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            if let Some((count, _)) = notes[0].duration.tuplet() {
+                let numeral = SMALL_NUMERALS[count as usize % 10];
+                let center = (notes[0].x + notes[run - 1].x) / 2 + 1;
+                let numeral_position = Point::new(center - numeral.0 as i32 / 2, bottom + 2);
+                rytmos_symbols::draw_symbol(target, position + numeral_position, numeral)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Y of the bottom of the stems.
+    fn stem_bottom(&self) -> i32 {
+        TabStaff::TOP_MARGIN + self.lines_height() + TabStaff::STEM_MARGIN + TabStaff::STEM_LENGTH
+    }
+
+    fn draw_barline<D>(&self, target: &mut D, working_position: Point) -> Result<u32, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let top = working_position + Point::new(1, TabStaff::TOP_MARGIN);
+        Line::new(top, top + Point::new(0, self.lines_height()))
+            .draw_styled(&PrimitiveStyle::with_stroke(BinaryColor::On, 1), target)?;
+
+        Ok(Staff::BARLINE_WIDTH)
+    }
+}
+
+/// Smallest box around both boxes.
+fn envelope(a: Option<Rectangle>, b: Option<Rectangle>) -> Option<Rectangle> {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let (Some(a_end), Some(b_end)) = (a.bottom_right(), b.bottom_right()) else {
+                return Some(a);
+            };
+            Some(Rectangle::with_corners(
+                a.top_left.component_min(b.top_left),
+                a_end.component_max(b_end),
+            ))
+        }
+        (a, b) => a.or(b),
+    }
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "std")]
+
+use embedded_graphics::{geometry::Point, pixelcolor::BinaryColor};
+use rytmos_engrave::{
+    bitmap::Bitmap,
+    c, e, fis,
+    staff::{Clef, Duration, Key, Music, Staff, StaffElement},
+};
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 64;
+
+fn elements(music: &[Music]) -> [StaffElement<'_>; 3] {
+    [
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::KeySignature(Key::GMajor),
+        StaffElement::Music(music),
+    ]
+}
+
+fn draw() -> Bitmap<WIDTH, HEIGHT> {
+    let music = [
+        Music::Note(c!(3), Duration::Eighth),
+        Music::Note(e!(3), Duration::Eighth),
+        Music::Note(fis!(3), Duration::Quarter),
+    ];
+    let staff = Staff::new(WIDTH as u32, Point::new(0, 0));
+
+    let mut bitmap = Bitmap::new();
+    staff.draw(&mut bitmap, &elements(&music)).unwrap();
+
+    bitmap
+}
+
+fn is_on(bitmap: &Bitmap<WIDTH, HEIGHT>, x: usize, y: usize) -> bool {
+    bitmap.pixel(Point::new(x as i32, y as i32)) == Some(BinaryColor::On)
+}
+
+#[test]
+fn test_export_svg_has_the_drawn_pixels() {
+    let bitmap = draw();
+    let svg = bitmap.to_svg(3);
+
+    assert!(svg.starts_with(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="192" height="192" viewBox="0 0 64 64""#
+    ));
+    assert!(svg.contains(r#"<rect width="64" height="64" fill="white"/>"#));
+    assert!(svg.trim_end().ends_with("</svg>"));
+
+    // Every run of the path is a row of pixels that are on
+    let path = svg
+        .split(r#"d=""#)
+        .nth(1)
+        .and_then(|path| path.split('"').next())
+        .unwrap();
+    let mut pixels = vec![vec![false; WIDTH]; HEIGHT];
+    for run in path.split('M').filter(|run| !run.is_empty()) {
+        let (x, rest) = run.split_once(' ').unwrap();
+        let (y, rest) = rest.split_once('h').unwrap();
+        let (length, _) = rest.split_once('v').unwrap();
+        let (x, y, length): (usize, usize, usize) = (
+            x.parse().unwrap(),
+            y.parse().unwrap(),
+            length.parse().unwrap(),
+        );
+        for pixel in &mut pixels[y][x..x + length] {
+            assert!(!*pixel, "runs overlap at {x}, {y}");
+            *pixel = true;
+        }
+    }
+
+    for (y, row) in pixels.iter().enumerate() {
+        for (x, &on) in row.iter().enumerate() {
+            assert_eq!(on, is_on(&bitmap, x, y), "pixel {x}, {y}");
+        }
+    }
+}
+
+#[test]
+fn test_export_png_has_the_drawn_pixels() {
+    let bitmap = draw();
+    let mut bytes = Vec::new();
+    bitmap.write_png(&mut bytes, 2).unwrap();
+
+    let mut reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    assert_eq!(
+        (info.width, info.height),
+        (WIDTH as u32 * 2, HEIGHT as u32 * 2)
+    );
+    assert_eq!(info.color_type, png::ColorType::Grayscale);
+
+    let mut drawn = 0;
+    for y in 0..HEIGHT * 2 {
+        for x in 0..WIDTH * 2 {
+            let gray = data[y * info.line_size + x];
+            let on = is_on(&bitmap, x / 2, y / 2);
+            assert_eq!(gray, if on { 0x00 } else { 0xff }, "pixel {x}, {y}");
+            drawn += usize::from(on);
+        }
+    }
+
+    // The staff lines alone are on in five rows
+    assert!(drawn > WIDTH * 2 * 2 * 5);
+}
+
+#[test]
+fn test_export_scale_is_at_least_one() {
+    let bitmap = draw();
+
+    assert_eq!(bitmap.to_svg(0), bitmap.to_svg(1));
+    assert!(bitmap
+        .to_svg(0)
+        .starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="64" height="64""#));
+
+    let mut scaled = Vec::new();
+    bitmap.write_png(&mut scaled, 0).unwrap();
+    let mut unscaled = Vec::new();
+    bitmap.write_png(&mut unscaled, 1).unwrap();
+    assert_eq!(scaled, unscaled);
+}
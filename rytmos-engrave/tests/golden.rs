@@ -0,0 +1,110 @@
+//! Compares the examples of the simulator against golden images in `tests/golden`, so that
+//! changes to what the engraver draws are noticed. After an intended change, run the tests with
+//! `UPDATE_GOLDEN=1` to write the new images, and check them before committing.
+
+use std::{env, fs, path::PathBuf};
+
+use embedded_graphics::geometry::Point;
+use rytmos_engrave::{
+    bitmap::Bitmap,
+    staff::{Staff, StaffElement},
+    tab::{TabStaff, Tuning},
+};
+
+#[path = "../../sim/src/examples.rs"]
+mod examples;
+
+/// Size of the display of the simulator.
+const WIDTH: usize = 128;
+const HEIGHT: usize = 64;
+
+type Image = Bitmap<WIDTH, HEIGHT>;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name)
+        .with_extension("pbm")
+}
+
+/// Compares the image against its golden image, or writes it as the golden image when updating.
+/// Returns whether it matches.
+fn matches_golden(name: &str, image: &Image) -> bool {
+    let path = golden_path(name);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        let mut text = String::new();
+        image.write_pbm(&mut text).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, text).unwrap();
+        return true;
+    }
+
+    let golden = fs::read_to_string(&path)
+        .ok()
+        .and_then(|text| Image::from_pbm(&text));
+
+    match golden {
+        Some(golden) if golden == *image => true,
+        Some(golden) => {
+            eprintln!("{name} differs from {}", path.display());
+            eprintln!("expected:\n{golden}");
+            eprintln!("drawn:\n{image}");
+            false
+        }
+        None => {
+            eprintln!("{name} has no golden image at {}", path.display());
+            eprintln!("drawn:\n{image}");
+            false
+        }
+    }
+}
+
+fn draw_staff(elements: &[StaffElement]) -> Image {
+    let staff = Staff::new(WIDTH as u32, Point::new(0, 0));
+    let mut image = Image::new();
+    staff.draw(&mut image, elements).unwrap();
+    image
+}
+
+fn draw_tab_staff(elements: &[StaffElement]) -> Image {
+    let tab_staff = TabStaff::new(WIDTH as u32, Point::new(0, 0), Tuning::FOUR_STRING_BASS);
+    let mut image = Image::new();
+    tab_staff.draw(&mut image, elements).unwrap();
+    image
+}
+
+#[test]
+fn test_bitmap_survives_pbm() {
+    let examples = examples::Examples::new();
+    let (_, elements) = &examples.staffs()[0];
+    let image = draw_staff(elements);
+
+    let mut text = String::new();
+    image.write_pbm(&mut text).unwrap();
+
+    assert_eq!(Image::from_pbm(&text), Some(image));
+    assert_eq!(Image::from_pbm("P1\n2 1\n01\n"), None);
+}
+
+#[test]
+fn test_examples_match_golden_images() {
+    let examples = examples::Examples::new();
+
+    let mut mismatches = Vec::new();
+    for (name, elements) in examples.staffs() {
+        if !matches_golden(name, &draw_staff(&elements)) {
+            mismatches.push(name.to_string());
+        }
+
+        let tab_name = format!("{name}_tab");
+        if !matches_golden(&tab_name, &draw_tab_staff(&elements)) {
+            mismatches.push(tab_name);
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "examples differ from their golden images: {mismatches:?}"
+    );
+}
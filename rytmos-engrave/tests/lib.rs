@@ -0,0 +1,1205 @@
+use embedded_graphics::{geometry::Point, pixelcolor::BinaryColor};
+use rytmos_engrave::{
+    a, aes, b, bes,
+    bitmap::Bitmap,
+    c, cis, d, dis, e, es, f, fis, g, ges, gis,
+    interval::{self, Direction, Interval, IntervalQuality},
+    layout::Layout,
+    notation::{self, ParseError, ParseErrorKind},
+    passage::Passage,
+    staff::{
+        Accidental, Articulation, Chord, ChordQuality, ChordSymbol, Clef, Duration, Dynamic,
+        EngraveError, Hairpin, Key, Mode, Music, Note, Spacing, Staff, StaffElement, Ticks,
+        TimeSignature,
+    },
+    tab::{FretChoice, TabStaff, Tuning},
+};
+
+#[test]
+fn test_tuplets_have_exact_lengths() {
+    let total = |duration: Duration, count: usize| {
+        (0..count).fold(Ticks(0), |total, _| total + duration.ticks())
+    };
+
+    assert_eq!(total(Duration::QuarterTriplet, 3), Duration::Half.ticks());
+    assert_eq!(total(Duration::EighthTriplet, 3), Duration::Quarter.ticks());
+    assert_eq!(
+        total(Duration::SixteenthTriplet, 3),
+        Duration::Eighth.ticks()
+    );
+    assert_eq!(
+        total(Duration::SixteenthQuintuplet, 5),
+        Duration::Quarter.ticks()
+    );
+
+    assert_eq!(
+        Duration::EighthTriplet.tuplet(),
+        Some((3, Duration::Quarter.ticks()))
+    );
+    assert_eq!(
+        Duration::SixteenthQuintuplet.tuplet(),
+        Some((5, Duration::Quarter.ticks()))
+    );
+    assert_eq!(Duration::Eighth.tuplet(), None);
+    assert_eq!(Duration::EighthTriplet.written(), Duration::Eighth);
+    assert_eq!(
+        Duration::from_ticks(Ticks(Ticks::PER_QUARTER / 3)),
+        Some(Duration::EighthTriplet)
+    );
+
+    // The note after a triplet starts on the beat, and the triplet has its number drawn
+    let triplets = [
+        Music::Note(c!(3), Duration::EighthTriplet),
+        Music::Note(d!(3), Duration::EighthTriplet),
+        Music::Note(e!(3), Duration::EighthTriplet),
+        Music::Note(f!(3), Duration::Quarter),
+    ];
+    let eighths = triplets.map(|music| match music {
+        Music::Note(note, Duration::EighthTriplet) => Music::Note(note, Duration::Eighth),
+        music => music,
+    });
+    let staff = Staff::new(128, Point::new(0, 0));
+    let draw = |music: &[Music]| {
+        let mut image = Bitmap::<128, 64>::new();
+        let layout = staff
+            .draw(
+                &mut image,
+                &[StaffElement::Clef(Clef::Bass), StaffElement::Music(music)],
+            )
+            .unwrap();
+        let drawn = (0..64)
+            .flat_map(|y| (0..128).map(move |x| Point::new(x, y)))
+            .filter(|&point| image.pixel(point) == Some(BinaryColor::On))
+            .count();
+        (layout, drawn)
+    };
+
+    let (layout, triplets_drawn) = draw(&triplets);
+    let starts: Vec<Ticks> = layout.symbols().iter().map(|symbol| symbol.start).collect();
+    assert_eq!(starts, [Ticks(0), Ticks(160), Ticks(320), Ticks(480)]);
+    assert_eq!(layout.length(), Ticks(960));
+
+    let (_, eighths_drawn) = draw(&eighths);
+    assert!(triplets_drawn > eighths_drawn);
+}
+
+#[test]
+fn test_passage_ties_notes_over_barlines() {
+    let music = [
+        Music::Note(c!(3), Duration::Half),
+        Music::Note(d!(3), Duration::DottedQuarter),
+        Music::Rest(Duration::Half),
+        Music::Note(a!(3), Duration::Whole),
+    ];
+
+    let passage = Passage::new(
+        &music,
+        Clef::Bass,
+        Key::CMajor,
+        TimeSignature::new(3, 4).unwrap(),
+    )
+    .unwrap();
+    let measures: Vec<&[Music]> = passage.measures().collect();
+
+    let expected: [&[Music]; 4] = [
+        &[
+            Music::Note(c!(3), Duration::Half),
+            Music::Note(d!(3), Duration::Quarter),
+            Music::Tie,
+        ],
+        &[
+            Music::Tie,
+            Music::Note(d!(3), Duration::Eighth),
+            Music::Rest(Duration::Half),
+            Music::Note(a!(3), Duration::Eighth),
+            Music::Tie,
+        ],
+        &[
+            Music::Tie,
+            Music::Note(a!(3), Duration::DottedHalf),
+            Music::Tie,
+        ],
+        &[Music::Tie, Music::Note(a!(3), Duration::Eighth)],
+    ];
+
+    assert_eq!(measures, expected);
+}
+
+#[test]
+fn test_passage_ties_chords_over_barlines() {
+    let chord = Chord::new(&[c!(3), e!(3), g!(3)]).unwrap();
+    let music = [
+        Music::Note(d!(3), Duration::DottedHalf),
+        Music::Chord(chord, Duration::DottedHalf),
+    ];
+
+    let passage = Passage::new(
+        &music,
+        Clef::Bass,
+        Key::CMajor,
+        TimeSignature::new(4, 4).unwrap(),
+    )
+    .unwrap();
+    let measures: Vec<&[Music]> = passage.measures().collect();
+
+    let expected: [&[Music]; 2] = [
+        &[
+            Music::Note(d!(3), Duration::DottedHalf),
+            Music::Chord(chord, Duration::Quarter),
+            Music::Tie,
+        ],
+        &[Music::Tie, Music::Chord(chord, Duration::Half)],
+    ];
+
+    assert_eq!(measures, expected);
+}
+
+#[test]
+fn test_passage_does_not_split_tuplets_over_barlines() {
+    let passage = |music: &[Music]| {
+        Passage::new(
+            music,
+            Clef::Bass,
+            Key::CMajor,
+            TimeSignature::new(4, 4).unwrap(),
+        )
+        .map(|passage| passage.measures().count())
+    };
+
+    let triplets = [Music::Note(c!(3), Duration::EighthTriplet); 3];
+
+    let mut music = vec![Music::Rest(Duration::DottedHalf)];
+    music.extend(triplets);
+    music.extend(triplets);
+    assert!(matches!(passage(&music), Ok(2)));
+
+    let mut music = vec![
+        Music::Rest(Duration::DottedHalf),
+        Music::Rest(Duration::Eighth),
+    ];
+    music.extend(triplets);
+    assert!(matches!(
+        passage(&music),
+        Err(EngraveError::DurationNotRepresentable)
+    ));
+}
+
+#[test]
+fn test_layout_finds_symbol_at_time() {
+    let music = [
+        Music::Note(c!(3), Duration::Half),
+        Music::Rest(Duration::Quarter),
+        Music::Note(d!(3), Duration::Quarter),
+    ];
+
+    let staff = Staff::new(128, Point::new(0, 0));
+    let layout = staff.layout(&[StaffElement::Clef(Clef::Bass), StaffElement::Music(&music)]);
+
+    let starts: Vec<Ticks> = layout.symbols().iter().map(|symbol| symbol.start).collect();
+    assert_eq!(starts, [Ticks(0), Ticks(960), Ticks(1440)]);
+    assert_eq!(layout.length(), Ticks(1920));
+
+    let rest = layout.symbol_at(Ticks::from_sixteenths(9)).unwrap();
+    assert!(rest.rest);
+
+    let boxes: Vec<i32> = layout
+        .symbols()
+        .iter()
+        .map(|symbol| symbol.bounding_box.top_left.x)
+        .collect();
+    assert!(boxes.windows(2).all(|pair| pair[0] < pair[1]));
+
+    assert!(layout.symbol_at(Ticks(1920)).is_none());
+}
+
+#[test]
+fn test_layout_hits_symbol_and_pitch_under_point() {
+    let music = [
+        Music::Note(c!(3), Duration::Half),
+        Music::Rest(Duration::Quarter),
+        Music::Note(d!(3), Duration::Quarter),
+    ];
+
+    let staff = Staff::new(128, Point::new(0, 0));
+    let layout = staff.layout(&[
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::KeySignature(Key::GMajor),
+        StaffElement::Music(&music),
+    ]);
+
+    let rest = layout.symbols()[1].bounding_box;
+    // The top staff line is an A, the line below it an F which is sharp in G major
+    let hit = layout.hit(Point::new(rest.center().x, 20)).unwrap();
+    assert_eq!(
+        (hit.index, hit.start, hit.pitch),
+        (1, Ticks(960), Some(a!(3)))
+    );
+    let hit = layout.hit(Point::new(rest.center().x, 24)).unwrap();
+    assert_eq!(hit.pitch, Some(fis!(3)));
+
+    // Beside the last note, and below the staff
+    let last = layout.symbols()[2].bounding_box;
+    let hit = layout.hit(Point::new(last.top_left.x + 20, 44)).unwrap();
+    assert_eq!((hit.index, hit.pitch), (2, Some(c!(2))));
+
+    assert_eq!(layout.hit(Point::new(-1, 28)), None);
+    assert_eq!(layout.hit(Point::new(128, 28)), None);
+}
+
+#[test]
+fn test_staff_with_capacity_lays_out_long_runs() {
+    let music = [Music::Note(c!(3), Duration::Sixteenth); 24];
+    let elements = [StaffElement::Clef(Clef::Bass), StaffElement::Music(&music)];
+
+    let staff = Staff::<32, 8>::with_capacity(256, Point::new(0, 0));
+    let layout = staff.layout(&elements);
+    assert_eq!(layout.symbols().len(), 24);
+    assert_eq!(layout.length(), Ticks::from_sixteenths(24));
+}
+
+/// Thirty-second notes up and down three octaves, over more staff positions than the default
+/// capacity.
+fn scale_run(count: usize) -> Vec<Music> {
+    let scale: Vec<Note> = (1..4)
+        .flat_map(|octave| {
+            [
+                c!(octave),
+                d!(octave),
+                e!(octave),
+                f!(octave),
+                g!(octave),
+                a!(octave),
+                b!(octave),
+            ]
+        })
+        .collect();
+
+    scale
+        .iter()
+        .chain(scale.iter().rev())
+        .cycle()
+        .take(count)
+        .map(|&note| Music::Note(note, Duration::ThirtySecond))
+        .collect()
+}
+
+#[test]
+fn test_staff_with_capacity_lays_out_wide_runs() {
+    let music = scale_run(32);
+    let elements = [StaffElement::Clef(Clef::Bass), StaffElement::Music(&music)];
+
+    let staff = Staff::<32, 8>::with_capacity(256, Point::new(0, 0));
+    let layout = staff.layout(&elements);
+    assert_eq!(layout.symbols().len(), 32);
+
+    // Music that doesn't fit is left out, but keeps its time
+    let layout = Staff::new(256, Point::new(0, 0)).layout(&elements);
+    assert_eq!(layout.symbols().len(), 0);
+    assert_eq!(layout.length(), Ticks::from_sixteenths(16));
+}
+
+#[test]
+fn test_passage_new_in_holds_full_measures() {
+    let mut music = vec![Music::Articulation(Articulation::Accent)];
+    music.extend(scale_run(32));
+
+    assert!(Passage::new(
+        &music,
+        Clef::Bass,
+        Key::CMajor,
+        TimeSignature::new(4, 4).unwrap()
+    )
+    .is_err());
+
+    let passage = Passage::<64>::new_in(
+        &music,
+        Clef::Bass,
+        Key::CMajor,
+        TimeSignature::new(4, 4).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(passage.measures().count(), 1);
+
+    let staff = Staff::<32, 8>::with_capacity(256, Point::new(0, 0));
+    let mut image = Bitmap::<256, 64>::new();
+    let layout = passage.draw_system(&mut image, &staff, 0).unwrap();
+    assert_eq!(layout.symbols().len(), 32);
+}
+
+#[test]
+fn test_key_signatures_are_drawn_in_their_width() {
+    let keys = [
+        Key::CMajor,
+        Key::GMajor,
+        Key::DMajor,
+        Key::AMajor,
+        Key::EMajor,
+        Key::BMajor,
+        Key::FsMajor,
+        Key::FMajor,
+        Key::BbMajor,
+        Key::EbMajor,
+        Key::AbMajor,
+        Key::DbMajor,
+        Key::GbMajor,
+    ];
+
+    for clef in [Clef::Bass, Clef::Treble, Clef::Alto, Clef::Tenor] {
+        for key in keys {
+            let mut image = Bitmap::<64, 64>::new();
+            let width = key.draw(&mut image, Point::new(0, 0), clef).unwrap();
+            assert_eq!(width, key.width(), "{key:?} in {clef:?}");
+
+            let drawn: Vec<Point> = (0..64)
+                .flat_map(|y| (0..64).map(move |x| Point::new(x, y)))
+                .filter(|&point| image.pixel(point) == Some(BinaryColor::On))
+                .collect();
+            assert_eq!(drawn.is_empty(), key == Key::CMajor, "{key:?} in {clef:?}");
+            // Within the width, and on the staff between the ledger margins
+            assert!(
+                drawn
+                    .iter()
+                    .all(|point| point.x < width as i32 && (14..42).contains(&point.y)),
+                "{key:?} in {clef:?}"
+            );
+        }
+    }
+
+    // The music starts after the key signature
+    let music = [Music::Note(c!(3), Duration::Whole)];
+    let start = |key| {
+        Staff::new(128, Point::new(0, 0))
+            .layout(&[
+                StaffElement::Clef(Clef::Bass),
+                StaffElement::KeySignature(key),
+                StaffElement::Music(&music),
+            ])
+            .symbols()[0]
+            .bounding_box
+            .top_left
+            .x
+    };
+    assert!(start(Key::CMajor) < start(Key::FMajor));
+    assert!(start(Key::FMajor) < start(Key::BbMajor));
+    assert_eq!(
+        start(Key::GMajor) - start(Key::CMajor),
+        Key::GMajor.width() as i32
+    );
+}
+
+#[test]
+fn test_clefs_place_notes_by_their_lines() {
+    let layout = |clef, music: &[Music]| {
+        Staff::new(128, Point::new(0, 0))
+            .layout(&[StaffElement::Clef(clef), StaffElement::Music(music)])
+    };
+
+    // The note on the middle line, the note below it has its stem up instead of down
+    let middle_lines = [
+        (Clef::Bass, d!(3), c!(3)),
+        (Clef::Treble, b!(4), a!(4)),
+        (Clef::Alto, c!(4), b!(3)),
+        (Clef::Tenor, a!(3), g!(3)),
+    ];
+    let mut boxes = Vec::new();
+    for (clef, middle, below) in middle_lines {
+        let music = [
+            Music::Note(middle, Duration::Half),
+            Music::Note(below, Duration::Half),
+        ];
+        let layout = layout(clef, &music);
+        let [middle_box, below_box] = [0, 1].map(|index| layout.symbols()[index].bounding_box);
+
+        let hit = layout.hit(Point::new(middle_box.center().x, 28)).unwrap();
+        assert_eq!(hit.pitch, Some(middle), "{clef:?}");
+        // Stem down below the middle line, and up above the note below it
+        assert!(middle_box.top_left.y > below_box.top_left.y, "{clef:?}");
+        assert!(
+            middle_box.bottom_right().unwrap().y > below_box.bottom_right().unwrap().y,
+            "{clef:?}"
+        );
+
+        boxes.push((middle_box.top_left.y, middle_box.size));
+    }
+    assert!(boxes.windows(2).all(|pair| pair[0] == pair[1]));
+}
+
+#[test]
+fn test_voices_have_opposite_stems_and_align_by_beat() {
+    let upper = [
+        Music::Note(e!(3), Duration::Quarter),
+        Music::Note(f!(3), Duration::Quarter),
+        Music::Rest(Duration::Half),
+    ];
+    let lower = [
+        Music::Rest(Duration::Half),
+        Music::Note(c!(3), Duration::Half),
+    ];
+    let staff = Staff::new(128, Point::new(0, 0));
+    let layout = staff.layout(&[
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::Voices(&upper, &lower),
+    ]);
+
+    let starts: Vec<u32> = layout
+        .symbols()
+        .iter()
+        .map(|symbol| symbol.start.sixteenths())
+        .collect();
+    assert_eq!(starts, [0, 4, 8, 0, 8]);
+    assert_eq!(layout.length(), Duration::Whole.ticks());
+
+    let boxes: Vec<_> = layout
+        .symbols()
+        .iter()
+        .map(|symbol| symbol.bounding_box)
+        .collect();
+    // Symbols that start together are in the same column
+    assert_eq!(boxes[0].top_left.x, boxes[3].top_left.x);
+    assert_eq!(boxes[2].top_left.x, boxes[4].top_left.x);
+
+    // Stems of the upper voice go up above the staff, those of the lower voice down below it
+    assert!(boxes[0].top_left.y < 20 && boxes[1].top_left.y < 20);
+    assert!(boxes[4].bottom_right().unwrap().y > 36);
+    // The rest of the upper voice is above the rest of the lower voice
+    assert!(boxes[2].top_left.y < boxes[3].top_left.y);
+}
+
+/// Whether every note in the layout is drawn with an accidental, seen from the width of its box,
+/// expects at least one note without an accidental.
+fn accidentals_drawn(layout: &Layout) -> Vec<bool> {
+    let widths: Vec<u32> = layout
+        .symbols()
+        .iter()
+        .map(|symbol| symbol.bounding_box.size.width)
+        .collect();
+    let narrowest = widths.iter().copied().min().unwrap_or_default();
+
+    widths.iter().map(|&width| width > narrowest).collect()
+}
+
+#[test]
+fn test_accidentals_stay_in_force_until_the_barline() {
+    let first = [
+        Music::Note(fis!(3), Duration::Quarter),
+        Music::Note(fis!(3), Duration::Quarter),
+        Music::Note(f!(3), Duration::Quarter),
+        Music::Note(f!(3), Duration::Quarter),
+    ];
+    let second = [
+        Music::Note(fis!(3), Duration::Quarter),
+        Music::Note(c!(3), Duration::Quarter),
+        Music::Note(fis!(3), Duration::Half),
+    ];
+    let elements = [
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::Music(&first),
+        StaffElement::Barline,
+        StaffElement::Music(&second),
+    ];
+
+    let layout = Staff::new(128, Point::new(0, 0)).layout(&elements);
+    assert_eq!(
+        accidentals_drawn(&layout),
+        [true, false, true, false, true, false, false]
+    );
+
+    // The key signature is in force in every measure
+    let elements = [
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::KeySignature(Key::GMajor),
+        StaffElement::Music(&first),
+    ];
+    let layout = Staff::new(128, Point::new(0, 0)).layout(&elements);
+    assert_eq!(accidentals_drawn(&layout), [false, false, true, false]);
+}
+
+#[test]
+fn test_accidentals_differ_from_the_key_signature() {
+    let music = [
+        Music::Note(bes!(2), Duration::Eighth),
+        Music::Note(b!(2), Duration::Eighth),
+        Music::Note(Note::F(Accidental::DoubleSharp, 2), Duration::Eighth),
+        Music::Note(Note::E(Accidental::DoubleFlat, 3), Duration::Eighth),
+        Music::Note(c!(3), Duration::Half),
+    ];
+    let layout = |key| {
+        Staff::new(128, Point::new(0, 0)).layout(&[
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::KeySignature(key),
+            StaffElement::Music(&music),
+        ])
+    };
+
+    // The B flat is in the key signature of F major, the B after it needs a natural in both
+    assert_eq!(
+        accidentals_drawn(&layout(Key::FMajor)),
+        [false, true, true, true, false]
+    );
+    assert_eq!(
+        accidentals_drawn(&layout(Key::CMajor)),
+        [true, true, true, true, false]
+    );
+
+    // Space is reserved for the accidentals, the symbols do not overlap
+    let boxes: Vec<_> = layout(Key::FMajor)
+        .symbols()
+        .iter()
+        .map(|symbol| symbol.bounding_box)
+        .collect();
+    assert!(boxes
+        .windows(2)
+        .all(|pair| pair[0].bottom_right().unwrap().x < pair[1].top_left.x));
+}
+
+#[test]
+fn test_courtesy_accidentals_follow_altered_measures() {
+    let first = [
+        Music::Note(c!(3), Duration::Half),
+        Music::Note(fis!(3), Duration::Half),
+    ];
+    let second = [
+        Music::Note(c!(3), Duration::Quarter),
+        Music::Note(f!(3), Duration::Quarter),
+        Music::Note(f!(3), Duration::Half),
+    ];
+    let third = [Music::Note(f!(3), Duration::Whole)];
+    let elements = [
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::Music(&first),
+        StaffElement::Barline,
+        StaffElement::Music(&second),
+        StaffElement::Barline,
+        StaffElement::Music(&third),
+    ];
+
+    // A natural after the F sharp of the measure before, only on the first F
+    let layout = Staff::new(128, Point::new(0, 0)).layout(&elements);
+    assert_eq!(
+        accidentals_drawn(&layout),
+        [false, true, false, true, false, false]
+    );
+
+    // No courtesy accidental in the measure after the next one, or in another octave
+    let other_octave = [Music::Note(f!(2), Duration::Whole)];
+    let elements = [
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::Music(&first),
+        StaffElement::Barline,
+        StaffElement::Music(&other_octave),
+    ];
+    let layout = Staff::new(128, Point::new(0, 0)).layout(&elements);
+    assert_eq!(accidentals_drawn(&layout), [false, true, false]);
+}
+
+#[test]
+fn test_tied_notes_do_not_put_accidentals_in_force() {
+    let first = [Music::Note(fis!(3), Duration::Half), Music::Tie];
+    let second = [
+        Music::Tie,
+        Music::Note(fis!(3), Duration::Quarter),
+        Music::Note(fis!(3), Duration::Quarter),
+        Music::Note(fis!(3), Duration::Quarter),
+    ];
+    let elements = [
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::Music(&first),
+        StaffElement::Barline,
+        StaffElement::Music(&second),
+    ];
+
+    let layout = Staff::new(128, Point::new(0, 0)).layout(&elements);
+    assert_eq!(accidentals_drawn(&layout), [true, false, true, false]);
+}
+
+#[test]
+fn test_proportional_spacing_scales_with_duration() {
+    let music = [
+        Music::Note(c!(3), Duration::Half),
+        Music::Note(d!(3), Duration::Quarter),
+        Music::Note(e!(3), Duration::Eighth),
+        Music::Note(e!(3), Duration::Eighth),
+    ];
+    let elements = [StaffElement::Clef(Clef::Bass), StaffElement::Music(&music)];
+
+    let spaces = |spacing| {
+        let layout = Staff::new(128, Point::new(0, 0))
+            .with_spacing(spacing)
+            .layout(&elements);
+        let x: Vec<i32> = layout
+            .symbols()
+            .iter()
+            .map(|symbol| symbol.bounding_box.top_left.x)
+            .collect();
+        x.windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect::<Vec<i32>>()
+    };
+
+    let even = spaces(Spacing::Even);
+    assert!(even.iter().all(|space| (space - even[0]).abs() <= 1));
+
+    let proportional = spaces(Spacing::Proportional);
+    assert!((proportional[0] - 2 * proportional[1]).abs() <= 1);
+    assert!((proportional[1] - 2 * proportional[2]).abs() <= 1);
+}
+
+#[test]
+fn test_passage_splits_into_dotted_durations() {
+    let music = [
+        Music::Note(c!(3), Duration::DottedSixteenth),
+        Music::Note(d!(3), Duration::DottedWhole),
+        Music::Note(e!(3), Duration::ThirtySecond),
+        Music::Rest(Duration::DoubleDottedHalf),
+    ];
+
+    let passage = Passage::new(
+        &music,
+        Clef::Bass,
+        Key::CMajor,
+        TimeSignature::new(4, 4).unwrap(),
+    )
+    .unwrap();
+    let measures: Vec<&[Music]> = passage.measures().collect();
+
+    let expected: [&[Music]; 3] = [
+        &[
+            Music::Note(c!(3), Duration::DottedSixteenth),
+            Music::Note(d!(3), Duration::DoubleDottedHalf),
+            Music::Tie,
+            Music::Note(d!(3), Duration::ThirtySecond),
+            Music::Tie,
+        ],
+        &[
+            Music::Tie,
+            Music::Note(d!(3), Duration::Half),
+            Music::Tie,
+            Music::Note(d!(3), Duration::DottedSixteenth),
+            Music::Note(e!(3), Duration::ThirtySecond),
+            Music::Rest(Duration::DottedQuarter),
+        ],
+        &[Music::Rest(Duration::Half)],
+    ];
+
+    assert_eq!(measures, expected);
+}
+
+#[test]
+fn test_passage_keeps_articulations_with_their_note() {
+    let music = [
+        Music::Note(c!(3), Duration::DottedHalf),
+        Music::Slur,
+        Music::Articulation(Articulation::Accent),
+        Music::Note(d!(3), Duration::Quarter),
+    ];
+
+    let passage = Passage::new(
+        &music,
+        Clef::Bass,
+        Key::CMajor,
+        TimeSignature::new(3, 4).unwrap(),
+    )
+    .unwrap();
+    let measures: Vec<&[Music]> = passage.measures().collect();
+
+    let expected: [&[Music]; 2] = [
+        &[Music::Note(c!(3), Duration::DottedHalf), Music::Slur],
+        &[
+            Music::Slur,
+            Music::Articulation(Articulation::Accent),
+            Music::Note(d!(3), Duration::Quarter),
+        ],
+    ];
+
+    assert_eq!(measures, expected);
+}
+
+#[test]
+fn test_passage_keeps_dynamics_with_their_note() {
+    let music = [
+        Music::Dynamic(Dynamic::Piano),
+        Music::Hairpin(Hairpin::Crescendo),
+        Music::Note(c!(3), Duration::Whole),
+        Music::Dynamic(Dynamic::Forte),
+        Music::Note(d!(3), Duration::Whole),
+    ];
+
+    let passage = Passage::new(
+        &music,
+        Clef::Bass,
+        Key::CMajor,
+        TimeSignature::new(4, 4).unwrap(),
+    )
+    .unwrap();
+    let measures: Vec<&[Music]> = passage.measures().collect();
+
+    let expected: [&[Music]; 2] = [
+        &[
+            Music::Dynamic(Dynamic::Piano),
+            Music::Hairpin(Hairpin::Crescendo),
+            Music::Note(c!(3), Duration::Whole),
+        ],
+        &[
+            Music::Dynamic(Dynamic::Forte),
+            Music::Note(d!(3), Duration::Whole),
+        ],
+    ];
+
+    assert_eq!(measures, expected);
+}
+
+#[test]
+fn test_chord_symbols_are_spelled() {
+    let spelled = |chord_symbol: ChordSymbol| std::format!("{chord_symbol}");
+
+    assert_eq!(
+        spelled(ChordSymbol::new(a!(2), ChordQuality::Minor7)),
+        "Am7"
+    );
+    assert_eq!(
+        spelled(ChordSymbol::new(c!(2), ChordQuality::Major).over(e!(1))),
+        "C/E"
+    );
+    assert_eq!(
+        spelled(ChordSymbol::new(fis!(2), ChordQuality::HalfDiminished7)),
+        "F#m7b5"
+    );
+    assert_eq!(
+        spelled(ChordSymbol::new(bes!(1), ChordQuality::Dominant7).over(d!(2))),
+        "Bb7/D"
+    );
+}
+
+#[test]
+fn test_counting_does_not_change_layout() {
+    let music = [
+        Music::ChordSymbol(ChordSymbol::new(g!(2), ChordQuality::Major)),
+        Music::Note(g!(2), Duration::Quarter),
+        Music::Note(b!(2), Duration::Eighth),
+        Music::Note(d!(3), Duration::Eighth),
+        Music::Rest(Duration::Half),
+    ];
+    let staff = Staff::new(128, Point::new(0, 0));
+
+    let counted = staff.layout(&[
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::Counting(true),
+        StaffElement::Music(&music),
+    ]);
+    let uncounted = staff.layout(&[StaffElement::Clef(Clef::Bass), StaffElement::Music(&music)]);
+
+    assert_eq!(counted.symbols(), uncounted.symbols());
+    assert_eq!(counted.symbols().len(), 4);
+
+    // The syllables are drawn below the staff and its stems
+    let below_staff = |elements: &[StaffElement]| {
+        let mut image = Bitmap::<128, 64>::new();
+        staff.draw(&mut image, elements).unwrap();
+        (44..64)
+            .flat_map(|y| (0..128).map(move |x| Point::new(x, y)))
+            .filter(|&point| image.pixel(point) == Some(BinaryColor::On))
+            .count()
+    };
+    assert_eq!(
+        below_staff(&[StaffElement::Clef(Clef::Bass), StaffElement::Music(&music)]),
+        0
+    );
+    assert!(
+        below_staff(&[
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Counting(true),
+            StaffElement::Music(&music),
+        ]) > 0
+    );
+}
+
+#[test]
+fn test_intervals_keep_spelling() {
+    assert_eq!(c!(3) + Interval::MAJOR_THIRD, Some(e!(3)));
+    assert_eq!(a!(2) + Interval::MINOR_THIRD, Some(c!(3)));
+    assert_eq!(e!(3) - Interval::PERFECT_FIFTH, Some(a!(2)));
+    assert_eq!(
+        b!(2) + Interval::AUGMENTED_FOURTH,
+        Some(Note::E(Accidental::Sharp, 3))
+    );
+    assert_eq!(
+        c!(3) + Interval::new(IntervalQuality::Diminished, 4).unwrap(),
+        Some(Note::F(Accidental::Flat, 3))
+    );
+    assert_eq!(
+        c!(3) + Interval::new(IntervalQuality::Major, 10).unwrap(),
+        Some(e!(4))
+    );
+
+    assert_eq!(Interval::between(a!(2), c!(3)), Some(Interval::MINOR_THIRD));
+    assert_eq!(
+        Interval::between(c!(3), fis!(3)),
+        Some(Interval::AUGMENTED_FOURTH)
+    );
+    assert_eq!(
+        Interval::between(c!(3), c!(4)),
+        Some(Interval::PERFECT_OCTAVE)
+    );
+    assert_eq!(Interval::between(c!(3), a!(2)), None);
+    assert_eq!(Interval::new(IntervalQuality::Major, 5), None);
+    assert_eq!(Interval::from_semitones(15).number(), 10);
+}
+
+#[test]
+fn test_transpose_music_and_key() {
+    let mut music = [
+        Music::ChordSymbol(ChordSymbol::new(d!(2), ChordQuality::Dominant7).over(fis!(2))),
+        Music::Note(fis!(2), Duration::Quarter),
+        Music::Rest(Duration::Quarter),
+        Music::Chord(Chord::new(&[d!(3), a!(3)]).unwrap(), Duration::Half),
+    ];
+    let mut key = Key::DMajor;
+
+    interval::transpose(&mut music, &mut key, Interval::MINOR_THIRD, Direction::Down).unwrap();
+
+    assert_eq!(key, Key::BMajor);
+    assert_eq!(
+        music,
+        [
+            Music::ChordSymbol(ChordSymbol::new(b!(1), ChordQuality::Dominant7).over(dis!(2))),
+            Music::Note(dis!(2), Duration::Quarter),
+            Music::Rest(Duration::Quarter),
+            Music::Chord(Chord::new(&[b!(2), fis!(3)]).unwrap(), Duration::Half),
+        ]
+    );
+
+    // B# major would have more than six sharps
+    let unchanged = music;
+    assert_eq!(
+        interval::transpose(
+            &mut music,
+            &mut key,
+            Interval::AUGMENTED_UNISON,
+            Direction::Up
+        ),
+        None
+    );
+    assert_eq!((music, key), (unchanged, Key::BMajor));
+    assert_eq!(
+        Key::EbMajor.transpose(Interval::MAJOR_SECOND, Direction::Up),
+        Some(Key::FMajor)
+    );
+    assert_eq!(es!(3) + Interval::MAJOR_SECOND, Some(f!(3)));
+}
+
+#[test]
+fn test_midi_is_spelled_in_key() {
+    let spell = |key| {
+        (60..72)
+            .map(|code| Note::from_midi(code, key, None))
+            .collect::<Vec<Note>>()
+    };
+
+    assert_eq!(
+        spell(Key::CMajor),
+        [
+            c!(4),
+            cis!(4),
+            d!(4),
+            es!(4),
+            e!(4),
+            f!(4),
+            fis!(4),
+            g!(4),
+            gis!(4),
+            a!(4),
+            bes!(4),
+            b!(4)
+        ]
+    );
+    assert_eq!(spell(Key::AMinor)[3], dis!(4));
+    assert_eq!(spell(Key::GMajor)[6], fis!(4));
+    assert_eq!(spell(Key::FMajor)[10], bes!(4));
+    assert_eq!(spell(Key::GbMajor)[11], Note::C(Accidental::Flat, 5));
+    assert_eq!(spell(Key::FsMajor)[5], Note::E(Accidental::Sharp, 4));
+
+    // Chromatic notes follow the chord they are in
+    let chord = ChordSymbol::new(aes!(3), ChordQuality::Dominant7);
+    assert_eq!(Note::from_midi(66, Key::CMajor, Some(chord)), ges!(4));
+    assert_eq!(Note::from_midi(66, Key::CMajor, None), fis!(4));
+}
+
+#[test]
+fn test_time_signatures_group_beams() {
+    let groups = |beats, beat_value| {
+        TimeSignature::new(beats, beat_value)
+            .unwrap()
+            .beam_groups()
+            .to_vec()
+    };
+
+    assert_eq!(groups(4, 4), [1, 1, 1, 1]);
+    assert_eq!(groups(3, 4), [1, 1, 1]);
+    assert_eq!(groups(2, 2), [1, 1]);
+    assert_eq!(groups(6, 8), [3, 3]);
+    assert_eq!(groups(12, 8), [3, 3, 3, 3]);
+    assert_eq!(groups(7, 8), [2, 2, 3]);
+    assert_eq!(groups(5, 8), [2, 3]);
+    assert_eq!(groups(2, 8), [2]);
+
+    assert_eq!(
+        TimeSignature::new(6, 8).unwrap().measure_length(),
+        Ticks::from_sixteenths(12)
+    );
+    assert_eq!(TimeSignature::new(0, 4), None);
+    assert_eq!(TimeSignature::new(3, 0), None);
+    assert_eq!(TimeSignature::new(3, 6), None);
+}
+
+#[test]
+fn test_time_signatures_come_before_their_beam_groups() {
+    let music = [
+        Music::Rest(Duration::Eighth),
+        Music::Rest(Duration::Eighth),
+        Music::Note(c!(3), Duration::Eighth),
+        Music::Note(d!(3), Duration::Eighth),
+        Music::Note(e!(3), Duration::Eighth),
+        Music::Note(f!(3), Duration::Eighth),
+        Music::Note(g!(3), Duration::Eighth),
+    ];
+    let layout = |time_signature: Option<TimeSignature>| {
+        let mut elements = vec![StaffElement::Clef(Clef::Bass)];
+        elements.extend(time_signature.map(StaffElement::TimeSignature));
+        elements.push(StaffElement::Music(&music));
+        Staff::new(128, Point::new(0, 0)).layout(&elements)
+    };
+    let start = |time_signature| layout(time_signature).symbols()[0].bounding_box.top_left.x;
+
+    // The numerals take space, two digits more than one
+    let seven_eight = TimeSignature::new(7, 8);
+    assert!(start(None) < start(seven_eight));
+    assert!(start(seven_eight) < start(TimeSignature::new(12, 8)));
+
+    // The beam groups of 2 + 2 + 3 eighths stay in the order of the music
+    let layout = layout(seven_eight);
+    let rests: Vec<bool> = layout.symbols().iter().map(|symbol| symbol.rest).collect();
+    assert_eq!(rests, [true, true, false, false, false, false, false]);
+    let starts: Vec<u32> = layout
+        .symbols()
+        .iter()
+        .map(|symbol| symbol.start.0 / Duration::Eighth.ticks().0)
+        .collect();
+    assert_eq!(starts, [0, 1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn test_keys_in_modes() {
+    assert_eq!(Key::new(a!(0), Mode::Minor), Some(Key::AMinor));
+    assert_eq!(Key::new(fis!(3), Mode::Minor), Some(Key::FsMinor));
+    assert_eq!(Key::new(es!(3), Mode::Major), Some(Key::EbMajor));
+    assert_eq!(Key::new(Note::C(Accidental::Flat, 3), Mode::Major), None);
+
+    let dorian = Key::new(d!(3), Mode::Dorian).unwrap();
+    assert_eq!(dorian.signature(), Key::CMajor.signature());
+    assert_eq!(dorian.mode(), Mode::Dorian);
+    assert_eq!(dorian.tonic(3), d!(3));
+    assert_eq!(
+        Key::new(e!(3), Mode::Mixolydian).unwrap().signature(),
+        Key::AMajor.signature()
+    );
+    assert_eq!(Key::BbMinor.tonic(2), bes!(2));
+
+    assert_eq!(
+        Key::EMinor.transpose(Interval::MAJOR_SECOND, Direction::Up),
+        Some(Key::FsMinor)
+    );
+    assert_eq!(
+        dorian.transpose(Interval::PERFECT_FIFTH, Direction::Up),
+        Key::new(a!(3), Mode::Dorian)
+    );
+}
+
+#[test]
+fn test_notation_parses_music() {
+    let music: heapless::Vec<Music, 16> = notation::parse("a,8. b,16 r8 fis8~ fis | es'4").unwrap();
+    assert_eq!(
+        music,
+        [
+            Music::Note(a!(2), Duration::DottedEighth),
+            Music::Note(b!(2), Duration::Sixteenth),
+            Music::Rest(Duration::Eighth),
+            Music::Note(fis!(3), Duration::Eighth),
+            Music::Tie,
+            Music::Note(fis!(3), Duration::Eighth),
+            Music::Note(es!(4), Duration::Quarter),
+        ]
+    );
+
+    // Marks after a note are in front of it
+    let music: heapless::Vec<Music, 16> =
+        notation::parse("\\tuplet 3/2 { <c e g>8-.\\p ^\"C\" d( e) } \\xNote c2").unwrap();
+    assert_eq!(
+        music,
+        [
+            Music::Articulation(Articulation::Staccato),
+            Music::Dynamic(Dynamic::Piano),
+            Music::ChordSymbol(ChordSymbol::new(c!(3), ChordQuality::Major)),
+            Music::Chord(
+                Chord::new(&[c!(3), e!(3), g!(3)]).unwrap(),
+                Duration::EighthTriplet
+            ),
+            Music::Note(d!(3), Duration::EighthTriplet),
+            Music::Slur,
+            Music::Note(e!(3), Duration::EighthTriplet),
+            Music::Articulation(Articulation::Ghost),
+            Music::Note(c!(3), Duration::Half),
+        ]
+    );
+}
+
+#[test]
+fn test_notation_reports_error_positions() {
+    let error = |text: &str| notation::parse::<16>(text).unwrap_err();
+
+    assert_eq!(
+        error("c4 d3"),
+        ParseError {
+            kind: ParseErrorKind::InvalidDuration,
+            span: 4..5
+        }
+    );
+    assert_eq!(error("c4 h4").kind, ParseErrorKind::UnknownName);
+    assert_eq!(error("c4 h4").span, 3..4);
+    assert_eq!(error("c16..").kind, ParseErrorKind::UnsupportedDuration);
+    assert_eq!(error("c4~ r4").span, 2..3);
+    assert_eq!(error("c4( d4").kind, ParseErrorKind::UnclosedSlur);
+    assert_eq!(error("-. c4").kind, ParseErrorKind::MisplacedMark);
+    assert_eq!(error("<c e g a b>4").span, 9..10);
+    assert_eq!(error("\\tuplet 3/2 { c2 }").span, 14..16);
+    assert_eq!(error("c4^\"Cm9\"").kind, ParseErrorKind::InvalidChordSymbol);
+    assert_eq!(notation::parse::<2>("c4 c4 c4").unwrap_err().span, 6..8);
+
+    let text = "c4 d4\ne4 f4 $";
+    assert_eq!(error(text).line_column(text), (2, 7));
+}
+
+#[test]
+fn test_notation_survives_writing() {
+    let text = "a,8.-> b,16 r8\\< fis8~ fis8\\f \\tuplet 3/2 { c'8( d'8 e'8) } \
+        <as ces' es'>4^\"Ab/Eb\" \\xNote bes,,2-- \\tuplet 5/4 { gisis16 ases16 c16 d16 e16 } g1.";
+    let music: heapless::Vec<Music, 32> = notation::parse(text).unwrap();
+
+    let mut written = String::new();
+    notation::write(&mut written, &music).unwrap();
+    assert_eq!(written, text);
+
+    // Durations that are left out are written
+    let music: heapless::Vec<Music, 4> = notation::parse("c8 d e").unwrap();
+    let mut written = String::new();
+    notation::write(&mut written, &music).unwrap();
+    assert_eq!(written, "c8 d8 e8");
+}
+
+#[test]
+fn test_notation_survives_ties_and_slurs_over_barlines() {
+    let music = [
+        Music::Note(c!(3), Duration::Half),
+        Music::Slur,
+        Music::Note(d!(3), Duration::Quarter),
+        Music::Note(e!(3), Duration::Half),
+        Music::Slur,
+        Music::Note(f!(3), Duration::Quarter),
+        Music::Slur,
+        Music::Note(g!(3), Duration::Half),
+    ];
+    let passage = Passage::new(
+        &music,
+        Clef::Bass,
+        Key::CMajor,
+        TimeSignature::new(2, 4).unwrap(),
+    )
+    .unwrap();
+
+    let measures: Vec<&[Music]> = passage.measures().collect();
+    assert_eq!(measures[2].first(), Some(&Music::Tie));
+    assert_eq!(measures[2].last(), Some(&Music::Slur));
+    assert_eq!(measures[3].first(), Some(&Music::Slur));
+
+    for &measure in &measures {
+        let mut written = String::new();
+        notation::write(&mut written, measure).unwrap();
+        let parsed: heapless::Vec<Music, 8> =
+            notation::parse(&written).unwrap_or_else(|error| panic!("{written:?}: {error}"));
+        assert_eq!(parsed, measure, "{written:?}");
+    }
+
+    let mut written = String::new();
+    notation::write(&mut written, measures[2]).unwrap();
+    assert_eq!(written, "~e4( f4)(");
+
+    // Continued from before and after the music, but not between its notes
+    assert_eq!(
+        notation::parse::<4>("(c4) d4~").unwrap(),
+        [
+            Music::Slur,
+            Music::Note(c!(3), Duration::Quarter),
+            Music::Note(d!(3), Duration::Quarter),
+            Music::Tie,
+        ]
+    );
+    assert!(notation::parse::<4>("~").is_err());
+    assert!(notation::parse::<4>("c4( d4").is_err());
+}
+
+#[test]
+fn test_tuning_chooses_frets_by_position() {
+    let tuning = Tuning::FOUR_STRING_BASS;
+
+    assert_eq!(
+        tuning.fret(a!(1), FretChoice::LowestPosition, &[]),
+        Some((1, 0))
+    );
+    assert_eq!(
+        tuning.fret(a!(1), FretChoice::FixedPosition(5), &[]),
+        Some((0, 5))
+    );
+    assert_eq!(
+        tuning.fret(d!(2), FretChoice::LowestPosition, &[2]),
+        Some((1, 5))
+    );
+    assert_eq!(tuning.fret(d!(1), FretChoice::LowestPosition, &[]), None);
+
+    assert_eq!(Tuning::new(&[e!(1), a!(1), d!(2)]), None);
+    assert_eq!(
+        Tuning::new(Tuning::FIVE_STRING_BASS.strings()),
+        Some(Tuning::FIVE_STRING_BASS)
+    );
+}
+
+#[test]
+fn test_tab_staff_lays_out_voices() {
+    let tab = TabStaff::new(128, Point::new(0, 0), Tuning::SIX_STRING_BASS);
+    let upper = [
+        Music::Note(g!(2), Duration::Quarter),
+        Music::Note(a!(2), Duration::Quarter),
+        Music::Note(g!(2), Duration::Half),
+    ];
+    let lower = [
+        Music::Note(e!(1), Duration::Half),
+        Music::Note(a!(1), Duration::Half),
+    ];
+    let elements = [
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::Voices(&upper, &lower),
+    ];
+
+    let layout = tab.layout(&elements);
+    let starts: Vec<u32> = layout
+        .symbols()
+        .iter()
+        .map(|symbol| symbol.start.sixteenths())
+        .collect();
+
+    assert_eq!(starts, vec![0, 4, 8, 0, 8]);
+    assert_eq!(layout.length(), Duration::Whole.ticks());
+
+    // Symbols that start together are in the same column
+    let x = |index: usize| layout.symbols()[index].bounding_box.top_left.x;
+    assert_eq!(x(0), x(3));
+    assert_eq!(x(2), x(4));
+}
@@ -0,0 +1,267 @@
+#![cfg(feature = "std")]
+
+use midly::{
+    num::{u15, u24, u28, u4, u7},
+    Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind,
+};
+use rytmos_engrave::{
+    a, ais, bes, c, d, e, g,
+    midi::{self, MidiError, MidiOptions, Spelling},
+    staff::{Chord, Duration, Key, Music, Ticks, TimeSignature},
+};
+
+/// A file with one track with the events, 96 ticks per quarter.
+fn smf(events: &[(u32, TrackEventKind<'static>)]) -> Vec<u8> {
+    let mut smf = Smf::new(Header::new(
+        Format::SingleTrack,
+        Timing::Metrical(u15::new(96)),
+    ));
+    let mut track: Vec<TrackEvent> = events
+        .iter()
+        .map(|&(delta, kind)| TrackEvent {
+            delta: u28::new(delta),
+            kind,
+        })
+        .collect();
+    track.push(TrackEvent {
+        delta: u28::new(0),
+        kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+    });
+    smf.tracks.push(track);
+
+    let mut bytes = Vec::new();
+    smf.write_std(&mut bytes).unwrap();
+    bytes
+}
+
+fn on(key: u8, vel: u8) -> TrackEventKind<'static> {
+    TrackEventKind::Midi {
+        channel: u4::new(0),
+        message: MidiMessage::NoteOn {
+            key: u7::new(key),
+            vel: u7::new(vel),
+        },
+    }
+}
+
+fn off(key: u8) -> TrackEventKind<'static> {
+    TrackEventKind::Midi {
+        channel: u4::new(0),
+        message: MidiMessage::NoteOff {
+            key: u7::new(key),
+            vel: u7::new(0),
+        },
+    }
+}
+
+#[test]
+fn test_midi_quantizes_to_music() {
+    let bytes = smf(&[
+        (
+            0,
+            TrackEventKind::Meta(MetaMessage::Tempo(u24::new(600_000))),
+        ),
+        (
+            0,
+            TrackEventKind::Meta(MetaMessage::KeySignature(-1, false)),
+        ),
+        (
+            0,
+            TrackEventKind::Meta(MetaMessage::TimeSignature(3, 2, 24, 8)),
+        ),
+        // A quarter note that starts a little late and ends a little early
+        (3, on(48, 80)),
+        (90, off(48)),
+        // A rest of an eighth, then a chord of a dotted quarter
+        (51, on(50, 64)),
+        (0, on(55, 64)),
+        (146, off(50)),
+        (0, on(55, 0)),
+        // A quarter and a sixteenth, tied
+        (0, on(58, 100)),
+        (120, off(58)),
+    ]);
+
+    let import = midi::read(&bytes, MidiOptions::default()).unwrap();
+    assert_eq!(import.key, Some(Key::FMajor));
+    assert_eq!(
+        import.time_signature,
+        Some(TimeSignature::new(3, 4).unwrap())
+    );
+    assert_eq!(import.tempos, vec![(Ticks(0), 600_000)]);
+    assert_eq!(import.notes.len(), 4);
+    assert_eq!(import.notes[0].velocity, 80);
+
+    assert_eq!(
+        import.music(),
+        vec![
+            Music::Note(c!(3), Duration::Quarter),
+            Music::Rest(Duration::Eighth),
+            Music::Chord(
+                Chord::new(&[d!(3), g!(3)]).unwrap(),
+                Duration::DottedQuarter
+            ),
+            Music::Note(bes!(3), Duration::Quarter),
+            Music::Tie,
+            Music::Note(bes!(3), Duration::Sixteenth),
+        ]
+    );
+
+    let eighths = MidiOptions {
+        resolution: Duration::Eighth,
+        ..Default::default()
+    };
+    let import = midi::read(&bytes, eighths).unwrap();
+    assert_eq!(import.notes[3].length, Duration::DottedQuarter.ticks());
+}
+
+#[test]
+fn test_midi_continues_the_longer_notes_of_chords() {
+    let bytes = smf(&[
+        // The G sounds a quarter longer than the C
+        (0, on(48, 64)),
+        (0, on(55, 64)),
+        (96, off(48)),
+        (96, off(55)),
+        (0, on(52, 64)),
+        (96, off(52)),
+        // The G is cut off by the D
+        (0, on(48, 64)),
+        (0, on(55, 64)),
+        (48, off(48)),
+        (48, on(50, 64)),
+        (96, off(55)),
+        (0, off(50)),
+    ]);
+
+    let import = midi::read(&bytes, MidiOptions::default()).unwrap();
+    assert_eq!(
+        import.music(),
+        vec![
+            Music::Chord(Chord::new(&[c!(3), g!(3)]).unwrap(), Duration::Quarter),
+            Music::Tie,
+            Music::Note(g!(3), Duration::Quarter),
+            Music::Note(e!(3), Duration::Quarter),
+            Music::Chord(Chord::new(&[c!(3), g!(3)]).unwrap(), Duration::Eighth),
+            Music::Tie,
+            Music::Note(g!(3), Duration::Eighth),
+            Music::Note(d!(3), Duration::Quarter),
+        ]
+    );
+}
+
+#[test]
+fn test_midi_spells_notes() {
+    let bytes = smf(&[
+        (0, on(58, 64)),
+        (96, off(58)),
+        (0, on(57, 64)),
+        (96, off(57)),
+    ]);
+    let spelled = |spelling| {
+        let options = MidiOptions {
+            spelling,
+            ..Default::default()
+        };
+        let import = midi::read(&bytes, options).unwrap();
+        import
+            .notes
+            .iter()
+            .map(|note| note.note)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(spelled(Spelling::Flats), vec![bes!(3), a!(3)]);
+    assert_eq!(spelled(Spelling::Sharps), vec![ais!(3), a!(3)]);
+    // Without a key signature notes are spelled as in C major
+    assert_eq!(spelled(Spelling::Key), vec![bes!(3), a!(3)]);
+
+    let second_track = MidiOptions {
+        track: 1,
+        ..Default::default()
+    };
+    assert!(matches!(
+        midi::read(&bytes, second_track),
+        Err(MidiError::NoSuchTrack)
+    ));
+    assert!(matches!(
+        midi::read(b"MThd", MidiOptions::default()),
+        Err(MidiError::Midi(_))
+    ));
+}
+
+#[test]
+fn test_midi_quantizes_to_triplets() {
+    let bytes = smf(&[
+        (0, on(48, 64)),
+        (32, off(48)),
+        // Played a little late and short
+        (1, on(50, 64)),
+        (30, off(50)),
+        (1, on(52, 64)),
+        (32, off(52)),
+        // Five triplet eighths long
+        (0, on(55, 64)),
+        (160, off(55)),
+    ]);
+
+    let triplets = MidiOptions {
+        resolution: Duration::EighthTriplet,
+        ..Default::default()
+    };
+    let import = midi::read(&bytes, triplets).unwrap();
+
+    assert_eq!(
+        import.music(),
+        vec![
+            Music::Note(c!(3), Duration::EighthTriplet),
+            Music::Note(d!(3), Duration::EighthTriplet),
+            Music::Note(e!(3), Duration::EighthTriplet),
+            Music::Note(g!(3), Duration::Quarter),
+            Music::Tie,
+            Music::Note(g!(3), Duration::QuarterTriplet),
+        ]
+    );
+}
+
+#[test]
+fn test_midi_rejects_what_does_not_fit() {
+    // A measure without beats is left out, the next time signature is used instead
+    let bytes = smf(&[
+        (
+            0,
+            TrackEventKind::Meta(MetaMessage::TimeSignature(0, 2, 24, 8)),
+        ),
+        (
+            0,
+            TrackEventKind::Meta(MetaMessage::TimeSignature(6, 3, 24, 8)),
+        ),
+        (0, on(48, 64)),
+        (96, off(48)),
+    ]);
+    let import = midi::read(&bytes, MidiOptions::default()).unwrap();
+    assert_eq!(
+        import.time_signature,
+        Some(TimeSignature::new(6, 8).unwrap())
+    );
+
+    let bytes = smf(&[(
+        0,
+        TrackEventKind::Meta(MetaMessage::TimeSignature(0, 2, 24, 8)),
+    )]);
+    let import = midi::read(&bytes, MidiOptions::default()).unwrap();
+    assert_eq!(import.time_signature, None);
+
+    // Notes later than the ticks can count
+    let latest = u28::max_value().as_int();
+    let bytes = smf(&[
+        (latest, on(48, 64)),
+        (latest, off(48)),
+        (latest, on(48, 64)),
+        (latest, off(48)),
+    ]);
+    assert!(matches!(
+        midi::read(&bytes, MidiOptions::default()),
+        Err(MidiError::TooLong)
+    ));
+}
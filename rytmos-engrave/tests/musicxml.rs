@@ -0,0 +1,263 @@
+#![cfg(feature = "std")]
+
+use rytmos_engrave::{
+    a, b, c, d, e, fis, g,
+    musicxml::{self, Element, Unsupported},
+    staff::{
+        Articulation, Chord, ChordQuality, ChordSymbol, Clef, Duration, Dynamic, Hairpin, Key,
+        Music, StaffElement, TimeSignature,
+    },
+};
+
+#[test]
+fn test_musicxml_survives_writing() {
+    let first = [
+        Music::Dynamic(Dynamic::Piano),
+        Music::Hairpin(Hairpin::Crescendo),
+        Music::Note(g!(2), Duration::DottedEighth),
+        Music::Articulation(Articulation::Staccato),
+        Music::Note(a!(2), Duration::Sixteenth),
+        Music::Rest(Duration::Eighth),
+        Music::Note(b!(2), Duration::Eighth),
+        Music::Slur,
+        Music::Note(c!(3), Duration::Eighth),
+        Music::Slur,
+        Music::Note(d!(3), Duration::Eighth),
+        Music::Note(fis!(3), Duration::Quarter),
+        Music::Tie,
+    ];
+    // The tie continues over the barline, like in the measures of a passage
+    let second = [
+        Music::Tie,
+        Music::Note(fis!(3), Duration::EighthTriplet),
+        Music::Dynamic(Dynamic::Forte),
+        Music::Articulation(Articulation::Ghost),
+        Music::Note(e!(3), Duration::EighthTriplet),
+        Music::Note(d!(3), Duration::EighthTriplet),
+        Music::ChordSymbol(ChordSymbol::new(c!(3), ChordQuality::Major7).over(e!(3))),
+        Music::Articulation(Articulation::Accent),
+        Music::Chord(
+            Chord::new(&[c!(3), e!(3), g!(3)]).unwrap(),
+            Duration::DottedHalf,
+        ),
+    ];
+    let elements = [
+        StaffElement::Clef(Clef::Bass),
+        StaffElement::KeySignature(Key::EMinor),
+        StaffElement::TimeSignature(TimeSignature::new(4, 4).unwrap()),
+        StaffElement::Music(&first),
+        StaffElement::Barline,
+        StaffElement::Music(&second),
+    ];
+
+    let xml = musicxml::write(&elements).unwrap();
+    let part = musicxml::read(&xml, None).unwrap();
+
+    assert_eq!(part.unsupported, vec![]);
+    assert_eq!(
+        part.elements,
+        vec![
+            Element::Clef(Clef::Bass),
+            Element::KeySignature(Key::EMinor),
+            Element::TimeSignature(TimeSignature::new(4, 4).unwrap()),
+            Element::Music(first.to_vec()),
+            Element::Barline,
+            Element::Music(second.to_vec()),
+        ]
+    );
+
+    let voices = [StaffElement::Voices(&first, &second)];
+    assert!(musicxml::write(&voices).is_err());
+}
+
+#[test]
+fn test_musicxml_continues_ties_and_slurs_over_barlines() {
+    let xml = r#"<score-partwise version="4.0">
+  <part id="P1">
+    <measure number="1">
+      <attributes><divisions>1</divisions></attributes>
+      <note>
+        <pitch><step>G</step><octave>2</octave></pitch>
+        <duration>1</duration><type>quarter</type>
+      </note>
+      <note>
+        <pitch><step>C</step><octave>3</octave></pitch>
+        <duration>1</duration><tie type="start"/><type>quarter</type>
+        <notations><tied type="start"/></notations>
+      </note>
+    </measure>
+    <measure number="2">
+      <note>
+        <pitch><step>C</step><octave>3</octave></pitch>
+        <duration>1</duration><tie type="stop"/><type>quarter</type>
+        <notations><tied type="stop"/></notations>
+      </note>
+      <note>
+        <pitch><step>D</step><octave>3</octave></pitch>
+        <duration>1</duration><type>quarter</type>
+        <notations><slur type="start"/></notations>
+      </note>
+    </measure>
+    <measure number="3">
+      <note>
+        <pitch><step>E</step><octave>3</octave></pitch>
+        <duration>1</duration><type>quarter</type>
+        <notations><slur type="stop"/></notations>
+      </note>
+      <forward><duration>1</duration></forward>
+      <note>
+        <pitch><step>G</step><octave>3</octave></pitch>
+        <duration>1</duration><type>quarter</type>
+      </note>
+    </measure>
+  </part>
+</score-partwise>"#;
+
+    let part = musicxml::read(xml, None).unwrap();
+    assert_eq!(
+        part.elements,
+        vec![
+            Element::Music(vec![
+                Music::Note(g!(2), Duration::Quarter),
+                Music::Note(c!(3), Duration::Quarter),
+                Music::Tie,
+            ]),
+            Element::Barline,
+            Element::Music(vec![
+                Music::Tie,
+                Music::Note(c!(3), Duration::Quarter),
+                Music::Note(d!(3), Duration::Quarter),
+                Music::Slur,
+            ]),
+            Element::Barline,
+            // The note after moving forward is skipped
+            Element::Music(vec![Music::Slur, Music::Note(e!(3), Duration::Quarter)]),
+        ]
+    );
+    assert_eq!(
+        part.unsupported,
+        vec![Unsupported {
+            measure: "3".to_owned(),
+            element: "forward".to_owned(),
+        }]
+    );
+
+    // Written back, the continued tie and slur are not doubled
+    let elements = part.staff_elements();
+    let written = musicxml::read(&musicxml::write(&elements).unwrap(), None).unwrap();
+    assert_eq!(written.elements, part.elements);
+}
+
+#[test]
+fn test_musicxml_reports_what_it_leaves_out() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<score-partwise version="4.0">
+  <part-list>
+    <score-part id="P1"><part-name>Bass</part-name></score-part>
+    <score-part id="P2"><part-name>Drums</part-name></score-part>
+  </part-list>
+  <part id="P1">
+    <measure number="1">
+      <attributes>
+        <divisions>2</divisions>
+        <key><fifths>1</fifths></key>
+        <time><beats>2</beats><beat-type>4</beat-type></time>
+        <clef><sign>F</sign><line>4</line></clef>
+      </attributes>
+      <direction placement="above">
+        <direction-type><metronome><beat-unit>quarter</beat-unit><per-minute>90</per-minute></metronome></direction-type>
+        <sound tempo="90"/>
+      </direction>
+      <note>
+        <grace/>
+        <pitch><step>F</step><alter>1</alter><octave>2</octave></pitch>
+        <voice>1</voice><type>eighth</type>
+      </note>
+      <note>
+        <pitch><step>G</step><octave>2</octave></pitch>
+        <duration>2</duration><voice>1</voice><type>quarter</type>
+        <notations><articulations><staccatissimo/></articulations></notations>
+      </note>
+      <note>
+        <rest/><duration>2</duration><voice>1</voice><type>quarter</type>
+      </note>
+      <backup><duration>4</duration></backup>
+      <note>
+        <pitch><step>G</step><octave>1</octave></pitch>
+        <duration>4</duration><voice>2</voice><type>half</type>
+      </note>
+    </measure>
+  </part>
+</score-partwise>"#;
+
+    let part = musicxml::read(xml, Some("P1")).unwrap();
+    assert_eq!(
+        part.elements,
+        vec![
+            Element::Clef(Clef::Bass),
+            Element::KeySignature(Key::GMajor),
+            Element::TimeSignature(TimeSignature::new(2, 4).unwrap()),
+            Element::Music(vec![
+                Music::Note(g!(2), Duration::Quarter),
+                Music::Rest(Duration::Quarter),
+            ]),
+        ]
+    );
+
+    let unsupported: Vec<&str> = part
+        .unsupported
+        .iter()
+        .map(|Unsupported { element, .. }| element.as_str())
+        .collect();
+    assert_eq!(
+        unsupported,
+        vec!["metronome", "grace", "staccatissimo", "backup"]
+    );
+
+    assert!(matches!(
+        musicxml::read(xml, Some("P3")),
+        Err(musicxml::MusicXmlError::NoSuchPart)
+    ));
+}
+
+#[test]
+fn test_musicxml_rejects_values_that_overflow() {
+    let note = |pitch: &str, duration: &str| {
+        format!(
+            r#"<score-partwise version="4.0">
+  <part id="P1">
+    <measure number="1">
+      <attributes><divisions>1</divisions></attributes>
+      <note>
+        <pitch>{pitch}</pitch>
+        <duration>{duration}</duration><type>quarter</type>
+      </note>
+    </measure>
+  </part>
+</score-partwise>"#
+        )
+    };
+    let invalid = |xml: &str| match musicxml::read(xml, None) {
+        Err(musicxml::MusicXmlError::Invalid(Unsupported { element, .. })) => element,
+        other => panic!("{other:?}"),
+    };
+
+    let part = musicxml::read(&note("<step>C</step><octave>3</octave>", "1"), None).unwrap();
+    assert_eq!(
+        part.elements,
+        vec![Element::Music(vec![Music::Note(c!(3), Duration::Quarter)])]
+    );
+
+    assert_eq!(
+        invalid(&note("<step>C</step><octave>3</octave>", "4000000000")),
+        "duration"
+    );
+    assert_eq!(
+        invalid(&note("<step>C</step><octave>2147483647</octave>", "1")),
+        "octave"
+    );
+    assert_eq!(
+        invalid(&note("<step>C</step><octave>-400000000</octave>", "1")),
+        "octave"
+    );
+}
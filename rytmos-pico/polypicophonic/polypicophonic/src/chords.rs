@@ -1,5 +1,5 @@
 use heapless::Vec;
-use rytmos_engrave::staff::Note;
+use rytmos_engrave::{interval::Interval, staff::Note};
 use rytmos_synth::commands::CommandMessage;
 
 pub enum ChordQuality {
@@ -53,13 +53,22 @@ pub fn add_chord(
 
 pub fn diatonic_up(root: Note, quality: ChordQuality) -> (Note, Note) {
     let third = match quality {
-        ChordQuality::Major => Note::from_u8_flat(root.to_midi_code() + 4),
-        ChordQuality::Minor => Note::from_u8_flat(root.to_midi_code() + 3),
+        ChordQuality::Major => Interval::MAJOR_THIRD,
+        ChordQuality::Minor => Interval::MINOR_THIRD,
     };
 
-    let fifth = Note::from_u8_flat(root.to_midi_code() + 7);
+    // Above a double sharp a third or fifth can need a triple sharp, spell those by their sound,
+    // and leave notes that have no MIDI code at the root
+    let up = |interval: Interval| {
+        (root + interval).unwrap_or_else(|| {
+            root.to_midi_code()
+                .zip(u8::try_from(interval.semitones()).ok())
+                .and_then(|(code, semitones)| code.checked_add(semitones))
+                .map_or(root, Note::from_u8_flat)
+        })
+    };
 
-    (third, fifth)
+    (up(third), up(Interval::PERFECT_FIFTH))
 }
 
 pub fn invert_to_within_octave(root: Note, quality: ChordQuality) -> (Note, Note) {
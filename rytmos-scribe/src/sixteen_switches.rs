@@ -2,7 +2,7 @@ use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::{PrimitiveStyle, Rectangle, StyledDrawable};
 use heapless::Vec;
-use rytmos_engrave::staff::{Accidental, Duration as Dur};
+use rytmos_engrave::staff::{Accidental, Duration as Dur, Ticks};
 use rytmos_engrave::staff::{Music, Note};
 
 /// Generates Rytmos sheet music based on the states of sixteen tri state switches.
@@ -21,6 +21,7 @@ pub enum ScribeError {
     MoreThanFourBeatsInCurrentBeat(u8),
     MoreThanFourBeatsAfterFirstBeatAfterRender(u8),
     DurationMustBeLessThanFourNow(u8),
+    NoDurationForSixteenths(u8),
 }
 
 /// Encodes the three states of a switch
@@ -134,6 +135,12 @@ impl RhythmDefinition {
         Ok(Self { sixteenths })
     }
 
+    /// The duration of a single note or rest that lasts the given amount of sixteenths.
+    fn duration(sixteenths: u8) -> Result<Dur, ScribeError> {
+        Dur::from_ticks(Ticks::from_sixteenths(sixteenths as u32))
+            .ok_or(ScribeError::NoDurationForSixteenths(sixteenths))
+    }
+
     /// Turns a play definition rhythm into Rytmos music, using the provided notes (or C as a default if they run out)
     /// Uses the fact that a playdefinition is always exactly 1 measure (checked in new)
     pub fn to_music(&self, notes: &Vec<Note, 16>) -> Result<Vec<Music, 16>, ScribeError> {
@@ -158,32 +165,16 @@ impl RhythmDefinition {
 
                     // Catch some special cases first
                     if let (0, 6 | 8 | 12 | 16) = (beat_pos, duration) {
-                        let duration_enum = match duration {
-                            6 => Dur::DottedQuarter,
-                            8 => Dur::Half,
-                            12 => Dur::DottedHalf,
-                            16 => Dur::Whole,
-                            _ => unreachable!(),
-                        };
                         music
-                            .push(note(duration_enum))
+                            .push(note(Self::duration(duration)?))
                             .map_err(|_| ScribeError::VecFull)?;
                         beat_pos = (beat_pos + duration) % 4;
                         continue;
                     }
 
                     match length_in_current_beat {
-                        1 => music
-                            .push(note(Dur::Sixteenth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        2 => music
-                            .push(note(Dur::Eighth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        3 => music
-                            .push(note(Dur::DottedEighth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        4 => music
-                            .push(note(Dur::Quarter))
+                        1..=4 => music
+                            .push(note(Self::duration(length_in_current_beat)?))
                             .map_err(|_| ScribeError::VecFull)?,
                         _ => {
                             return Err(ScribeError::MoreThanFourBeatsInCurrentBeat(
@@ -216,14 +207,8 @@ impl RhythmDefinition {
 
                     match after_first_beat_duration {
                         0 => (),
-                        1 => music
-                            .push(note(Dur::Sixteenth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        2 => music
-                            .push(note(Dur::Eighth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        3 => music
-                            .push(note(Dur::DottedEighth))
+                        1..=3 => music
+                            .push(note(Self::duration(after_first_beat_duration)?))
                             .map_err(|_| ScribeError::VecFull)?,
                         _ => {
                             return Err(ScribeError::DurationMustBeLessThanFourNow(
@@ -240,32 +225,16 @@ impl RhythmDefinition {
 
                     // Catch some special cases first
                     if let (0, 6 | 8 | 12 | 16) = (beat_pos, duration) {
-                        let duration_enum = match duration {
-                            6 => Dur::DottedQuarter,
-                            8 => Dur::Half,
-                            12 => Dur::DottedHalf,
-                            16 => Dur::Whole,
-                            _ => unreachable!(),
-                        };
                         music
-                            .push(rest(duration_enum))
+                            .push(rest(Self::duration(duration)?))
                             .map_err(|_| ScribeError::VecFull)?;
                         beat_pos = (beat_pos + duration) % 4;
                         continue;
                     }
 
                     match length_in_current_beat {
-                        1 => music
-                            .push(rest(Dur::Sixteenth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        2 => music
-                            .push(rest(Dur::Eighth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        3 => music
-                            .push(rest(Dur::DottedEighth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        4 => music
-                            .push(rest(Dur::Quarter))
+                        1..=4 => music
+                            .push(rest(Self::duration(length_in_current_beat)?))
                             .map_err(|_| ScribeError::VecFull)?,
                         _ => {
                             return Err(ScribeError::MoreThanFourBeatsInCurrentBeat(
@@ -291,14 +260,8 @@ impl RhythmDefinition {
 
                     match after_first_beat_duration {
                         0 => (),
-                        1 => music
-                            .push(rest(Dur::Sixteenth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        2 => music
-                            .push(rest(Dur::Eighth))
-                            .map_err(|_| ScribeError::VecFull)?,
-                        3 => music
-                            .push(rest(Dur::DottedEighth))
+                        1..=3 => music
+                            .push(rest(Self::duration(after_first_beat_duration)?))
                             .map_err(|_| ScribeError::VecFull)?,
                         _ => {
                             return Err(ScribeError::DurationMustBeLessThanFourNow(
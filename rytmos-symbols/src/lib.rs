@@ -51,6 +51,44 @@ pub const SIXTEENTH_FLAG: (u32, &[u32]) = (2, &[
     pix!("BBB"),
 ]);
 
+#[rustfmt::skip]
+pub const THIRTY_SECOND_FLAG: (u32, &[u32]) = (2, &[
+    pix!("WBB"),
+    pix!("BWB"),
+    pix!("BWB"),
+    pix!("WBB"),
+    pix!("BWB"),
+    pix!("BWB"),
+    pix!("WBB"),
+    pix!("BWB"),
+    pix!("BWB"),
+    pix!("BBB"),
+]);
+
+#[rustfmt::skip]
+pub const BREVE_REST: (u32, &[u32]) = (7, &[
+    pix!("________"),
+    pix!("________"),
+    pix!("_WWW____"),
+    pix!("_WWW____"),
+    pix!("_WWW____"),
+    pix!("_WWW____"),
+    pix!("_WWW____"),
+    pix!("________"),
+]);
+
+#[rustfmt::skip]
+pub const DOTTED_WHOLE_REST: (u32, &[u32]) = (7, &[
+    pix!("________"),
+    pix!("________"),
+    pix!("________"),
+    pix!("_WWW_W__"),
+    pix!("_WWW____"),
+    pix!("________"),
+    pix!("________"),
+    pix!("________"),
+]);
+
 #[rustfmt::skip]
 pub const WHOLE_REST: (u32, &[u32]) = (7, &[
     pix!("________"),
@@ -87,6 +125,18 @@ pub const DOTTED_HALF_REST: (u32, &[u32]) = (7, &[
     pix!("________"),
 ]);
 
+#[rustfmt::skip]
+pub const DOUBLE_DOTTED_HALF_REST: (u32, &[u32]) = (7, &[
+    pix!("________"),
+    pix!("________"),
+    pix!("________"),
+    pix!("________"),
+    pix!("_WWW_W_W"),
+    pix!("_WWW____"),
+    pix!("________"),
+    pix!("________"),
+]);
+
 #[rustfmt::skip]
 pub const QUARTER_REST: (u32, &[u32]) = (7, &[
     pix!("_W______"),
@@ -111,6 +161,18 @@ pub const DOTTED_QUARTER_REST: (u32, &[u32]) = (7, &[
     pix!("__W_____"),
 ]);
 
+#[rustfmt::skip]
+pub const DOUBLE_DOTTED_QUARTER_REST: (u32, &[u32]) = (7, &[
+    pix!("_W______"),
+    pix!("__W_____"),
+    pix!("BBWWB___"),
+    pix!("_WWW____"),
+    pix!("__W__W_W"),
+    pix!("_WWW____"),
+    pix!("BWBBB___"),
+    pix!("__W_____"),
+]);
+
 #[rustfmt::skip]
 pub const EIGHTH_REST: (u32, &[u32]) = (7, &[
     pix!("_______"),
@@ -137,6 +199,19 @@ pub const DOTTED_EIGHTH_REST: (u32, &[u32]) = (7, &[
     pix!("_BWB___"),
 ]);
 
+#[rustfmt::skip]
+pub const DOUBLE_DOTTED_EIGHTH_REST: (u32, &[u32]) = (9, &[
+    pix!("_________"),
+    pix!("_BB______"),
+    pix!("BWWBBWB__"),
+    pix!("BWWWWB___"),
+    pix!("_BBBWBW_W"),
+    pix!("__BWB____"),
+    pix!("__BWB____"),
+    pix!("_BWB_____"),
+    pix!("_BWB_____"),
+]);
+
 #[rustfmt::skip]
 pub const SIXTEENTH_REST: (u32, &[u32]) = (8, &[
 pix!("________"),
@@ -150,6 +225,33 @@ pix!("___W____"),
 pix!("___W____"),
 ]);
 
+#[rustfmt::skip]
+pub const DOTTED_SIXTEENTH_REST: (u32, &[u32]) = (8, &[
+    pix!("________"),
+    pix!("________"),
+    pix!("_BWWBBWB"),
+    pix!("__WWWW__"),
+    pix!("_WW__W_W"),
+    pix!("_WWWW___"),
+    pix!("BBBBWB__"),
+    pix!("___W____"),
+    pix!("___W____"),
+]);
+
+#[rustfmt::skip]
+pub const THIRTY_SECOND_REST: (u32, &[u32]) = (8, &[
+    pix!("________"),
+    pix!("_BWWBBWB"),
+    pix!("__WWWW__"),
+    pix!("_WW__W__"),
+    pix!("_WWWW___"),
+    pix!("WW__W___"),
+    pix!("WWWW____"),
+    pix!("BBBWB___"),
+    pix!("__W_____"),
+    pix!("__W_____"),
+]);
+
 #[rustfmt::skip]
 pub const BASS_CLEF: (u32, &[u32]) = (12, &[
     pix!("___BBBBB____"), 
@@ -203,6 +305,412 @@ __WW__
 _BBBB_
 );
 
+// Notehead of a ghost note.
+art!(GHOST_NOTEHEAD, 6,
+W____W
+_W__W_
+__WW__
+_W__W_
+W____W
+);
+
+art!(ACCENT, 6,
+WW____
+__WW__
+____WW
+__WW__
+WW____
+);
+
+// Erases the staff lines around the dot.
+art!(STACCATO, 4,
+BBBB
+BWWB
+BWWB
+BBBB
+);
+
+art!(TENUTO, 6, WWWWWW);
+
+// Clef of tablature, on a background that hides the lines behind it.
+art!(TAB_CLEF, 7,
+BBBBBBB
+BWWWWWB
+BBBWBBB
+BBBWBBB
+BBBWBBB
+BBBWBBB
+BBBBBBB
+BBWWWBB
+BWBBBWB
+BWWWWWB
+BWBBBWB
+BWBBBWB
+BBBBBBB
+BWWWWBB
+BWBBBWB
+BWWWWBB
+BWBBBWB
+BWWWWBB
+BBBBBBB
+);
+
+// Letters of dynamics, on a shared baseline below row 4.
+art!(DYNAMIC_P, 4,
+____
+____
+WWW_
+W__W
+WWW_
+W___
+W___
+);
+
+art!(DYNAMIC_M, 5,
+_____
+_____
+WW_W_
+W_W_W
+W_W_W
+_____
+_____
+);
+
+art!(DYNAMIC_F, 4,
+__WW
+_W__
+WWW_
+_W__
+_W__
+_W__
+W___
+);
+
+// An empty notehead between two bars, drawn a pixel up and left of the notehead position.
+art!(BREVE_NOTEHEAD, 8,
+W______W
+W_BBBB_W
+W__WW__W
+WBW__WBW
+W__WW__W
+W_BBBB_W
+W______W
+);
+
+// Accidentals erase the staff lines between their strokes, the vertical center is row 3 for
+// sharps and naturals and row 4 (the middle of the bowl) for flats.
+art!(SHARP, 5,
+_W_W_
+BWBWB
+WWWWW
+BWBWB
+WWWWW
+BWBWB
+_W_W_
+);
+
+art!(FLAT, 4,
+W___
+W___
+W___
+WWW_
+WBBW
+WBW_
+WW__
+);
+
+art!(NATURAL, 3,
+W__
+WBW
+WWW
+WBW
+WWW
+WBW
+__W
+);
+
+// Vertical center is row 2
+art!(DOUBLE_SHARP, 5,
+WWBWW
+BWBWB
+BBWBB
+BWBWB
+WWBWW
+);
+
+// Vertical center is row 4, like the single flat
+art!(DOUBLE_FLAT, 7,
+W__W___
+W__W___
+W__W___
+WWWWWW_
+WBBWBBW
+WBWWBW_
+WW_WW__
+);
+
+// Numerals for time signatures, drawn on a blank background so staff lines do not cross them
+art!(NUMERAL_ZERO, 5,
+BWWWB
+WWBWW
+WWBWW
+WWBWW
+WWBWW
+WWBWW
+BWWWB
+);
+
+art!(NUMERAL_ONE, 5,
+BBWWB
+BWWWB
+BBWWB
+BBWWB
+BBWWB
+BBWWB
+BWWWW
+);
+
+art!(NUMERAL_TWO, 5,
+BWWWB
+WWBWW
+BBBWW
+BBWWB
+BWWBB
+WWBBB
+WWWWW
+);
+
+art!(NUMERAL_THREE, 5,
+WWWWB
+BBBWW
+BBBWW
+BWWWB
+BBBWW
+BBBWW
+WWWWB
+);
+
+art!(NUMERAL_FOUR, 5,
+BBBWW
+BBWWW
+BWBWW
+WBBWW
+WWWWW
+BBBWW
+BBBWW
+);
+
+art!(NUMERAL_FIVE, 5,
+WWWWW
+WWBBB
+WWWWB
+BBBWW
+BBBWW
+WWBWW
+BWWWB
+);
+
+art!(NUMERAL_SIX, 5,
+BWWWB
+WWBBB
+WWWWB
+WWBWW
+WWBWW
+WWBWW
+BWWWB
+);
+
+art!(NUMERAL_SEVEN, 5,
+WWWWW
+BBBWW
+BBBWW
+BBWWB
+BBWWB
+BWWBB
+BWWBB
+);
+
+art!(NUMERAL_EIGHT, 5,
+BWWWB
+WWBWW
+WWBWW
+BWWWB
+WWBWW
+WWBWW
+BWWWB
+);
+
+art!(NUMERAL_NINE, 5,
+BWWWB
+WWBWW
+WWBWW
+BWWWW
+BBBWW
+BBBWW
+BWWWB
+);
+
+pub const NUMERALS: [(u32, &[u32]); 10] = [
+    NUMERAL_ZERO,
+    NUMERAL_ONE,
+    NUMERAL_TWO,
+    NUMERAL_THREE,
+    NUMERAL_FOUR,
+    NUMERAL_FIVE,
+    NUMERAL_SIX,
+    NUMERAL_SEVEN,
+    NUMERAL_EIGHT,
+    NUMERAL_NINE,
+];
+
+// Small 3x5 numerals, e.g. for tuplet numbers
+art!(SMALL_NUMERAL_ZERO, 3,
+WWW
+WBW
+WBW
+WBW
+WWW
+);
+
+art!(SMALL_NUMERAL_ONE, 3,
+BWB
+WWB
+BWB
+BWB
+WWW
+);
+
+art!(SMALL_NUMERAL_TWO, 3,
+WWW
+BBW
+WWW
+WBB
+WWW
+);
+
+art!(SMALL_NUMERAL_THREE, 3,
+WWW
+BBW
+BWW
+BBW
+WWW
+);
+
+art!(SMALL_NUMERAL_FOUR, 3,
+WBW
+WBW
+WWW
+BBW
+BBW
+);
+
+art!(SMALL_NUMERAL_FIVE, 3,
+WWW
+WBB
+WWW
+BBW
+WWW
+);
+
+art!(SMALL_NUMERAL_SIX, 3,
+WWW
+WBB
+WWW
+WBW
+WWW
+);
+
+art!(SMALL_NUMERAL_SEVEN, 3,
+WWW
+BBW
+BWB
+BWB
+BWB
+);
+
+art!(SMALL_NUMERAL_EIGHT, 3,
+WWW
+WBW
+WWW
+WBW
+WWW
+);
+
+art!(SMALL_NUMERAL_NINE, 3,
+WWW
+WBW
+WWW
+BBW
+WWW
+);
+
+pub const SMALL_NUMERALS: [(u32, &[u32]); 10] = [
+    SMALL_NUMERAL_ZERO,
+    SMALL_NUMERAL_ONE,
+    SMALL_NUMERAL_TWO,
+    SMALL_NUMERAL_THREE,
+    SMALL_NUMERAL_FOUR,
+    SMALL_NUMERAL_FIVE,
+    SMALL_NUMERAL_SIX,
+    SMALL_NUMERAL_SEVEN,
+    SMALL_NUMERAL_EIGHT,
+    SMALL_NUMERAL_NINE,
+];
+
+// Row 6 is placed on the top staff line, the curl is centered on the G line in row 18
+art!(TREBLE_CLEF, 10,
+______WW__
+_____WBBW_
+_____WBBW_
+_____WBW__
+_____WW___
+_____W____
+____WW____
+___W_W____
+__W__W____
+__W__W____
+_W___W____
+_W___W____
+W____W____
+W____W____
+W____W____
+W___WWWW__
+W__WBWBBW_
+W_WBBWBBBW
+W_WBBWBBBW
+W_WBBWBBBW
+W__WBWBBW_
+_W__WWWW__
+__W__W____
+___WWW____
+_____W____
+_____W____
+__W__W____
+_WWW_W____
+__WWW_____
+);
+
+// Spans the staff, the center row 8 is placed on the C line
+art!(C_CLEF, 9,
+WWBWBWWW_
+WWBWBWBBW
+WWBWBBBBW
+WWBWBBBWW
+WWBWBBWW_
+WWBWBWWB_
+WWBWWWBB_
+WWBWWBBB_
+WWBWBBBB_
+WWBWWBBB_
+WWBWWWBB_
+WWBWBWWB_
+WWBWBBWW_
+WWBWBBBWW
+WWBWBBBBW
+WWBWBWBBW
+WWBWBWWW_
+);
+
 #[rustfmt::skip]
 // pub const EMPTY_NOTEHEAD: (u32, &[u32]) = (6, &[
 //     pix!("_BBBB_"),
@@ -268,6 +776,204 @@ pub const BEAT_A: (u32, &[u32]) = (3, &[
     pix!("W_W"),
 ]);
 
+#[rustfmt::skip]
+pub const BEAT_ZERO: (u32, &[u32]) = (3, &[
+    pix!("WWW"),
+    pix!("W_W"),
+    pix!("W_W"),
+    pix!("WWW"),
+]);
+
+#[rustfmt::skip]
+pub const BEAT_FIVE: (u32, &[u32]) = (3, &[
+    pix!("WWW"),
+    pix!("WW_"),
+    pix!("__W"),
+    pix!("WW_"),
+]);
+
+#[rustfmt::skip]
+pub const BEAT_SIX: (u32, &[u32]) = (3, &[
+    pix!("W__"),
+    pix!("WWW"),
+    pix!("W_W"),
+    pix!("WWW"),
+]);
+
+#[rustfmt::skip]
+pub const BEAT_SEVEN: (u32, &[u32]) = (3, &[
+    pix!("WWW"),
+    pix!("__W"),
+    pix!("_W_"),
+    pix!("_W_"),
+]);
+
+#[rustfmt::skip]
+pub const BEAT_EIGHT: (u32, &[u32]) = (3, &[
+    pix!("WWW"),
+    pix!("_W_"),
+    pix!("W_W"),
+    pix!("WWW"),
+]);
+
+#[rustfmt::skip]
+pub const BEAT_NINE: (u32, &[u32]) = (3, &[
+    pix!("WWW"),
+    pix!("W_W"),
+    pix!("WWW"),
+    pix!("__W"),
+]);
+
+pub const BEAT_NUMBERS: [(u32, &[u32]); 10] = [
+    BEAT_ZERO, BEAT_ONE, BEAT_TWO, BEAT_THREE, BEAT_FOUR, BEAT_FIVE, BEAT_SIX, BEAT_SEVEN,
+    BEAT_EIGHT, BEAT_NINE,
+];
+
+// Characters of text such as chord symbols, five pixels high.
+art!(TEXT_A, 3,
+_W_
+W_W
+WWW
+W_W
+W_W
+);
+
+art!(TEXT_B, 3,
+WW_
+W_W
+WW_
+W_W
+WW_
+);
+
+art!(TEXT_C, 3,
+_WW
+W__
+W__
+W__
+_WW
+);
+
+art!(TEXT_D, 3,
+WW_
+W_W
+W_W
+W_W
+WW_
+);
+
+art!(TEXT_E, 3,
+WWW
+W__
+WW_
+W__
+WWW
+);
+
+art!(TEXT_F, 3,
+WWW
+W__
+WW_
+W__
+W__
+);
+
+art!(TEXT_G, 3,
+_WW
+W__
+W_W
+W_W
+_WW
+);
+
+art!(TEXT_LOWER_A, 3,
+___
+___
+_WW
+W_W
+_WW
+);
+
+art!(TEXT_LOWER_B, 3,
+W__
+W__
+WW_
+W_W
+WW_
+);
+
+art!(TEXT_LOWER_D, 3,
+__W
+__W
+_WW
+W_W
+_WW
+);
+
+art!(TEXT_LOWER_I, 1,
+W
+_
+W
+W
+W
+);
+
+art!(TEXT_LOWER_J, 2,
+_W
+__
+_W
+_W
+W_
+);
+
+art!(TEXT_LOWER_M, 5,
+_____
+_____
+WWWW_
+W_W_W
+W_W_W
+);
+
+art!(TEXT_LOWER_S, 3,
+___
+_WW
+W__
+__W
+WW_
+);
+
+art!(TEXT_LOWER_U, 3,
+___
+___
+W_W
+W_W
+_WW
+);
+
+art!(TEXT_SHARP, 3,
+W_W
+WWW
+W_W
+WWW
+W_W
+);
+
+art!(TEXT_PLUS, 3,
+___
+_W_
+WWW
+_W_
+___
+);
+
+art!(TEXT_SLASH, 3,
+__W
+__W
+_W_
+W__
+W__
+);
+
 art!(PAUSED, 6,
 WWBBWW
 WWBBWW
@@ -407,3 +1113,58 @@ where
 
     Ok(width)
 }
+
+/// Glyph of a character of text, `None` for characters without one. Supports the letters and
+/// signs of chord symbols: A to G, a, b, d, i, j, m, s, u, digits, #, + and /.
+pub fn text_glyph(character: char) -> Option<(u32, &'static [u32])> {
+    let glyph = match character {
+        'A' => TEXT_A,
+        'B' => TEXT_B,
+        'C' => TEXT_C,
+        'D' => TEXT_D,
+        'E' => TEXT_E,
+        'F' => TEXT_F,
+        'G' => TEXT_G,
+        'a' => TEXT_LOWER_A,
+        'b' => TEXT_LOWER_B,
+        'd' => TEXT_LOWER_D,
+        'i' => TEXT_LOWER_I,
+        'j' => TEXT_LOWER_J,
+        'm' => TEXT_LOWER_M,
+        's' => TEXT_LOWER_S,
+        'u' => TEXT_LOWER_U,
+        '#' => TEXT_SHARP,
+        '+' => TEXT_PLUS,
+        '/' => TEXT_SLASH,
+        '0'..='9' => SMALL_NUMERALS[character as usize - '0' as usize],
+        _ => return None,
+    };
+
+    Some(glyph)
+}
+
+/// Draws the text with a pixel between the characters, characters without a glyph are left out.
+/// Returns the width used.
+pub fn draw_text<D>(target: &mut D, position: Point, text: &str) -> Result<u32, D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let mut x = 0;
+    for glyph in text.chars().filter_map(text_glyph) {
+        draw_symbol(target, position + Point::new(x, 0), glyph)?;
+        x += glyph.0 as i32 + 1;
+    }
+
+    Ok((x - 1).max(0) as u32)
+}
+
+/// The width used by `draw_text`.
+pub fn text_width(text: &str) -> u32 {
+    let width: u32 = text
+        .chars()
+        .filter_map(text_glyph)
+        .map(|(width, _)| width + 1)
+        .sum();
+
+    width.saturating_sub(1)
+}
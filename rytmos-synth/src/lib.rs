@@ -1,5 +1,10 @@
 #![no_std]
 pub mod commands;
 pub mod effect;
+#[cfg(feature = "std")]
+pub mod midi;
 pub mod synth;
 pub mod wavetables;
+
+#[cfg(feature = "std")]
+extern crate std;
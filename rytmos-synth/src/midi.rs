@@ -0,0 +1,51 @@
+use std::vec::Vec;
+
+use fixed::types::U4F4;
+use rytmos_engrave::{midi::MidiImport, staff::Ticks};
+
+use crate::commands::{Command, CommandMessage};
+
+/// MIDI velocity that plays at a velocity of 1, as mezzo forte does.
+const MEZZO_FORTE_VELOCITY: u16 = 64;
+
+/// Commands that play the notes of a MIDI track on the synth at the given address, with the
+/// time at which to send each of them. Notes are released by playing them at a velocity of zero,
+/// and the tempo is set at the start and wherever it changes.
+pub fn commands(import: &MidiImport, address: u32) -> Vec<(Ticks, Command)> {
+    let command = |message| Command { address, message };
+    let mut commands = Vec::new();
+
+    for &(time, tempo) in &import.tempos {
+        let sixteenths_per_minute = 4 * 60_000_000 / tempo.max(1);
+        let tempo = sixteenths_per_minute.min(u16::MAX as u32) as u16;
+        commands.push((time, command(CommandMessage::SetTempo(tempo))));
+    }
+
+    for note in &import.notes {
+        // Rounded to sixteenths, but never to zero as that releases the note
+        let sixteenths =
+            (u16::from(note.velocity) * 16 + MEZZO_FORTE_VELOCITY / 2) / MEZZO_FORTE_VELOCITY;
+        let velocity = U4F4::from_bits(sixteenths as u8).max(U4F4::from_bits(1));
+        commands.push((
+            note.start,
+            command(CommandMessage::Play(note.note, velocity)),
+        ));
+        commands.push((
+            note.start + note.length,
+            command(CommandMessage::Play(note.note, U4F4::ZERO)),
+        ));
+    }
+
+    // At the same time the tempo is set first, and notes are released before others are played,
+    // so that a note that is played again is not released right away
+    commands.sort_by_key(|&(time, command)| {
+        let order = match command.message {
+            CommandMessage::SetTempo(_) => 0,
+            CommandMessage::Play(_, velocity) if velocity == U4F4::ZERO => 1,
+            _ => 2,
+        };
+        (time, order)
+    });
+
+    commands
+}
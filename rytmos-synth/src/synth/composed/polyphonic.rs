@@ -106,8 +106,8 @@ where
             }
         } else {
             warn!(
-                "No synth available to play {} {}",
-                note.to_midi_code(),
+                "No synth available to play {:?} {}",
+                note,
                 velocity.to_bits()
             )
         }
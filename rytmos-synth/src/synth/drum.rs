@@ -58,10 +58,7 @@ impl Synth for DrumSynth {
             WEAK_NOTE => self.weak_synth.play(note, velocity),
             CYMBAL_NOTE => self.cymbal_synth.play(note, velocity),
             note => {
-                warn!(
-                    "Unimplemented note {} requested of drum synth",
-                    note.to_midi_code()
-                );
+                warn!("Unimplemented note {:?} requested of drum synth", note);
                 self.kick_synth.play(note, velocity); // fallback
             }
         }
@@ -0,0 +1,104 @@
+#![cfg(feature = "std")]
+
+use fixed::types::U4F4;
+use rytmos_engrave::{
+    c, e,
+    midi::{MidiImport, MidiNote},
+    staff::{Duration, Ticks},
+};
+use rytmos_synth::{
+    commands::{Command, CommandMessage},
+    midi,
+};
+
+#[test]
+fn test_midi_commands() {
+    let import = MidiImport {
+        notes: vec![
+            MidiNote {
+                start: Ticks(0),
+                length: Ticks(480),
+                note: c!(3),
+                velocity: 64,
+            },
+            MidiNote {
+                start: Ticks(480),
+                length: Ticks(240),
+                note: c!(3),
+                velocity: 96,
+            },
+            MidiNote {
+                start: Ticks(480),
+                length: Ticks(480),
+                note: e!(3),
+                velocity: 32,
+            },
+        ],
+        resolution: Duration::Sixteenth,
+        key: None,
+        time_signature: None,
+        tempos: vec![(Ticks(0), 500_000), (Ticks(480), 600_000)],
+    };
+
+    let command = |message| Command {
+        address: 3,
+        message,
+    };
+    let play = |velocity: f32| U4F4::from_num(velocity);
+
+    assert_eq!(
+        midi::commands(&import, 3),
+        vec![
+            (Ticks(0), command(CommandMessage::SetTempo(480))),
+            (Ticks(0), command(CommandMessage::Play(c!(3), play(1.)))),
+            (Ticks(480), command(CommandMessage::SetTempo(400))),
+            (Ticks(480), command(CommandMessage::Play(c!(3), play(0.)))),
+            (Ticks(480), command(CommandMessage::Play(c!(3), play(1.5)))),
+            (Ticks(480), command(CommandMessage::Play(e!(3), play(0.5)))),
+            (Ticks(720), command(CommandMessage::Play(c!(3), play(0.)))),
+            (Ticks(960), command(CommandMessage::Play(e!(3), play(0.)))),
+        ]
+    );
+}
+
+#[test]
+fn test_midi_commands_keep_soft_notes() {
+    let velocities = |velocities: &[u8]| {
+        let import = MidiImport {
+            notes: velocities
+                .iter()
+                .enumerate()
+                .map(|(index, &velocity)| MidiNote {
+                    start: Ticks(index as u32 * 480),
+                    length: Ticks(240),
+                    note: c!(3),
+                    velocity,
+                })
+                .collect(),
+            resolution: Duration::Sixteenth,
+            key: None,
+            time_signature: None,
+            tempos: vec![],
+        };
+
+        midi::commands(&import, 0)
+            .into_iter()
+            .filter_map(|(_, command)| match command.message {
+                CommandMessage::Play(_, velocity) if velocity != U4F4::ZERO => Some(velocity),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // A velocity of zero would release the note instead of playing it
+    assert_eq!(
+        velocities(&[1, 2, 3, 6, 127]),
+        vec![
+            U4F4::from_bits(1),
+            U4F4::from_bits(1),
+            U4F4::from_bits(1),
+            U4F4::from_bits(2),
+            U4F4::lit("2"),
+        ]
+    );
+}
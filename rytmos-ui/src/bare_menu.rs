@@ -96,7 +96,7 @@ impl BareMenu {
         let metronome_position = position + Point { x: 79, y: 0 };
 
         if self.metronome_enabled {
-            let beat = (self.synth_controller.beat() * 4.) as u64;
+            let beat = self.synth_controller.beat().sixteenths();
 
             let symbol = match beat {
                 0 => rytmos_symbols::METRONOME_CENTER,
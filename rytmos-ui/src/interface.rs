@@ -92,13 +92,19 @@ impl Interface {
         // self.analysis.step(self.ringing);
         self.music = play_def.to_music(&Vec::new()).unwrap(); // TODO: only recalc on changed toggle switch?
 
-        self.staff.draw(
-            target,
-            &[
-                StaffElement::Clef(Clef::Bass),
-                StaffElement::Music(&self.music),
-            ],
-        )?;
+        let elements = [
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&self.music),
+        ];
+
+        // Follow along with the pattern by highlighting the note that is playing
+        let synth_controller = &self.menu.synth_controller;
+        if synth_controller.playing() {
+            self.staff
+                .draw_highlighted(target, &elements, synth_controller.beat())?;
+        } else {
+            self.staff.draw(target, &elements)?;
+        }
 
         // self.analysis.draw(target, Point { x: 0, y: 50 })?;
         // self.states.draw(target, Point { x: 0, y: 0 })?;
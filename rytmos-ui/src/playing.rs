@@ -1,5 +1,5 @@
 use fixed::types::U4F4;
-use rytmos_engrave::{c, staff::Note};
+use rytmos_engrave::{c, interval::Interval, staff::Note};
 use rytmos_synth::commands::CommandMessage;
 
 use crate::interface::PlayingButtons;
@@ -87,15 +87,20 @@ pub struct ChromaticActionToCommand {
 
 impl ChromaticActionToCommand {
     pub fn new(string_tuning: Note) -> Self {
-        let code = string_tuning.to_midi_code();
+        // Spelled from the open string, e.g. A Bb B C C# or E F F# G G#. Above a string tuned to
+        // a double sharp that can need a triple sharp, spell those by their sound. Frets without
+        // a MIDI code play the open string.
+        let fret = |semitones: u8| {
+            (string_tuning + Interval::from_semitones(semitones)).unwrap_or_else(|| {
+                string_tuning
+                    .to_midi_code()
+                    .and_then(|code| code.checked_add(semitones))
+                    .map_or(string_tuning, Note::from_u8_sharp)
+            })
+        };
+
         Self {
-            notes: [
-                Note::from_u8_sharp(code),
-                Note::from_u8_sharp(code + 1),
-                Note::from_u8_sharp(code + 2),
-                Note::from_u8_sharp(code + 3),
-                Note::from_u8_sharp(code + 4),
-            ],
+            notes: [fret(0), fret(1), fret(2), fret(3), fret(4)],
         }
     }
 }
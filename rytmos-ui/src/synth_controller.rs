@@ -1,7 +1,10 @@
 use fixed::types::U4F4;
 use heapless::Vec;
 use log::info;
-use rytmos_engrave::{c, staff::Music};
+use rytmos_engrave::{
+    c,
+    staff::{Articulation, Duration, Dynamic, Hairpin, Music, Ticks},
+};
 use rytmos_synth::commands::{Command, CommandMessage};
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -34,6 +37,14 @@ pub struct SynthController {
 
 /// Given a certain music definition, translates that music to commands for a synth.
 impl SynthController {
+    /// Dynamic of music that doesn't start with one.
+    const DEFAULT_DYNAMIC: Dynamic = Dynamic::MezzoForte;
+    const NORMAL_FACTOR: U4F4 = U4F4::lit("1");
+    const ACCENT_FACTOR: U4F4 = U4F4::lit("1.5");
+    const GHOST_FACTOR: U4F4 = U4F4::lit("0.5");
+    /// Change in velocity over a hairpin that isn't followed by a dynamic.
+    const HAIRPIN_FACTOR: U4F4 = U4F4::lit("1.5");
+
     pub fn new(settings: SynthControllerSettings) -> Self {
         Self {
             settings,
@@ -99,55 +110,94 @@ impl SynthController {
         commands
     }
 
-    pub fn beat(&self) -> f64 {
-        (self.time % 16) as f64 / 4.
+    /// Position in the current measure.
+    pub fn beat(&self) -> Ticks {
+        Ticks::from_sixteenths((self.time % 16) as u32)
     }
 
+    /// Time advances a sixteenth per call, music that starts in between two sixteenths
+    /// (e.g. tuplets) is played at the sixteenth before it.
     pub fn command_for_time(&mut self) -> Vec<CommandMessage, 4> {
         if !self.settings.play_pattern || self.state == SynthControllerState::Stopped {
             return Vec::new();
         }
 
-        // Current time indexed in sixteenths, looping over the measure we're playing
-        let t16 = (self.time % ((self.settings.measures_silence as u64 + 1) * 16)) as f64;
-        let mut count16 = 0;
+        // Current time, looping over the measure we're playing
+        let t16 = self.time % ((self.settings.measures_silence as u64 + 1) * 16);
+        let now = Ticks::from_sixteenths(t16 as u32)..Ticks::from_sixteenths(t16 as u32 + 1);
+        let mut start = Ticks(0);
 
         let mut commands = Vec::new();
         let mut last_was_tie = false;
+        let mut dynamic = Self::dynamic_velocity(Self::DEFAULT_DYNAMIC);
+        // Start and end of the hairpin the music is in, with their velocities
+        let mut hairpin: Option<((Ticks, U4F4), (Ticks, U4F4))> = None;
+        let mut factor = Self::NORMAL_FACTOR;
 
         let beat = self.beat();
 
         if self.settings.metronome {
-            if beat == 0.0 {
+            if beat == Ticks(0) {
                 commands.push(CommandMessage::Tick(true)).unwrap();
-            } else if [1.0, 2.0, 3.0].contains(&beat) {
+            } else if beat % Duration::Quarter.ticks() == Ticks(0) {
                 commands.push(CommandMessage::Tick(false)).unwrap();
             }
         }
 
-        for &music in self.music.iter() {
+        for (index, &music) in self.music.iter().enumerate() {
+            let level = match hairpin {
+                Some(hairpin) => Self::hairpin_velocity(hairpin, start),
+                None => dynamic,
+            };
+            let velocity = level.saturating_mul(factor);
+
             match music {
                 Music::Note(note, dur) => {
-                    if t16 == count16 as f64 && !last_was_tie {
-                        commands
-                            .push(CommandMessage::Play(note, U4F4::from_num(1.)))
-                            .unwrap();
+                    if now.contains(&start) && !last_was_tie {
+                        commands.push(CommandMessage::Play(note, velocity)).unwrap();
                         break;
                     }
-                    count16 += (dur.value() * 4.) as u64;
+                    start += dur.ticks();
                     last_was_tie = false;
+                    factor = Self::NORMAL_FACTOR;
+                }
+                Music::Chord(chord, dur) => {
+                    if now.contains(&start) && !last_was_tie {
+                        for &note in chord.notes() {
+                            // Notes that don't fit next to a metronome tick are dropped
+                            let _ = commands.push(CommandMessage::Play(note, velocity));
+                        }
+                        break;
+                    }
+                    start += dur.ticks();
+                    last_was_tie = false;
+                    factor = Self::NORMAL_FACTOR;
                 }
                 Music::Rest(dur) => {
-                    if t16 == count16 as f64 && !last_was_tie {
+                    if now.contains(&start) && !last_was_tie {
                         commands
-                            .push(CommandMessage::Play(c!(0), U4F4::from_num(0.)))
+                            .push(CommandMessage::Play(c!(0), U4F4::ZERO))
                             .unwrap();
                         break;
                     }
-                    count16 += (dur.value() as u64) * 4;
+                    start += dur.ticks();
                     last_was_tie = false;
+                    factor = Self::NORMAL_FACTOR;
                 }
                 Music::Tie => last_was_tie = true,
+                // Slurred notes are played like any other note, chord symbols are not played
+                Music::Slur | Music::ChordSymbol(_) => {}
+                Music::Articulation(Articulation::Accent) => factor = Self::ACCENT_FACTOR,
+                Music::Articulation(Articulation::Ghost) => factor = Self::GHOST_FACTOR,
+                Music::Articulation(_) => {}
+                Music::Dynamic(new_dynamic) => {
+                    dynamic = Self::dynamic_velocity(new_dynamic);
+                    hairpin = None;
+                }
+                Music::Hairpin(new_hairpin) => {
+                    let end = self.hairpin_end(index, start, new_hairpin, level);
+                    hairpin = Some(((start, level), end));
+                }
             }
         }
 
@@ -157,4 +207,56 @@ impl SynthController {
 
         commands
     }
+
+    fn dynamic_velocity(dynamic: Dynamic) -> U4F4 {
+        match dynamic {
+            Dynamic::Pianissimo => U4F4::lit("0.25"),
+            Dynamic::Piano => U4F4::lit("0.5"),
+            Dynamic::MezzoPiano => U4F4::lit("0.75"),
+            Dynamic::MezzoForte => U4F4::lit("1"),
+            Dynamic::Forte => U4F4::lit("1.5"),
+            Dynamic::Fortissimo => U4F4::lit("2"),
+        }
+    }
+
+    /// Time and velocity at which the hairpin at the given index ends: at the next dynamic, or
+    /// at the end of the music if no dynamic follows it.
+    fn hairpin_end(
+        &self,
+        index: usize,
+        start: Ticks,
+        hairpin: Hairpin,
+        velocity: U4F4,
+    ) -> (Ticks, U4F4) {
+        let mut end = start;
+        for &music in &self.music[index + 1..] {
+            match music {
+                Music::Note(_, dur) | Music::Chord(_, dur) | Music::Rest(dur) => end += dur.ticks(),
+                Music::Dynamic(dynamic) => return (end, Self::dynamic_velocity(dynamic)),
+                _ => {}
+            }
+        }
+
+        match hairpin {
+            Hairpin::Crescendo => (end, velocity.saturating_mul(Self::HAIRPIN_FACTOR)),
+            Hairpin::Decrescendo => (end, velocity / Self::HAIRPIN_FACTOR),
+        }
+    }
+
+    /// Velocity at the given time, changing linearly from the start to the end of the hairpin.
+    /// Interpolates the raw fixed point values, the RP2040 has no floating point unit.
+    fn hairpin_velocity(
+        ((start, from), (end, to)): ((Ticks, U4F4), (Ticks, U4F4)),
+        time: Ticks,
+    ) -> U4F4 {
+        if end <= start {
+            return to;
+        }
+
+        let length = (end - start).0 as i32;
+        let elapsed = (time - start).0.min(end.0 - start.0) as i32;
+        let (from, to) = (from.to_bits() as i32, to.to_bits() as i32);
+
+        U4F4::from_bits((from + (to - from) * elapsed / length) as u8)
+    }
 }
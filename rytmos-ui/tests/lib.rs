@@ -2,11 +2,14 @@ use std::sync::Once;
 
 use fixed::types::U4F4;
 use rytmos_engrave::{
-    a, cis, dis,
-    staff::{Duration, Music},
+    a, b, c, cis, dis,
+    staff::{Accidental, Articulation, Duration, Dynamic, Hairpin, Music, Note},
 };
 use rytmos_synth::commands::CommandMessage;
-use rytmos_ui::synth_controller::{SynthController, SynthControllerSettings};
+use rytmos_ui::{
+    playing::{ActionToCommand, ChromaticActionToCommand, PlayAction},
+    synth_controller::{SynthController, SynthControllerSettings},
+};
 
 static INIT: Once = Once::new();
 
@@ -78,3 +81,139 @@ fn test_command_for_time() {
     //     }
     // }
 }
+
+#[test]
+fn test_command_for_time_after_short_rest() {
+    init_logger();
+
+    let settings = SynthControllerSettings {
+        play_pattern: true,
+        measures_silence: 0,
+        metronome: false,
+    };
+
+    let music_sequence = vec![
+        Music::Rest(Duration::DottedEighth),
+        Music::Note(a!(3), Duration::Sixteenth),
+        Music::Note(cis!(4), Duration::EighthTriplet),
+        Music::Note(dis!(4), Duration::EighthTriplet),
+    ];
+
+    let mut player = SynthController::new(settings);
+    player.set_music(heapless::Vec::from_iter(music_sequence));
+    player.play_or_stop_toggle();
+
+    let mut played = vec![];
+    for t in 0..8 {
+        for command in player.next_command() {
+            if let CommandMessage::Play(note, velocity) = command {
+                if velocity > U4F4::from_num(0.) {
+                    played.push((t, note));
+                }
+            }
+        }
+    }
+
+    // The second triplet starts a third of a beat in, which is played on the sixteenth before it
+    assert_eq!(played, vec![(3, a!(3)), (4, cis!(4)), (5, dis!(4))]);
+}
+
+#[test]
+fn test_command_for_time_follows_dynamics() {
+    init_logger();
+
+    let settings = SynthControllerSettings {
+        play_pattern: true,
+        measures_silence: 0,
+        metronome: false,
+    };
+
+    let music_sequence = vec![
+        Music::Dynamic(Dynamic::Piano),
+        Music::Hairpin(Hairpin::Crescendo),
+        Music::Note(a!(3), Duration::Quarter),
+        Music::Note(a!(3), Duration::Quarter),
+        Music::Dynamic(Dynamic::Forte),
+        Music::Note(a!(3), Duration::Quarter),
+        Music::Note(a!(3), Duration::Quarter),
+    ];
+
+    let mut player = SynthController::new(settings);
+    player.set_music(heapless::Vec::from_iter(music_sequence));
+    player.play_or_stop_toggle();
+
+    let mut velocities = vec![];
+    for _ in 0..16 {
+        for command in player.next_command() {
+            if let CommandMessage::Play(_, velocity) = command {
+                velocities.push(velocity);
+            }
+        }
+    }
+
+    // The crescendo goes from piano to forte over the first two beats
+    let expected: Vec<U4F4> = [0.5, 1., 1.5, 1.5].map(U4F4::from_num).to_vec();
+    assert_eq!(velocities, expected);
+}
+
+#[test]
+fn test_command_for_time_scales_articulations_and_decrescendos() {
+    init_logger();
+
+    let settings = SynthControllerSettings {
+        play_pattern: true,
+        measures_silence: 0,
+        metronome: false,
+    };
+
+    let music_sequence = vec![
+        Music::Dynamic(Dynamic::Piano),
+        Music::Articulation(Articulation::Accent),
+        Music::Note(a!(3), Duration::Quarter),
+        Music::Articulation(Articulation::Ghost),
+        Music::Note(a!(3), Duration::Quarter),
+        Music::Hairpin(Hairpin::Decrescendo),
+        Music::Note(a!(3), Duration::Quarter),
+        Music::Note(a!(3), Duration::Quarter),
+    ];
+
+    let mut player = SynthController::new(settings);
+    player.set_music(heapless::Vec::from_iter(music_sequence));
+    player.play_or_stop_toggle();
+
+    let mut velocities = vec![];
+    for _ in 0..16 {
+        for command in player.next_command() {
+            if let CommandMessage::Play(_, velocity) = command {
+                velocities.push(velocity);
+            }
+        }
+    }
+
+    // Without a dynamic after it the decrescendo ends at two thirds of piano, in sixteenths
+    let expected: Vec<U4F4> = [0.75, 0.25, 0.5, 0.4375].map(U4F4::from_num).to_vec();
+    assert_eq!(velocities, expected);
+}
+
+#[test]
+fn test_chromatic_frets_at_the_top_of_the_range() {
+    let played = |string_tuning| {
+        let frets = ChromaticActionToCommand::new(string_tuning);
+        [PlayAction::PlayOpen, PlayAction::PlayFret2].map(|action| match frets.translate(action) {
+            Some(CommandMessage::Play(note, _)) => note,
+            _ => panic!("{action:?} doesn't play"),
+        })
+    };
+
+    assert_eq!(played(a!(3)), [a!(3), b!(3)]);
+
+    // A major second above B double sharp is a C triple sharp, spelled by its sound
+    let b_double_sharp = Note::B(Accidental::DoubleSharp, 3);
+    assert_eq!(played(b_double_sharp), [b_double_sharp, dis!(4)]);
+
+    // Without a MIDI code it plays the open string
+    let highest = Note::B(Accidental::DoubleSharp, 20);
+    assert_eq!(played(highest), [highest, highest]);
+    assert_eq!(highest.to_midi_code(), None);
+    assert_eq!(c!(-1).to_midi_code(), Some(0));
+}
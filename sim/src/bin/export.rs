@@ -0,0 +1,170 @@
+//! Exports an example of the simulator, or a score file, as an SVG or PNG image, e.g. for
+//! documentation, or as MusicXML to open it in a notation program:
+//!
+//! `cargo run -p sim --bin export -- <example|input> <output.svg|output.png|output.musicxml> [--scale <n>] [--tab]`
+//!
+//! Input files ending in `.musicxml` or `.xml` are read as MusicXML, other files as the text
+//! notation of `rytmos_engrave::notation`.
+
+use std::{env, fs, path::Path, process::ExitCode};
+
+use embedded_graphics::geometry::{Point, Size};
+use rytmos_engrave::{
+    bitmap::Bitmap,
+    musicxml, notation,
+    staff::{Clef, Staff, StaffElement},
+    tab::{TabStaff, Tuning},
+};
+use sim::examples::Examples;
+
+/// Size of the display of the simulator.
+const WIDTH: usize = 128;
+const HEIGHT: usize = 64;
+const SIZE: Size = Size::new(WIDTH as u32, HEIGHT as u32);
+/// Most music symbols read from a file in the text notation.
+const NOTATION_CAPACITY: usize = 256;
+const DEFAULT_SCALE: u32 = 4;
+
+const USAGE: &str =
+    "usage: export <example|input> <output.svg|output.png|output.musicxml> [--scale <n>] [--tab]";
+
+struct Arguments {
+    /// Name of an example, or path of a score file
+    input: String,
+    output: String,
+    scale: u32,
+    tab: bool,
+}
+
+impl Arguments {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut scale = DEFAULT_SCALE;
+        let mut tab = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scale" => {
+                    scale = args
+                        .next()
+                        .and_then(|scale| scale.parse().ok())
+                        .filter(|&scale| scale > 0)
+                        .ok_or("--scale needs a positive number")?;
+                }
+                "--tab" => tab = true,
+                _ => positional.push(arg),
+            }
+        }
+
+        let [input, output] = <[String; 2]>::try_from(positional).map_err(|_| USAGE)?;
+
+        Ok(Self {
+            input,
+            output,
+            scale,
+            tab,
+        })
+    }
+}
+
+fn main() -> ExitCode {
+    let arguments = match Arguments::parse(env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let examples = Examples::new();
+    let staffs = examples.staffs();
+    let input = Path::new(&arguments.input);
+    let text;
+    let part;
+    let music;
+    let elements: Vec<StaffElement> = if input.is_file() {
+        text = match fs::read_to_string(input) {
+            Ok(text) => text,
+            Err(error) => {
+                eprintln!("{}: {error}", input.display());
+                return ExitCode::FAILURE;
+            }
+        };
+
+        match input.extension().and_then(|extension| extension.to_str()) {
+            Some("musicxml" | "xml") => {
+                part = match musicxml::read(&text, None) {
+                    Ok(part) => part,
+                    Err(error) => {
+                        eprintln!("{}: {error}", input.display());
+                        return ExitCode::FAILURE;
+                    }
+                };
+                for unsupported in &part.unsupported {
+                    eprintln!(
+                        "left out {} in measure {}",
+                        unsupported.element, unsupported.measure
+                    );
+                }
+                part.staff_elements()
+            }
+            _ => {
+                music = match notation::parse::<NOTATION_CAPACITY>(&text) {
+                    Ok(music) => music,
+                    Err(error) => {
+                        let (line, column) = error.line_column(&text);
+                        eprintln!("{}:{line}:{column}: {}", input.display(), error.kind);
+                        return ExitCode::FAILURE;
+                    }
+                };
+                vec![StaffElement::Clef(Clef::Bass), StaffElement::Music(&music)]
+            }
+        }
+    } else if let Some((_, elements)) = staffs.iter().find(|(name, _)| *name == arguments.input) {
+        elements.clone()
+    } else {
+        let names: Vec<_> = staffs.iter().map(|(name, _)| *name).collect();
+        eprintln!(
+            "no file or example named {}, choose one of: {}",
+            arguments.input,
+            names.join(", ")
+        );
+        return ExitCode::FAILURE;
+    };
+
+    let mut image: Bitmap<WIDTH, HEIGHT> = Bitmap::new();
+    // Drawing into an image does not fail
+    let Ok(_) = if arguments.tab {
+        TabStaff::new(SIZE.width, Point::zero(), Tuning::FOUR_STRING_BASS)
+            .draw(&mut image, &elements)
+    } else {
+        Staff::new(SIZE.width, Point::zero()).draw(&mut image, &elements)
+    };
+
+    let output = Path::new(&arguments.output);
+    let written = match output.extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => fs::write(output, image.to_svg(arguments.scale)).map_err(|e| e.to_string()),
+        Some("png") => fs::File::create(output)
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                image
+                    .write_png(file, arguments.scale)
+                    .map_err(|e| e.to_string())
+            }),
+        Some("musicxml") => musicxml::write(&elements)
+            .map_err(|e| e.to_string())
+            .and_then(|xml| fs::write(output, xml).map_err(|e| e.to_string())),
+        _ => Err(format!(
+            "{} is not an .svg, .png or .musicxml file",
+            output.display()
+        )),
+    };
+
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
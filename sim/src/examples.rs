@@ -0,0 +1,598 @@
+//! Hand-written examples of what the engraver draws. Shown by the simulator, and compared
+//! against golden images by the tests of rytmos-engrave.
+
+use rytmos_engrave::staff::{
+    self, Accidental, Articulation, Chord, ChordQuality, ChordSymbol, Clef, Dynamic, Hairpin, Key,
+    Music, Note, StaffElement, TimeSignature,
+};
+
+/// Music of the examples that cannot be written in place, as it is not a constant.
+pub struct Examples {
+    chord_symbols: [Music; 11],
+    chords: [Music; 5],
+    /// A moving line over a held root
+    upper_voice: [Music; 6],
+    lower_voice: [Music; 4],
+}
+
+impl Default for Examples {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Examples {
+    pub fn new() -> Self {
+        Self {
+            chord_symbols: [
+                Music::ChordSymbol(ChordSymbol::new(
+                    Note::A(Accidental::Natural, 2),
+                    ChordQuality::Minor7,
+                )),
+                Music::Note(Note::A(Accidental::Natural, 1), staff::Duration::Quarter),
+                Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Note(Note::G(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::ChordSymbol(ChordSymbol::new(
+                    Note::D(Accidental::Natural, 2),
+                    ChordQuality::Dominant7,
+                )),
+                Music::Note(Note::D(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::F(Accidental::Sharp, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::ChordSymbol(
+                    ChordSymbol::new(Note::G(Accidental::Natural, 2), ChordQuality::Major)
+                        .over(Note::B(Accidental::Natural, 1)),
+                ),
+                Music::Note(Note::B(Accidental::Natural, 1), staff::Duration::Quarter),
+            ],
+            chords: [
+                Music::Chord(
+                    Chord::new(&[
+                        Note::C(Accidental::Natural, 4),
+                        Note::E(Accidental::Natural, 4),
+                        Note::G(Accidental::Natural, 4),
+                    ])
+                    .unwrap(),
+                    staff::Duration::Quarter,
+                ),
+                // The G is a second away from both, and moves to the other side of the stem
+                Music::Chord(
+                    Chord::new(&[
+                        Note::F(Accidental::Natural, 4),
+                        Note::G(Accidental::Natural, 4),
+                        Note::A(Accidental::Natural, 4),
+                    ])
+                    .unwrap(),
+                    staff::Duration::Quarter,
+                ),
+                Music::Chord(
+                    Chord::new(&[
+                        Note::C(Accidental::Sharp, 5),
+                        Note::E(Accidental::Flat, 5),
+                        Note::G(Accidental::Sharp, 5),
+                    ])
+                    .unwrap(),
+                    staff::Duration::Eighth,
+                ),
+                Music::Chord(
+                    Chord::new(&[
+                        Note::B(Accidental::Natural, 4),
+                        Note::D(Accidental::Natural, 5),
+                    ])
+                    .unwrap(),
+                    staff::Duration::Eighth,
+                ),
+                Music::Chord(
+                    Chord::new(&[
+                        Note::A(Accidental::Natural, 3),
+                        Note::B(Accidental::Flat, 3),
+                    ])
+                    .unwrap(),
+                    staff::Duration::Quarter,
+                ),
+            ],
+            upper_voice: [
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Quarter),
+                Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::G(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Rest(staff::Duration::Quarter),
+                Music::Note(Note::F(Accidental::Sharp, 3), staff::Duration::Eighth),
+                Music::Note(Note::G(Accidental::Natural, 3), staff::Duration::Eighth),
+            ],
+            lower_voice: [
+                Music::Note(Note::C(Accidental::Natural, 2), staff::Duration::Half),
+                Music::Tie,
+                Music::Note(Note::C(Accidental::Natural, 2), staff::Duration::Quarter),
+                Music::Rest(staff::Duration::Quarter),
+            ],
+        }
+    }
+
+    /// The examples in the order the simulator shows them, with the name of their golden image.
+    pub fn staffs(&self) -> Vec<(&'static str, Vec<StaffElement<'_>>)> {
+        let music1 = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Note(
+                    Note::A(Accidental::Natural, 2),
+                    staff::Duration::DottedEighth,
+                ),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Eighth),
+                Music::Note(Note::F(Accidental::Sharp, 3), staff::Duration::Eighth),
+                Music::Tie,
+                Music::Note(Note::F(Accidental::Sharp, 3), staff::Duration::Eighth),
+                Music::Note(Note::D(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::G(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::F(Accidental::Sharp, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Flat, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Sixteenth),
+            ]),
+        ];
+
+        let all_rests = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Rest(staff::Duration::Whole),
+                Music::Rest(staff::Duration::Half),
+                Music::Rest(staff::Duration::Quarter),
+                Music::Rest(staff::Duration::Eighth),
+                Music::Rest(staff::Duration::Sixteenth),
+            ]),
+        ];
+
+        let sixteenths = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+            ]),
+        ];
+
+        let beamed_sixteenths_and_eighths = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Sixteenth),
+            ]),
+        ];
+
+        let disconnected_beams = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                // 1 e + a
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
+                // 2 e + a
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                // 3 e + a
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                // 4 e + a
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(
+                    Note::A(Accidental::Natural, 2),
+                    staff::Duration::DottedEighth,
+                ),
+            ]),
+        ];
+
+        let ties = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                // 1 e + a
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Tie,
+                // 2 e + a
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Tie,
+                // 3 e + a
+                Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::D(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(Note::F(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Tie,
+                // 4 e + a
+                Music::Note(Note::F(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Note(
+                    Note::A(Accidental::Natural, 3),
+                    staff::Duration::DottedEighth,
+                ),
+            ]),
+        ];
+
+        let dotted_rests = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Rest(staff::Duration::DottedHalf),
+                Music::Rest(staff::Duration::DottedQuarter),
+                Music::Rest(staff::Duration::DottedEighth),
+            ]),
+        ];
+
+        let dynamics = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Dynamic(Dynamic::Piano),
+                Music::Hairpin(Hairpin::Crescendo),
+                Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Quarter),
+                Music::Note(Note::G(Accidental::Natural, 2), staff::Duration::Quarter),
+                Music::Dynamic(Dynamic::Forte),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Quarter),
+                Music::Hairpin(Hairpin::Decrescendo),
+                Music::Note(Note::G(Accidental::Natural, 2), staff::Duration::Quarter),
+            ]),
+            StaffElement::Barline,
+            StaffElement::Music(&[
+                Music::Dynamic(Dynamic::MezzoPiano),
+                Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Half),
+                Music::Rest(staff::Duration::Half),
+            ]),
+        ];
+
+        let articulations = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Articulation(Articulation::Accent),
+                Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Quarter),
+                Music::Articulation(Articulation::Staccato),
+                Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Articulation(Articulation::Ghost),
+                Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Articulation(Articulation::Tenuto),
+                Music::Note(Note::G(Accidental::Natural, 2), staff::Duration::Quarter),
+                // Hammer-on
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Slur,
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Eighth),
+            ]),
+        ];
+
+        let thirty_seconds = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::TimeSignature(TimeSignature::new(2, 4).unwrap()),
+            StaffElement::Music(&[
+                Music::Note(
+                    Note::C(Accidental::Natural, 3),
+                    staff::Duration::ThirtySecond,
+                ),
+                Music::Note(
+                    Note::D(Accidental::Natural, 3),
+                    staff::Duration::ThirtySecond,
+                ),
+                Music::Note(
+                    Note::E(Accidental::Natural, 3),
+                    staff::Duration::ThirtySecond,
+                ),
+                Music::Note(
+                    Note::F(Accidental::Natural, 3),
+                    staff::Duration::ThirtySecond,
+                ),
+                Music::Note(Note::G(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(
+                    Note::A(Accidental::Natural, 2),
+                    staff::Duration::DottedSixteenth,
+                ),
+                Music::Note(
+                    Note::B(Accidental::Natural, 2),
+                    staff::Duration::ThirtySecond,
+                ),
+                Music::Rest(staff::Duration::ThirtySecond),
+                Music::Note(
+                    Note::C(Accidental::Natural, 3),
+                    staff::Duration::DoubleDottedEighth,
+                ),
+            ]),
+        ];
+
+        let long_durations = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Breve),
+                Music::Rest(staff::Duration::Breve),
+                Music::Note(
+                    Note::E(Accidental::Natural, 3),
+                    staff::Duration::DottedWhole,
+                ),
+                Music::Rest(staff::Duration::DottedWhole),
+                Music::Note(
+                    Note::G(Accidental::Natural, 2),
+                    staff::Duration::DoubleDottedHalf,
+                ),
+                Music::Rest(staff::Duration::DoubleDottedQuarter),
+            ]),
+        ];
+
+        let flags = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Rest(staff::Duration::Eighth),
+                Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Rest(staff::Duration::Eighth),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Quarter),
+            ]),
+        ];
+
+        // TODO: all this is an octave transposed (fix now, then make configurable once we synth)
+        let ledgers = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::D(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 2), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 1), staff::Duration::Sixteenth),
+                Music::Note(Note::G(Accidental::Natural, 1), staff::Duration::Sixteenth),
+                Music::Note(Note::F(Accidental::Natural, 1), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 4), staff::Duration::Sixteenth),
+                Music::Note(Note::D(Accidental::Natural, 4), staff::Duration::Sixteenth),
+                Music::Note(Note::E(Accidental::Natural, 4), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::G(Accidental::Natural, 4), staff::Duration::Sixteenth),
+                Music::Note(Note::A(Accidental::Natural, 4), staff::Duration::Sixteenth),
+                Music::Rest(staff::Duration::Sixteenth),
+                Music::Note(Note::C(Accidental::Natural, 5), staff::Duration::Sixteenth),
+            ]),
+        ];
+
+        let treble = vec![
+            StaffElement::Clef(Clef::Treble),
+            StaffElement::Music(&[
+                Music::Note(Note::C(Accidental::Natural, 4), staff::Duration::Eighth),
+                Music::Note(Note::E(Accidental::Natural, 4), staff::Duration::Eighth),
+                Music::Note(Note::G(Accidental::Natural, 4), staff::Duration::Eighth),
+                Music::Note(Note::B(Accidental::Natural, 4), staff::Duration::Eighth),
+                Music::Note(Note::D(Accidental::Natural, 5), staff::Duration::Quarter),
+                Music::Note(Note::A(Accidental::Natural, 5), staff::Duration::Quarter),
+            ]),
+        ];
+
+        let alto = vec![
+            StaffElement::Clef(Clef::Alto),
+            StaffElement::KeySignature(Key::BbMajor),
+            StaffElement::Music(&[
+                Music::Note(Note::C(Accidental::Natural, 4), staff::Duration::Quarter),
+                Music::Note(Note::F(Accidental::Natural, 3), staff::Duration::Quarter),
+                Music::Note(Note::B(Accidental::Flat, 3), staff::Duration::Quarter),
+                Music::Note(Note::F(Accidental::Natural, 4), staff::Duration::Quarter),
+            ]),
+        ];
+
+        let tenor = vec![
+            StaffElement::Clef(Clef::Tenor),
+            StaffElement::KeySignature(Key::AMajor),
+            StaffElement::Music(&[
+                Music::Note(Note::C(Accidental::Sharp, 4), staff::Duration::Quarter),
+                Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Quarter),
+                Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Quarter),
+                Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Quarter),
+            ]),
+        ];
+
+        let key_signatures = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::KeySignature(Key::EMajor),
+            StaffElement::Music(&[
+                Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Quarter),
+                Music::Note(Note::G(Accidental::Sharp, 2), staff::Duration::Quarter),
+                Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Quarter),
+                Music::Note(Note::D(Accidental::Sharp, 3), staff::Duration::Quarter),
+            ]),
+            StaffElement::KeySignature(Key::AbMajor),
+        ];
+
+        let accidentals = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::KeySignature(Key::DMajor),
+            StaffElement::Music(&[
+                Music::Note(Note::F(Accidental::Sharp, 2), staff::Duration::Eighth),
+                Music::Note(Note::F(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Note(Note::F(Accidental::Natural, 2), staff::Duration::Eighth),
+                Music::Note(Note::F(Accidental::Sharp, 2), staff::Duration::Eighth),
+                Music::Note(Note::B(Accidental::Flat, 2), staff::Duration::Eighth),
+                Music::Tie,
+                Music::Note(Note::B(Accidental::Flat, 2), staff::Duration::Eighth),
+                Music::Note(
+                    Note::G(Accidental::DoubleSharp, 2),
+                    staff::Duration::Quarter,
+                ),
+            ]),
+        ];
+
+        let compound_meter = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::TimeSignature(TimeSignature::new(6, 8).unwrap()),
+            StaffElement::Music(&[
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::D(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::F(Accidental::Natural, 3), staff::Duration::Quarter),
+                Music::Note(Note::G(Accidental::Natural, 3), staff::Duration::Eighth),
+            ]),
+        ];
+
+        let odd_meter = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::TimeSignature(TimeSignature::new(7, 8).unwrap()),
+            StaffElement::Music(&[
+                // 2 + 2 + 3
+                Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::D(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::F(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::G(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Eighth),
+                Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Eighth),
+            ]),
+        ];
+
+        let triplets = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Music(&[
+                Music::Note(
+                    Note::C(Accidental::Natural, 3),
+                    staff::Duration::EighthTriplet,
+                ),
+                Music::Note(
+                    Note::D(Accidental::Natural, 3),
+                    staff::Duration::EighthTriplet,
+                ),
+                Music::Note(
+                    Note::E(Accidental::Natural, 3),
+                    staff::Duration::EighthTriplet,
+                ),
+                Music::Note(
+                    Note::C(Accidental::Natural, 4),
+                    staff::Duration::EighthTriplet,
+                ),
+                Music::Rest(staff::Duration::EighthTriplet),
+                Music::Note(
+                    Note::E(Accidental::Natural, 3),
+                    staff::Duration::EighthTriplet,
+                ),
+                Music::Note(
+                    Note::G(Accidental::Natural, 2),
+                    staff::Duration::QuarterTriplet,
+                ),
+                Music::Note(
+                    Note::A(Accidental::Natural, 2),
+                    staff::Duration::QuarterTriplet,
+                ),
+                Music::Note(
+                    Note::B(Accidental::Natural, 2),
+                    staff::Duration::QuarterTriplet,
+                ),
+            ]),
+        ];
+
+        let quintuplets = vec![
+            StaffElement::Clef(Clef::Treble),
+            StaffElement::Music(&[
+                Music::Note(
+                    Note::G(Accidental::Natural, 4),
+                    staff::Duration::SixteenthQuintuplet,
+                ),
+                Music::Note(
+                    Note::A(Accidental::Natural, 4),
+                    staff::Duration::SixteenthQuintuplet,
+                ),
+                Music::Note(
+                    Note::B(Accidental::Natural, 4),
+                    staff::Duration::SixteenthQuintuplet,
+                ),
+                Music::Note(
+                    Note::C(Accidental::Natural, 5),
+                    staff::Duration::SixteenthQuintuplet,
+                ),
+                Music::Note(
+                    Note::D(Accidental::Natural, 5),
+                    staff::Duration::SixteenthQuintuplet,
+                ),
+                Music::Note(
+                    Note::E(Accidental::Natural, 4),
+                    staff::Duration::SixteenthTriplet,
+                ),
+                Music::Note(
+                    Note::F(Accidental::Natural, 4),
+                    staff::Duration::SixteenthTriplet,
+                ),
+                Music::Note(
+                    Note::G(Accidental::Natural, 4),
+                    staff::Duration::SixteenthTriplet,
+                ),
+                Music::Note(Note::C(Accidental::Natural, 5), staff::Duration::Eighth),
+                Music::Note(Note::C(Accidental::Natural, 5), staff::Duration::Half),
+            ]),
+        ];
+
+        let voices = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Voices(&self.upper_voice, &self.lower_voice),
+        ];
+
+        let chords = vec![
+            StaffElement::Clef(Clef::Treble),
+            StaffElement::Music(&self.chords),
+        ];
+
+        let chord_symbols = vec![
+            StaffElement::Clef(Clef::Bass),
+            StaffElement::Counting(true),
+            StaffElement::Music(&self.chord_symbols),
+        ];
+
+        vec![
+            ("chord_symbols", chord_symbols),
+            ("dynamics", dynamics),
+            ("articulations", articulations),
+            ("thirty_seconds", thirty_seconds),
+            ("long_durations", long_durations),
+            ("voices", voices),
+            ("chords", chords),
+            ("triplets", triplets),
+            ("quintuplets", quintuplets),
+            ("compound_meter", compound_meter),
+            ("odd_meter", odd_meter),
+            ("accidentals", accidentals),
+            ("key_signatures", key_signatures),
+            ("ledgers", ledgers),
+            ("treble", treble),
+            ("alto", alto),
+            ("tenor", tenor),
+            ("ties", ties),
+            ("disconnected_beams", disconnected_beams),
+            (
+                "beamed_sixteenths_and_eighths",
+                beamed_sixteenths_and_eighths,
+            ),
+            ("music1", music1),
+            ("all_rests", all_rests),
+            ("sixteenths", sixteenths),
+            ("dotted_rests", dotted_rests),
+            // ("all_quarters", all_quarters),
+            ("flags", flags),
+        ]
+    }
+}
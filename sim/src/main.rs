@@ -11,204 +11,50 @@ use embedded_graphics_simulator::{
     Window,
 };
 use env_logger::{Builder, Env};
-use log::LevelFilter;
-use rytmos_engrave::staff::{self, Accidental, Clef, Music, Note, Staff, StaffElement};
+use log::{info, LevelFilter};
+use rytmos_engrave::passage::Passage;
+use rytmos_engrave::staff::{
+    self, Accidental, Clef, Key, Music, Note, Spacing, Staff, TimeSignature,
+};
+use rytmos_engrave::tab::{TabStaff, Tuning};
+use sim::examples::Examples;
 
 fn main() -> Result<(), core::convert::Infallible> {
-    let music1 = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            Music::Note(
-                Note::A(Accidental::Natural, 2),
-                staff::Duration::DottedEighth,
-            ),
-            Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Eighth),
-            Music::Note(Note::F(Accidental::Sharp, 3), staff::Duration::Eighth),
-            Music::Tie,
-            Music::Note(Note::F(Accidental::Sharp, 3), staff::Duration::Eighth),
-            Music::Note(Note::D(Accidental::Natural, 3), staff::Duration::Eighth),
-            Music::Note(Note::G(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::F(Accidental::Sharp, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Flat, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Sixteenth),
-        ]),
-    ];
-
-    let all_rests = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            Music::Rest(staff::Duration::Whole),
-            Music::Rest(staff::Duration::Half),
-            Music::Rest(staff::Duration::Quarter),
-            Music::Rest(staff::Duration::Eighth),
-            Music::Rest(staff::Duration::Sixteenth),
-        ]),
-    ];
-
-    let sixteenths = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-        ]),
-    ];
-
-    let beamed_sixteenths_and_eighths = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 3), staff::Duration::Sixteenth),
-        ]),
-    ];
-
-    let disconnected_beams = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            // 1 e + a
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
-            // 2 e + a
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            // 3 e + a
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            // 4 e + a
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(
-                Note::A(Accidental::Natural, 2),
-                staff::Duration::DottedEighth,
-            ),
-        ]),
-    ];
-
-    let ties = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            // 1 e + a
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Eighth),
-            Music::Tie,
-            // 2 e + a
-            Music::Note(Note::B(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Eighth),
-            Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Tie,
-            // 3 e + a
-            Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
-            Music::Note(Note::D(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(Note::F(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Tie,
-            // 4 e + a
-            Music::Note(Note::F(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Note(
-                Note::A(Accidental::Natural, 3),
-                staff::Duration::DottedEighth,
-            ),
-        ]),
-    ];
-
-    let dotted_rests = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            Music::Rest(staff::Duration::DottedHalf),
-            Music::Rest(staff::Duration::DottedQuarter),
-            Music::Rest(staff::Duration::DottedEighth),
-        ]),
-    ];
-
-    let flags = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Eighth),
-            Music::Rest(staff::Duration::Eighth),
-            Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Eighth),
-            Music::Rest(staff::Duration::Eighth),
-            Music::Note(Note::A(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Quarter),
-        ]),
-    ];
-
-    // TODO: all this is an octave transposed (fix now, then make configurable once we synth)
-    let ledgers = vec![
-        StaffElement::Clef(Clef::Bass),
-        StaffElement::Music(&[
-            Music::Note(Note::E(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::D(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 2), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 1), staff::Duration::Sixteenth),
-            Music::Note(Note::G(Accidental::Natural, 1), staff::Duration::Sixteenth),
-            Music::Note(Note::F(Accidental::Natural, 1), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 4), staff::Duration::Sixteenth),
-            Music::Note(Note::D(Accidental::Natural, 4), staff::Duration::Sixteenth),
-            Music::Note(Note::E(Accidental::Natural, 4), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::G(Accidental::Natural, 4), staff::Duration::Sixteenth),
-            Music::Note(Note::A(Accidental::Natural, 4), staff::Duration::Sixteenth),
-            Music::Rest(staff::Duration::Sixteenth),
-            Music::Note(Note::C(Accidental::Natural, 5), staff::Duration::Sixteenth),
-        ]),
-    ];
-
-    let treble = vec![
-        StaffElement::Clef(Clef::Treble),
-        StaffElement::Music(&[Music::Note(
-            Note::A(Accidental::Natural, 1),
-            staff::Duration::Eighth,
-        )]),
-    ];
-
-    let examples = &[
-        ledgers,
-        treble,
-        ties,
-        disconnected_beams,
-        beamed_sixteenths_and_eighths,
-        music1,
-        all_rests,
-        sixteenths,
-        dotted_rests,
-        // all_quarters,
-        flags,
+    let examples = Examples::new();
+    let examples: Vec<_> = examples
+        .staffs()
+        .into_iter()
+        .map(|(_, elements)| elements)
+        .collect();
+
+    // Longer than a staff, scroll through its systems with the up and down keys
+    let passage_music = [
+        Music::Note(Note::C(Accidental::Natural, 3), staff::Duration::Half),
+        Music::Note(
+            Note::D(Accidental::Natural, 3),
+            staff::Duration::DottedQuarter,
+        ),
+        Music::Note(Note::F(Accidental::Sharp, 3), staff::Duration::Half),
+        Music::Note(Note::G(Accidental::Natural, 3), staff::Duration::Eighth),
+        Music::Rest(staff::Duration::Half),
+        Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Quarter),
+        Music::Tie,
+        Music::Note(Note::A(Accidental::Natural, 3), staff::Duration::Quarter),
+        Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+        Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+        Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+        Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+        Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+        Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Eighth),
+        Music::Note(Note::E(Accidental::Natural, 3), staff::Duration::Whole),
     ];
+    let passage = Passage::new(
+        &passage_music,
+        Clef::Bass,
+        Key::DMajor,
+        TimeSignature::new(3, 4).unwrap(),
+    )
+    .unwrap();
 
     Builder::from_env(Env::default().default_filter_or(LevelFilter::Trace.to_string())).init();
 
@@ -223,16 +69,32 @@ fn main() -> Result<(), core::convert::Infallible> {
     let mut window = Window::new("Rytmos", &output_settings);
 
     // let staff = Staff::new(display_size.width - 8, Point::new(4, 4));
-    let staff = Staff::new(display_size.width, Point::new(0, 0));
+    let mut staff = Staff::new(display_size.width, Point::new(0, 0));
+    let mut tab_staff = TabStaff::new(
+        display_size.width,
+        Point::new(0, 0),
+        Tuning::FOUR_STRING_BASS,
+    );
+    let mut proportional = false;
+    let mut tab = false;
 
     let mut example_idx = 0;
+    let systems = passage.systems(&staff).len();
+    let mut system_idx = 0;
 
     'main: loop {
         Rectangle::new(Point::zero(), display_size)
             .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
             .draw(&mut display)?;
 
-        staff.draw(&mut display, &examples[example_idx])?;
+        // The passage is shown after the examples
+        let layout = if example_idx < examples.len() && tab {
+            tab_staff.draw(&mut display, &examples[example_idx])?
+        } else if example_idx < examples.len() {
+            staff.draw(&mut display, &examples[example_idx])?
+        } else {
+            passage.draw_system(&mut display, &staff, system_idx)?
+        };
 
         window.update(&display);
 
@@ -242,10 +104,32 @@ fn main() -> Result<(), core::convert::Infallible> {
                     keycode,
                     keymod: _,
                     repeat,
-                } => {
-                    if let (Keycode::Space, false) = (keycode, repeat) {
-                        example_idx = (example_idx + 1) % examples.len();
+                } => match (keycode, repeat) {
+                    (Keycode::Space, false) => {
+                        example_idx = (example_idx + 1) % (examples.len() + 1);
+                    }
+                    (Keycode::Down, false) => {
+                        system_idx = (system_idx + 1).min(systems.saturating_sub(1));
+                    }
+                    (Keycode::Up, false) => system_idx = system_idx.saturating_sub(1),
+                    // Toggle between even and proportional spacing
+                    (Keycode::P, false) => {
+                        proportional = !proportional;
+                        let spacing = if proportional {
+                            Spacing::Proportional
+                        } else {
+                            Spacing::Even
+                        };
+                        staff = staff.with_spacing(spacing);
+                        tab_staff = tab_staff.with_spacing(spacing);
                     }
+                    // Toggle between the staff and tablature of the examples
+                    (Keycode::T, false) => tab = !tab,
+                    _ => (),
+                },
+                // Show what is under the mouse when clicking
+                SimulatorEvent::MouseButtonUp { point, .. } => {
+                    info!("{:?}", layout.hit(point));
                 }
                 SimulatorEvent::Quit => break 'main,
                 _ => (),